@@ -0,0 +1,261 @@
+use file::track::SMFTrackChunk;
+use file::track::data::event::EventType;
+use file::track::data::event::MidiEventType;
+use file::track::data::event::midi::ControllerChange;
+
+// Registered Parameter Numbers
+pub const RPN_PITCH_BEND_SENSITIVITY: u16 = 0x0000;
+pub const RPN_FINE_TUNING: u16 = 0x0001;
+pub const RPN_COARSE_TUNING: u16 = 0x0002;
+pub const RPN_TUNING_PROGRAM_CHANGE: u16 = 0x0003;
+pub const RPN_TUNING_BANK_SELECT: u16 = 0x0004;
+pub const RPN_MODULATION_DEPTH_RANGE: u16 = 0x0005;
+pub const RPN_NULL: u16 = 0x3FFF;
+
+pub fn rpn_name(parameter: u16) -> Option<&'static str> {
+    match parameter {
+        RPN_PITCH_BEND_SENSITIVITY => Some("Pitch Bend Sensitivity"),
+        RPN_FINE_TUNING => Some("Channel Fine Tuning"),
+        RPN_COARSE_TUNING => Some("Channel Coarse Tuning"),
+        RPN_TUNING_PROGRAM_CHANGE => Some("Tuning Program Change"),
+        RPN_TUNING_BANK_SELECT => Some("Tuning Bank Select"),
+        RPN_MODULATION_DEPTH_RANGE => Some("Modulation Depth Range"),
+        RPN_NULL => Some("Null"),
+        _ => None
+    }
+}
+
+// Pitch Bend Sensitivity value, MSB in semitones and LSB in cents
+pub fn pitch_bend_sensitivity_cents(value: u16) -> f64 {
+    f64::from(value >> 7) * 100.0 + f64::from(value & 0x7Fu16)
+}
+
+pub fn pitch_bend_sensitivity_value(cents: f64) -> u16 {
+    let cents: f64 = if cents < 0.0 { 0.0 } else { cents.round() };
+    let semitones: u16 = ((cents / 100.0).floor() as u16).min(127);
+    let rest: u16 = ((cents - f64::from(semitones) * 100.0) as u16).min(127);
+    (semitones << 7) | rest
+}
+
+// Fine Tuning, 0x2000 is A440, -100 to almost +100 cents
+pub fn fine_tuning_cents(value: u16) -> f64 {
+    (f64::from(value) - 8192.0) * 100.0 / 8192.0
+}
+
+// Coarse Tuning, only the MSB is used, 64 is no change
+pub fn coarse_tuning_semitones(value: u16) -> i8 {
+    ((value >> 7) as i16 - 64) as i8
+}
+
+// Parameter number and value of a Registered or Non-Registered Parameter, both 14 bits
+
+#[derive(Clone)]
+pub struct ParameterValue {
+    pub parameter: u16,
+    pub value: u16
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ParameterSelection {
+    None,
+    Registered(u16),
+    NonRegistered(u16)
+}
+
+// What a Data Entry related controller did to the selected parameter
+#[derive(Clone, Copy, PartialEq)]
+pub enum DataChange {
+    Coarse,  // Data Entry MSB (6), resets the LSB
+    Fine,  // Data Entry LSB (38)
+    Increment,  // Data Increment (96)
+    Decrement  // Data Decrement (97)
+}
+
+// Logical meaning of one or more Controller Changes
+
+#[derive(Clone)]
+pub enum ParameterEvent {
+    Registered { parameter: u16, value: u16, change: DataChange },
+    NonRegistered { parameter: u16, value: u16, change: DataChange },
+    // Controllers 0 to 31 paired with 32 to 63, the MSB resets the LSB
+    Controller14 { controller_number: u8, value: u16 },
+    // Every other controller
+    Controller { controller_number: u8, value: u8 }
+}
+
+impl ParameterEvent {
+    // Controller Changes sending this event, parameters are deselected afterwards
+    pub fn to_controllers(&self) -> Vec<ControllerChange> {
+        match *self {
+            ParameterEvent::Registered { parameter, value, change } => encode_parameter(true, parameter, value, change, true),
+            ParameterEvent::NonRegistered { parameter, value, change } => encode_parameter(false, parameter, value, change, true),
+            ParameterEvent::Controller14 { controller_number, value } => encode_14_bit(controller_number, value),
+            ParameterEvent::Controller { controller_number, value } => vec![cc(controller_number, value)]
+        }
+    }
+}
+
+fn cc(controller_number: u8, controller_value: u8) -> ControllerChange {
+    ControllerChange {
+        controller_number: controller_number & 0x7Fu8,
+        controller_value: controller_value & 0x7Fu8
+    }
+}
+
+// Selects the parameter then sends its value (or the increment / decrement), deselecting with the null RPN if asked
+pub fn encode_parameter(registered: bool, parameter: u16, value: u16, change: DataChange, deselect: bool) -> Vec<ControllerChange> {
+    let (msb, lsb) = if registered { (101u8, 100u8) } else { (99u8, 98u8) };
+    let mut out: Vec<ControllerChange> = vec![cc(msb, (parameter >> 7) as u8), cc(lsb, parameter as u8)];
+    match change {
+        DataChange::Coarse => out.push(cc(6, (value >> 7) as u8)),
+        DataChange::Fine => {
+            out.push(cc(6, (value >> 7) as u8));
+            out.push(cc(38, value as u8));
+        },
+        DataChange::Increment => out.push(cc(96, 0)),
+        DataChange::Decrement => out.push(cc(97, 0))
+    }
+    if deselect {
+        out.push(cc(101, 127));
+        out.push(cc(100, 127));
+    }
+    out
+}
+
+// MSB then LSB, controller_number must be between 0 and 31
+pub fn encode_14_bit(controller_number: u8, value: u16) -> Vec<ControllerChange> {
+    vec![cc(controller_number, (value >> 7) as u8), cc((controller_number & 0x1Fu8) + 32, value as u8)]
+}
+
+// Turns the Controller Changes of one channel into Parameter Events, remembering every parameter value
+
+#[derive(Clone)]
+pub struct ControllerDecoder {
+    pub selection: ParameterSelection,
+    pub rpn_values: Vec<ParameterValue>,
+    pub nrpn_values: Vec<ParameterValue>,
+    msbs: Vec<u8>  // Last MSB of controllers 0 to 31
+}
+
+impl ControllerDecoder {
+    pub fn new() -> ControllerDecoder {
+        ControllerDecoder {
+            selection: ParameterSelection::None,
+            rpn_values: Vec::new(),
+            nrpn_values: Vec::new(),
+            msbs: vec![0; 32]
+        }
+    }
+
+    pub fn rpn(&self, parameter: u16) -> Option<u16> {
+        self.rpn_values.iter().find(|p| p.parameter == parameter).map(|p| p.value)
+    }
+
+    pub fn nrpn(&self, parameter: u16) -> Option<u16> {
+        self.nrpn_values.iter().find(|p| p.parameter == parameter).map(|p| p.value)
+    }
+
+    // Parameter selection controllers give nothing, Data Entry without a selected parameter gives nothing either
+    pub fn feed(&mut self, controller: &ControllerChange) -> Option<ParameterEvent> {
+        let value: u8 = controller.controller_value & 0x7Fu8;
+        match controller.controller_number {
+            6 => self.change(DataChange::Coarse, |_| u16::from(value) << 7),
+            38 => self.change(DataChange::Fine, |current| (current & 0x3F80u16) | u16::from(value)),
+            96 => self.change(DataChange::Increment, |current| if current < 0x3FFFu16 { current + 1 } else { current }),
+            97 => self.change(DataChange::Decrement, |current| if current > 0 { current - 1 } else { current }),
+            98 => self.select(false, None, Some(value)),
+            99 => self.select(false, Some(value), None),
+            100 => self.select(true, None, Some(value)),
+            101 => self.select(true, Some(value), None),
+            number @ 0..=31 => {
+                self.msbs[number as usize] = value;
+                Some(ParameterEvent::Controller14 { controller_number: number, value: u16::from(value) << 7 })
+            },
+            number @ 32..=63 => {
+                let msb: u8 = self.msbs[(number - 32) as usize];
+                Some(ParameterEvent::Controller14 {
+                    controller_number: number - 32,
+                    value: (u16::from(msb) << 7) | u16::from(value)
+                })
+            },
+            number => Some(ParameterEvent::Controller { controller_number: number, value })
+        }
+    }
+
+    // Reset All Controllers deselects the current parameter
+    pub fn reset(&mut self) {
+        self.selection = ParameterSelection::None;
+        self.msbs = vec![0; 32];
+    }
+
+    fn select(&mut self, registered: bool, msb: Option<u8>, lsb: Option<u8>) -> Option<ParameterEvent> {
+        let current: u16 = match (self.selection, registered) {
+            (ParameterSelection::Registered(p), true) => p,
+            (ParameterSelection::NonRegistered(p), false) => p,
+            _ => RPN_NULL
+        };
+        let msb: u16 = msb.map(u16::from).unwrap_or(current >> 7) & 0x7Fu16;
+        let lsb: u16 = lsb.map(u16::from).unwrap_or(current) & 0x7Fu16;
+        let parameter: u16 = (msb << 7) | lsb;
+        // 127/127 is the null parameter, Data Entries are then ignored
+        self.selection = if registered {
+            ParameterSelection::Registered(parameter)
+        } else {
+            ParameterSelection::NonRegistered(parameter)
+        };
+        None
+    }
+
+    fn change<F: Fn(u16) -> u16>(&mut self, change: DataChange, apply: F) -> Option<ParameterEvent> {
+        match self.selection {
+            ParameterSelection::Registered(parameter) if parameter != RPN_NULL => {
+                let value: u16 = set_parameter(&mut self.rpn_values, parameter, apply);
+                Some(ParameterEvent::Registered { parameter, value, change })
+            },
+            ParameterSelection::NonRegistered(parameter) if parameter != RPN_NULL => {
+                let value: u16 = set_parameter(&mut self.nrpn_values, parameter, apply);
+                Some(ParameterEvent::NonRegistered { parameter, value, change })
+            },
+            _ => None
+        }
+    }
+}
+
+impl Default for ControllerDecoder {
+    fn default() -> ControllerDecoder {
+        ControllerDecoder::new()
+    }
+}
+
+fn set_parameter<F: Fn(u16) -> u16>(values: &mut Vec<ParameterValue>, parameter: u16, apply: F) -> u16 {
+    let current: u16 = values.iter().find(|p| p.parameter == parameter).map(|p| p.value).unwrap_or(0);
+    let value: u16 = apply(current) & 0x3FFFu16;
+    if let Some(p) = values.iter_mut().find(|p| p.parameter == parameter) {
+        p.value = value;
+        return value;
+    }
+    values.push(ParameterValue { parameter, value });
+    value
+}
+
+impl SMFTrackChunk {
+    // Every Parameter Event of this track with its absolute tick and channel
+    pub fn parameter_events(&self) -> Vec<(u64, u8, ParameterEvent)> {
+        let mut decoders: Vec<ControllerDecoder> = vec![ControllerDecoder::new(); 16];
+        let mut events: Vec<(u64, u8, ParameterEvent)> = Vec::new();
+        for (tick, event) in self.absolute_events() {
+            if let EventType::MidiEvent(ref midi_event) = event.event {
+                if let MidiEventType::ControllerChange(ref c) = midi_event.event {
+                    let channel: u8 = midi_event.channel & 0x0Fu8;
+                    if c.controller_number == 121 {
+                        decoders[channel as usize].reset();
+                    }
+                    if let Some(parameter_event) = decoders[channel as usize].feed(c) {
+                        events.push((tick, channel, parameter_event));
+                    }
+                }
+            }
+        }
+        events
+    }
+}
@@ -0,0 +1,353 @@
+use convert::parse_error;
+use convert::parse_number;
+use file::SMF;
+use file::header::SMFHeaderChunk;
+use file::header::data::MidiDivisionsType;
+use file::header::data::MidiFormat;
+use file::track::SMFTrackChunk;
+use file::track::data::event::Event;
+use file::track::data::event::EventType;
+use file::track::data::event::MetaEvent;
+use file::track::data::event::MetaEventType;
+use file::track::data::event::MidiEvent;
+use file::track::data::event::MidiEventType;
+use file::track::data::event::SysexEvent;
+use file::track::data::event::SysexEventType;
+use file::track::data::event::midi::ChannelKeyPressure;
+use file::track::data::event::midi::ControllerChange;
+use file::track::data::event::midi::NoteChange;
+use file::track::data::event::midi::PitchBend;
+use file::track::data::event::midi::PolyphonicKeyPressure;
+use file::track::data::event::midi::ProgramChange;
+use file::track::data::event::sysex::Sysex;
+use std::error::Error;
+use std::result::Result;
+
+// Record types of the Text Meta Events, by sub code byte
+const TEXT_TYPES: [(u8, &str); 7] = [
+    (0x01, "Text_t"),
+    (0x02, "Copyright_t"),
+    (0x03, "Title_t"),
+    (0x04, "Instrument_name_t"),
+    (0x05, "Lyric_t"),
+    (0x06, "Marker_t"),
+    (0x07, "Cue_point_t")
+];
+
+// Quotes are doubled, backslashes and control characters are escaped like midicsv does
+fn quote(text: &str) -> String {
+    let mut out: String = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\"\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 || (c as u32) == 0x7F => out.push_str(&format!("\\{:03o}", c as u32)),
+            c => out.push(c)
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn bytes(data: &[u8]) -> String {
+    let mut out: String = format!("{}", data.len());
+    for b in data {
+        out.push_str(&format!(", {}", b));
+    }
+    out
+}
+
+// Fields of a record, only quoted fields may contain commas
+fn split_fields(line: &str) -> Option<Vec<String>> {
+    let mut fields: Vec<String> = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.peek().map(|c| c.is_whitespace()).unwrap_or(false) {
+            chars.next();
+        }
+        let mut field: String = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            loop {
+                match chars.next()? {
+                    '"' if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    },
+                    '"' => break,
+                    '\\' => match chars.next()? {
+                        c if c.is_digit(8) => {
+                            let mut code: u32 = c.to_digit(8)?;
+                            for _ in 0..2 {
+                                match chars.peek().and_then(|c| c.to_digit(8)) {
+                                    Some(d) => {
+                                        code = code * 8 + d;
+                                        chars.next();
+                                    },
+                                    None => break
+                                }
+                            }
+                            field.push(::std::char::from_u32(code)?);
+                        },
+                        c => field.push(c)
+                    },
+                    c => field.push(c)
+                }
+            }
+            while chars.peek().map(|&c| c != ',').unwrap_or(false) {
+                chars.next();
+            }
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c == ',' {
+                    break;
+                }
+                field.push(c);
+                chars.next();
+            }
+            field = field.trim().to_string();
+        }
+        fields.push(field);
+        if chars.next().is_none() {
+            return Some(fields)
+        }
+    }
+}
+
+// Length then as many bytes
+fn parse_bytes(fields: &[String], line: usize) -> Result<Vec<u8>, Box<Error>> {
+    let length: usize = parse_number(fields.first().ok_or_else(|| parse_error(line, "Missing length"))?, line)?;
+    if fields.len() != length + 1 {
+        return Err(parse_error(line, "The number of bytes does not match the length"))
+    }
+    fields[1..].iter().map(|f| parse_number(f, line)).collect()
+}
+
+// Record type and parameters of an event, End of Track excluded
+fn event_record(event: &Event) -> Result<String, Box<Error>> {
+    Ok(match event.event {
+        EventType::MidiEvent(ref e) => {
+            let ch: u8 = e.channel;
+            match e.event {
+                MidiEventType::NoteOff(ref n) => format!("Note_off_c, {}, {}, {}", ch, n.key, n.velocity),
+                MidiEventType::NoteOn(ref n) => format!("Note_on_c, {}, {}, {}", ch, n.key, n.velocity),
+                MidiEventType::PolyphonicKeyPressure(ref p) => format!("Poly_aftertouch_c, {}, {}, {}", ch, p.key, p.pressure),
+                MidiEventType::ControllerChange(ref c) => format!("Control_c, {}, {}, {}", ch, c.controller_number, c.controller_value),
+                MidiEventType::ProgramChange(ref p) => format!("Program_c, {}, {}", ch, p.new_program_number),
+                MidiEventType::ChannelKeyPressure(ref p) => format!("Channel_aftertouch_c, {}, {}", ch, p.value),
+                MidiEventType::PitchBend(ref b) => format!("Pitch_bend_c, {}, {}", ch, b.value)
+            }
+        },
+        EventType::SysExEvent(ref e) => match e.event {
+            SysexEventType::F0SysexEvent(ref s) => format!("System_exclusive, {}", bytes(&s.data)),
+            SysexEventType::F7SysexEvent(ref s) => format!("System_exclusive_packet, {}", bytes(&s.data))
+        },
+        EventType::MetaEvent(ref m) => match m.event {
+            MetaEventType::SequenceNumber(ref n) => format!("Sequence_number, {}", n.sequence_number),
+            MetaEventType::TextEvent(ref t) | MetaEventType::CopyrightNotice(ref t) | MetaEventType::SequenceTrackName(ref t)
+            | MetaEventType::InstrumentName(ref t) | MetaEventType::Lyric(ref t) | MetaEventType::Marker(ref t)
+            | MetaEventType::CuePoint(ref t) => {
+                let sub_code_byte: u8 = m.event.sub_code_byte();
+                let name: &str = TEXT_TYPES.iter().find(|&&(b, _)| b == sub_code_byte).map(|&(_, n)| n).unwrap_or("Text_t");
+                format!("{}, {}", name, quote(&t.text))
+            },
+            MetaEventType::MIDIChannelPrefix(ref p) => format!("Channel_prefix, {}", p.channel),
+            MetaEventType::MIDIPort(ref p) => format!("MIDI_port, {}", p.port),
+            MetaEventType::EndOfTrack(_) => String::from("End_track"),
+            MetaEventType::SetTempo(ref t) => format!("Tempo, {}", t.tempo),
+            MetaEventType::SMTPEOffset(ref o) => format!("SMPTE_offset, {}, {}, {}, {}, {}", o.hour, o.minute, o.seconds, o.frames, o.hundred_of_frame),
+            MetaEventType::TimeSignature(ref t) => format!("Time_signature, {}, {}, {}, {}",
+                t.nominator, t.denominator, t.midi_ticks_per_metronome_tick, t.thing),
+            MetaEventType::KeySignature(ref k) => format!("Key_signature, {}, \"{}\"",
+                k.number_of_sharp_flats as i8, if k.major_key { "major" } else { "minor" }),
+            MetaEventType::SequencerSpecific(_) => format!("Sequencer_specific, {}", bytes(&m.event.data_bytes()?)),
+            // Program Name, Device Name and unknown events, midicsv has no record for them
+            _ => format!("Unknown_meta_event, {}, {}", m.event.sub_code_byte(), bytes(&m.event.data_bytes()?))
+        }
+    })
+}
+
+fn parse_event(kind: &str, fields: &[String], line: usize) -> Result<Event, Box<Error>> {
+    let number = |index: usize| -> Result<u32, Box<Error>> {
+        parse_number(fields.get(index).ok_or_else(|| parse_error(line, &format!("{} needs {} parameters", kind, index + 1)))?, line)
+    };
+    let midi = |event: MidiEventType| -> Result<Event, Box<Error>> {
+        let channel: u32 = number(0)?;
+        if channel > 15 {
+            return Err(parse_error(line, "Channels go from 0 to 15"))
+        }
+        Ok(Event::from_midi(MidiEvent::from_type(channel as u8, event)))
+    };
+    let byte = |index: usize| -> Result<u8, Box<Error>> {
+        let value: u32 = number(index)?;
+        if value > 0xFF {
+            return Err(parse_error(line, &format!("{} does not fit in a byte", value)))
+        }
+        Ok(value as u8)
+    };
+    // Data bytes of Midi Events stay under 0x80
+    let data_byte = |index: usize| -> Result<u8, Box<Error>> {
+        let value: u32 = number(index)?;
+        if value > 0x7F {
+            return Err(parse_error(line, &format!("{} is not between 0 and 127", value)))
+        }
+        Ok(value as u8)
+    };
+    let meta = |sub_code_byte: u8, data: &[u8]| -> Result<Event, Box<Error>> {
+        Ok(Event::from_meta(MetaEvent::from_bytes(sub_code_byte, data)?))
+    };
+    let sysex = |f0: bool| -> Result<Event, Box<Error>> {
+        let data: Vec<u8> = parse_bytes(fields, line)?;
+        let sysex = Sysex { length: data.len() as u32, data };
+        Ok(Event::from_sysex(SysexEvent {
+            event: if f0 { SysexEventType::F0SysexEvent(sysex) } else { SysexEventType::F7SysexEvent(sysex) }
+        }))
+    };
+    if let Some(&(sub_code_byte, _)) = TEXT_TYPES.iter().find(|&&(_, n)| n == kind) {
+        let text: &String = fields.first().ok_or_else(|| parse_error(line, "Missing text"))?;
+        return meta(sub_code_byte, text.as_bytes())
+    }
+    match kind {
+        "Note_off_c" => midi(MidiEventType::NoteOff(NoteChange { key: data_byte(1)?, velocity: data_byte(2)? })),
+        "Note_on_c" => midi(MidiEventType::NoteOn(NoteChange { key: data_byte(1)?, velocity: data_byte(2)? })),
+        "Poly_aftertouch_c" => midi(MidiEventType::PolyphonicKeyPressure(PolyphonicKeyPressure { key: data_byte(1)?, pressure: data_byte(2)? })),
+        "Control_c" => midi(MidiEventType::ControllerChange(ControllerChange { controller_number: data_byte(1)?, controller_value: data_byte(2)? })),
+        "Program_c" => midi(MidiEventType::ProgramChange(ProgramChange { new_program_number: data_byte(1)? })),
+        "Channel_aftertouch_c" => midi(MidiEventType::ChannelKeyPressure(ChannelKeyPressure { value: data_byte(1)? })),
+        "Pitch_bend_c" => {
+            let value: u32 = number(1)?;
+            if value > 0x3FFF {
+                return Err(parse_error(line, &format!("{} is not between 0 and 16383", value)))
+            }
+            midi(MidiEventType::PitchBend(PitchBend { value: value as u16 }))
+        },
+        "System_exclusive" => sysex(true),
+        "System_exclusive_packet" => sysex(false),
+        "Sequence_number" => {
+            let value: u32 = number(0)?;
+            meta(0x00, &[(value >> 8) as u8, value as u8])
+        },
+        "Channel_prefix" => meta(0x20, &[byte(0)?]),
+        "MIDI_port" => meta(0x21, &[byte(0)?]),
+        "End_track" => Ok(Event::end_of_track()),
+        "Tempo" => {
+            let tempo: u32 = number(0)?;
+            meta(0x51, &[(tempo >> 16) as u8, (tempo >> 8) as u8, tempo as u8])
+        },
+        "SMPTE_offset" => meta(0x54, &[byte(0)?, byte(1)?, byte(2)?, byte(3)?, byte(4)?]),
+        "Time_signature" => meta(0x58, &[byte(0)?, byte(1)?, byte(2)?, byte(3)?]),
+        "Key_signature" => {
+            let key: i8 = parse_number(fields.first().ok_or_else(|| parse_error(line, "Missing key"))?, line)?;
+            let mode: u8 = match fields.get(1).map(|f| f.as_str()) {
+                Some("major") => 0,
+                Some("minor") => 1,
+                _ => return Err(parse_error(line, "The mode must be \"major\" or \"minor\""))
+            };
+            meta(0x59, &[key as u8, mode])
+        },
+        "Sequencer_specific" => meta(0x7F, &parse_bytes(fields, line)?),
+        "Unknown_meta_event" => {
+            let sub_code_byte: u8 = byte(0)?;
+            meta(sub_code_byte, &parse_bytes(&fields[1..], line)?)
+        },
+        other => Err(parse_error(line, &format!("Unknown record type {}", other)))
+    }
+}
+
+impl SMF {
+    // Records of midicsv: track, time, type, then the parameters of the type
+    // Tracks are numbered from 1, times are absolute ticks and channels go from 0 to 15
+    pub fn to_csv(&self) -> Result<String, Box<Error>> {
+        let mut out: String = format!("0, 0, Header, {}, {}, {}\n",
+            self.header.format.number(), self.tracks.len(), self.header.division_system.raw());
+        for (index, track) in self.tracks.iter().enumerate() {
+            let number: usize = index + 1;
+            out.push_str(&format!("{}, 0, Start_track\n", number));
+            let mut tick: u64 = 0;
+            let mut ended: bool = false;
+            for event in &track.track_events {
+                tick += u64::from(event.delta_time);
+                out.push_str(&format!("{}, {}, {}\n", number, tick, event_record(&event.event)?));
+                if event.event.is_end_of_track() {
+                    ended = true;
+                    break;
+                }
+            }
+            if !ended {
+                out.push_str(&format!("{}, {}, End_track\n", number, tick));
+            }
+        }
+        out.push_str("0, 0, End_of_file\n");
+        Ok(out)
+    }
+
+    // Reads midicsv records, blank lines and lines starting with # are skipped
+    pub fn from_csv(text: &str) -> Result<SMF, Box<Error>> {
+        let mut header: Option<(MidiFormat, usize, MidiDivisionsType)> = None;
+        let mut tracks: Vec<Option<Vec<(u64, Event)>>> = Vec::new();
+        for (index, raw) in text.lines().enumerate() {
+            let line: usize = index + 1;
+            let content: &str = raw.trim();
+            if content.is_empty() || content.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<String> = split_fields(content).ok_or_else(|| parse_error(line, "Unterminated quoted text"))?;
+            if fields.len() < 3 {
+                return Err(parse_error(line, "Records start with a track, a time and a type"))
+            }
+            let track: usize = parse_number(&fields[0], line)?;
+            let tick: u64 = parse_number(&fields[1], line)?;
+            let parameters: &[String] = &fields[3..];
+            match fields[2].as_str() {
+                "Header" => {
+                    if parameters.len() != 3 {
+                        return Err(parse_error(line, "Header takes a format, a number of tracks and a division"))
+                    }
+                    let format: MidiFormat = MidiFormat::from_number(parse_number(&parameters[0], line)?)
+                        .ok_or_else(|| parse_error(line, "Invalid format"))?;
+                    let nb_tracks: u16 = parse_number(&parameters[1], line)?;
+                    header = Some((format, nb_tracks as usize, MidiDivisionsType::from_raw(parse_number(&parameters[2], line)?)));
+                },
+                "End_of_file" => break,
+                "Start_track" => {
+                    if track == 0 {
+                        return Err(parse_error(line, "Tracks are numbered from 1"))
+                    }
+                    // The Header tells how many tracks follow
+                    match header {
+                        Some((_, nb_tracks, _)) if track > nb_tracks => {
+                            return Err(parse_error(line, "Track number above the number of tracks of the Header"))
+                        },
+                        None => return Err(parse_error(line, "Start_track before the Header")),
+                        _ => ()
+                    }
+                    if tracks.len() < track {
+                        tracks.resize(track, None);
+                    }
+                    tracks[track - 1] = Some(Vec::new());
+                },
+                kind => {
+                    let events: &mut Vec<(u64, Event)> = match tracks.get_mut(track.wrapping_sub(1)) {
+                        Some(&mut Some(ref mut e)) => e,
+                        _ => return Err(parse_error(line, "Event before the Start_track of its track"))
+                    };
+                    events.push((tick, parse_event(kind, parameters, line)?));
+                }
+            }
+        }
+        let (format, _, division_system) = header.ok_or_else(|| parse_error(1, "Missing Header"))?;
+        let mut chunks: Vec<SMFTrackChunk> = Vec::with_capacity(tracks.len());
+        for events in tracks {
+            chunks.push(SMFTrackChunk::from_absolute_events(events.unwrap_or_else(Vec::new))?);
+        }
+        Ok(SMF {
+            header: SMFHeaderChunk {
+                length: 6,
+                format,
+                nb_tracks: chunks.len() as u16,
+                division_system
+            },
+            tracks: chunks
+        })
+    }
+}
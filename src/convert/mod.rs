@@ -0,0 +1,36 @@
+use std::error::Error;
+use std::result::Result;
+use std::fmt;
+
+pub mod rmid;
+pub mod xmi;
+pub mod mus;
+pub mod text;
+pub mod csv;
+
+// Error of the textual formats, text and csv
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,  // Starts at 1
+    pub message: String
+}
+
+impl Error for ParseError {
+    fn description(&self) -> &str {
+        "The text is not a valid textual MIDI file"
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+
+fn parse_error(line: usize, message: &str) -> Box<Error> {
+    Box::new(ParseError { line, message: String::from(message) })
+}
+
+fn parse_number<T: ::std::str::FromStr>(text: &str, line: usize) -> Result<T, Box<Error>> {
+    text.parse::<T>().map_err(|_| parse_error(line, &format!("Invalid number {}", text)))
+}
@@ -0,0 +1,312 @@
+use VLVRead;
+use VLVWrite;
+use ez_io::ReadE;
+use file::SMF;
+use file::header::SMFHeaderChunk;
+use file::header::data::MidiDivisionsType;
+use file::header::data::MidiFormat;
+use file::header::data::MidiTPQNDivisions;
+use file::tempo::TempoMap;
+use file::track::SMFTrackChunk;
+use file::track::data::event::Event;
+use file::track::data::event::EventType;
+use file::track::data::event::MetaEvent;
+use file::track::data::event::MetaEventType;
+use file::track::data::event::MidiEvent;
+use file::track::data::event::MidiEventType;
+use file::track::data::event::meta::SetTempo;
+use file::track::data::event::midi::ControllerChange;
+use file::track::data::event::midi::NoteChange;
+use file::track::data::event::midi::PitchBend;
+use file::track::data::event::midi::ProgramChange;
+use gm::GM_PERCUSSION_CHANNEL;
+use std::error::Error;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Write;
+use std::result::Result;
+use std::fmt;
+
+#[derive(Debug)]
+pub struct NotMusError;
+
+impl Error for NotMusError {
+    fn description(&self) -> &str {
+        "The file does not start with MUS and 0x1A"
+    }
+}
+
+impl fmt::Display for NotMusError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Not a MUS file")
+    }
+}
+
+#[derive(Debug)]
+pub struct TooManyMusChannelsError;
+
+impl Error for TooManyMusChannelsError {
+    fn description(&self) -> &str {
+        "MUS only has 15 channels for instruments besides the percussion channel"
+    }
+}
+
+impl fmt::Display for TooManyMusChannelsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Too many channels for MUS")
+    }
+}
+
+#[derive(Debug)]
+pub struct MusTooLongError;
+
+impl Error for MusTooLongError {
+    fn description(&self) -> &str {
+        "The score or the instrument list does not fit in the 16-bit sizes of the MUS header"
+    }
+}
+
+impl fmt::Display for MusTooLongError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Too long for MUS")
+    }
+}
+
+// MUS ticks are fixed at 140 Hz, 70 ticks per quarter note at 500 000 microseconds per quarter note keep them as they are
+pub const MUS_TICKS_PER_SECOND: f64 = 140.0;
+pub const MUS_TICKS_PER_QUARTER_NOTE: u16 = 70;
+pub const MUS_TEMPO: u32 = 500_000;
+pub const MUS_PERCUSSION_CHANNEL: u8 = 15;
+
+// MIDI controllers of MUS controllers 1 to 9, 0 is the Program Change
+const MUS_CONTROLLERS: [u8; 10] = [0, 0, 1, 7, 10, 11, 91, 93, 64, 67];
+// MIDI controllers of MUS system events 10 to 14
+const MUS_SYSTEM_EVENTS: [u8; 5] = [120, 123, 126, 127, 121];
+
+// Percussion notes are listed in the instruments of the header as key + 100
+const MUS_PERCUSSION_INSTRUMENT_OFFSET: u16 = 100;
+
+const NOTE_OFF_VELOCITY: u8 = 0x40;
+const DEFAULT_NOTE_VOLUME: u8 = 127;
+
+// Percussion channels trade places, the others stay
+fn swap_percussion_channel(channel: u8) -> u8 {
+    if channel == MUS_PERCUSSION_CHANNEL {
+        GM_PERCUSSION_CHANNEL
+    } else if channel == GM_PERCUSSION_CHANNEL {
+        MUS_PERCUSSION_CHANNEL
+    } else {
+        channel
+    }
+}
+
+fn midi(channel: u8, event: MidiEventType) -> Event {
+    Event::from_midi(MidiEvent::from_type(channel, event))
+}
+
+fn controller(channel: u8, controller_number: u8, controller_value: u8) -> Event {
+    midi(channel, MidiEventType::ControllerChange(ControllerChange { controller_number, controller_value }))
+}
+
+// MUS event type and data bytes of a MIDI event, Play Note keeps the velocity as its second byte
+fn to_mus_event(event: &MidiEventType) -> Option<(u8, Vec<u8>)> {
+    match *event {
+        MidiEventType::NoteOff(ref n) => Some((0, vec![n.key])),
+        MidiEventType::NoteOn(ref n) if n.velocity == 0 => Some((0, vec![n.key])),
+        MidiEventType::NoteOn(ref n) => Some((1, vec![n.key, n.velocity])),
+        MidiEventType::PitchBend(ref b) => Some((2, vec![(b.value >> 6) as u8])),
+        MidiEventType::ControllerChange(ref c) => {
+            if let Some(n) = MUS_SYSTEM_EVENTS.iter().position(|&m| m == c.controller_number) {
+                Some((3, vec![10 + n as u8]))
+            } else {
+                MUS_CONTROLLERS.iter().skip(1).position(|&m| m == c.controller_number)
+                    .map(|n| (4, vec![1 + n as u8, c.controller_value & 0x7Fu8]))
+            }
+        },
+        MidiEventType::ProgramChange(ref p) => Some((4, vec![0, p.new_program_number])),
+        _ => None
+    }
+}
+
+impl SMF {
+    // Format 0 file with the MUS channel 15 on the percussion channel 9 and the other way around
+    pub fn read_mus<R: Read>(reader: &mut R) -> Result<SMF, Box<Error>> {
+        let mut data: Vec<u8> = Vec::new();
+        reader.read_to_end(&mut data)?;
+        if (data.len() < 16) || (&data[..4] != b"MUS\x1A") {
+            return Err(Box::new(NotMusError))
+        }
+        let mut header = Cursor::new(&data[4..8]);
+        let score_length: usize = header.read_le_to_u16()? as usize;
+        let score_start: usize = header.read_le_to_u16()? as usize;
+        let end: usize = (score_start + score_length).min(data.len());
+        let mut reader = Cursor::new(&data[score_start.min(end)..end]);
+        let mut tick: u64 = 0;
+        let mut volumes: [u8; 16] = [DEFAULT_NOTE_VOLUME; 16];
+        let mut events: Vec<(u64, Event)> = vec![
+            (0, Event::from_meta(MetaEvent::from_type(MetaEventType::SetTempo(SetTempo { tempo: MUS_TEMPO }))?))
+        ];
+        // Stops at Score End, or at the end of the data when it is missing
+        while let Ok(descriptor) = reader.read_to_u8() {
+            let mus_channel: u8 = descriptor & 0x0Fu8;
+            let channel: u8 = swap_percussion_channel(mus_channel);
+            let event: Option<Event> = match (descriptor >> 4) & 0x07u8 {
+                0 => {
+                    let key: u8 = reader.read_to_u8()? & 0x7Fu8;
+                    Some(midi(channel, MidiEventType::NoteOff(NoteChange { key, velocity: NOTE_OFF_VELOCITY })))
+                },
+                1 => {
+                    // The volume is only given when bit 7 of the key is set, the last one is used otherwise
+                    let key: u8 = reader.read_to_u8()?;
+                    if key & 0x80u8 != 0 {
+                        volumes[mus_channel as usize] = reader.read_to_u8()? & 0x7Fu8;
+                    }
+                    Some(midi(channel, MidiEventType::NoteOn(NoteChange { key: key & 0x7Fu8, velocity: volumes[mus_channel as usize] })))
+                },
+                2 => {
+                    let amount: u8 = reader.read_to_u8()?;
+                    Some(midi(channel, MidiEventType::PitchBend(PitchBend { value: u16::from(amount) << 6 })))
+                },
+                3 => {
+                    let number: u8 = reader.read_to_u8()?;
+                    if (10..=14).contains(&number) {
+                        Some(controller(channel, MUS_SYSTEM_EVENTS[(number - 10) as usize], 0))
+                    } else {
+                        None
+                    }
+                },
+                4 => {
+                    let number: u8 = reader.read_to_u8()?;
+                    let value: u8 = reader.read_to_u8()? & 0x7Fu8;
+                    match number {
+                        0 => Some(midi(channel, MidiEventType::ProgramChange(ProgramChange { new_program_number: value }))),
+                        n if n <= 9 => Some(controller(channel, MUS_CONTROLLERS[number as usize], value)),
+                        _ => None
+                    }
+                },
+                6 => {
+                    events.push((tick, Event::end_of_track()));
+                    break;
+                },
+                // End of Measure and unused types carry no data
+                _ => None
+            };
+            if let Some(event) = event {
+                events.push((tick, event));
+            }
+            if descriptor & 0x80u8 != 0 {
+                tick += u64::from(reader.read_vlv()?.data);
+            }
+        }
+        Ok(SMF {
+            header: SMFHeaderChunk {
+                length: 6,
+                format: MidiFormat::SingleTrack,
+                nb_tracks: 1,
+                division_system: MidiDivisionsType::TicksPerQuarterNote(MidiTPQNDivisions {
+                    ticks_per_quarter_note: MUS_TICKS_PER_QUARTER_NOTE
+                })
+            },
+            tracks: vec![SMFTrackChunk::from_absolute_events(events)?]
+        })
+    }
+
+    // Channels are given MUS channels in the order they are first used, the percussion channel 9 goes on 15
+    // Events MUS has no equivalent for are left out, times follow the tempo map
+    pub fn write_mus<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        let map: TempoMap = self.tempo_map();
+        let mut events: Vec<(u64, Event)> = Vec::new();
+        for track in &self.tracks {
+            events.extend(track.absolute_events());
+        }
+        events.sort_by_key(|&(tick, _)| tick);
+        let mut allocated: [Option<u8>; 16] = [None; 16];
+        let mut next_channel: u8 = 0;
+        let mut instruments: Vec<u16> = Vec::new();
+        let mut mus_events: Vec<(u64, u8, Vec<u8>)> = Vec::new();
+        for &(tick, ref event) in &events {
+            let midi_event: &MidiEvent = match event.event {
+                EventType::MidiEvent(ref e) => e,
+                _ => continue
+            };
+            let (kind, data) = match to_mus_event(&midi_event.event) {
+                Some(e) => e,
+                None => continue
+            };
+            let mus_channel: u8 = match allocated[midi_event.channel as usize] {
+                Some(c) => c,
+                None => {
+                    let c: u8 = if midi_event.channel == GM_PERCUSSION_CHANNEL {
+                        MUS_PERCUSSION_CHANNEL
+                    } else {
+                        if next_channel >= MUS_PERCUSSION_CHANNEL {
+                            return Err(Box::new(TooManyMusChannelsError))
+                        }
+                        next_channel += 1;
+                        next_channel - 1
+                    };
+                    allocated[midi_event.channel as usize] = Some(c);
+                    c
+                }
+            };
+            let instrument: Option<u16> = match (kind, mus_channel == MUS_PERCUSSION_CHANNEL) {
+                (1, true) => Some(u16::from(data[0]) + MUS_PERCUSSION_INSTRUMENT_OFFSET),
+                (4, false) if data[0] == 0 => Some(u16::from(data[1])),
+                _ => None
+            };
+            if let Some(i) = instrument {
+                if !instruments.contains(&i) {
+                    instruments.push(i);
+                }
+            }
+            let mus_tick: u64 = (map.tick_to_seconds(tick) * MUS_TICKS_PER_SECOND).round() as u64;
+            mus_events.push((mus_tick, (kind << 4) | mus_channel, data));
+        }
+        instruments.sort();
+        let end_tick: u64 = (map.tick_to_seconds(self.end_tick()) * MUS_TICKS_PER_SECOND).round() as u64;
+        let mut score: Vec<u8> = Vec::new();
+        // A leading delay needs an event to hang on, End of Measure does nothing
+        if mus_events.first().map(|&(t, _, _)| t).unwrap_or(end_tick) > 0 {
+            mus_events.insert(0, (0, 0x50u8, Vec::new()));
+        }
+        let mut volumes: [u8; 16] = [DEFAULT_NOTE_VOLUME; 16];
+        let mut index: usize = 0;
+        while index < mus_events.len() {
+            let (tick, descriptor, ref data) = mus_events[index];
+            let next_tick: u64 = mus_events.get(index + 1).map(|&(t, _, _)| t).unwrap_or(end_tick.max(tick));
+            let delayed: bool = next_tick > tick;
+            score.push(descriptor | if delayed { 0x80u8 } else { 0 });
+            if descriptor >> 4 == 1 {
+                // The volume is only written when it changes
+                let channel: usize = (descriptor & 0x0Fu8) as usize;
+                if data[1] != volumes[channel] {
+                    volumes[channel] = data[1];
+                    score.push(data[0] | 0x80u8);
+                    score.push(data[1]);
+                } else {
+                    score.push(data[0]);
+                }
+            } else {
+                score.extend_from_slice(data);
+            }
+            if delayed {
+                score.write_vlv((next_tick - tick) as u32)?;
+            }
+            index += 1;
+        }
+        score.push(0x60u8);
+        let primary_channels: u16 = u16::from(next_channel);
+        // The score starts right after the instrument list, both offsets are 16 bits
+        let score_start: usize = 16 + 2 * instruments.len();
+        if (score.len() > 0xFFFF) | (score_start > 0xFFFF) {
+            return Err(Box::new(MusTooLongError))
+        }
+        let mut out: Vec<u8> = b"MUS\x1A".to_vec();
+        for value in [score.len() as u16, score_start as u16, primary_channels, 0, instruments.len() as u16, 0].iter().chain(instruments.iter()) {
+            out.extend_from_slice(&[*value as u8, (*value >> 8) as u8]);
+        }
+        out.extend_from_slice(&score);
+        writer.write_all(&out)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,226 @@
+use ez_io::ReadE;
+use file::SMF;
+use std::error::Error;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::result::Result;
+use std::fmt;
+
+#[derive(Debug)]
+pub struct NotRmidError;
+
+impl Error for NotRmidError {
+    fn description(&self) -> &str {
+        "The file is not a RIFF file of the RMID form"
+    }
+}
+
+impl fmt::Display for NotRmidError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Not an RMID file")
+    }
+}
+
+#[derive(Debug)]
+pub struct MissingDataChunkError;
+
+impl Error for MissingDataChunkError {
+    fn description(&self) -> &str {
+        "The RMID file has no data chunk holding the Standard MIDI File"
+    }
+}
+
+impl fmt::Display for MissingDataChunkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "No data chunk")
+    }
+}
+
+#[derive(Debug)]
+pub struct ChunkTooLongError;
+
+impl Error for ChunkTooLongError {
+    fn description(&self) -> &str {
+        "A chunk of the RMID file is longer than what is left of the RIFF chunk or of its list"
+    }
+}
+
+impl fmt::Display for ChunkTooLongError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Chunk too long")
+    }
+}
+
+// Entry of the INFO list, like INAM (name), IART (artist), ICOP (copyright) or ICMT (comments)
+
+#[derive(Clone)]
+pub struct InfoTag {
+    pub id: String,  // 4 characters
+    pub text: String
+}
+
+// Standard MIDI File wrapped in a RIFF container
+
+#[derive(Clone)]
+pub struct Rmid {
+    pub smf: SMF,
+    pub info: Vec<InfoTag>,
+    pub dls: Option<Vec<u8>>  // Embedded DLS bank, a complete RIFF DLS file
+}
+
+// Looks for "RIFF" at the current position without moving it
+pub fn is_rmid<R: Read + Seek>(reader: &mut R) -> Result<bool, Box<Error>> {
+    let position: u64 = reader.seek(SeekFrom::Current(0))?;
+    let mut magic: [u8; 4] = [0; 4];
+    let found: bool = reader.read_exact(&mut magic).is_ok() && &magic == b"RIFF";
+    reader.seek(SeekFrom::Start(position))?;
+    Ok(found)
+}
+
+fn write_chunk<W: Write>(writer: &mut W, id: &[u8], data: &[u8]) -> Result<(), Box<Error>> {
+    writer.write_all(id)?;
+    let length: u32 = data.len() as u32;
+    writer.write_all(&[length as u8, (length >> 8) as u8, (length >> 16) as u8, (length >> 24) as u8])?;
+    writer.write_all(data)?;
+    // Chunks are padded to an even size
+    if data.len() % 2 == 1 {
+        writer.write_all(&[0])?;
+    }
+    Ok(())
+}
+
+fn read_info_list(data: &[u8]) -> Result<Vec<InfoTag>, Box<Error>> {
+    let mut reader = Cursor::new(data);
+    let mut tags: Vec<InfoTag> = Vec::new();
+    while (reader.position() + 8) <= data.len() as u64 {
+        let id: String = reader.read_to_string_n(4)?;
+        let length: u32 = reader.read_le_to_u32()?;
+        // Same as the chunks of the RIFF chunk, the length must fit in what is left of the list
+        if u64::from(length) > data.len() as u64 - reader.position() {
+            return Err(Box::new(ChunkTooLongError))
+        }
+        let mut text: Vec<u8> = vec![0; length as usize];
+        reader.read_exact(&mut text)?;
+        if length % 2 == 1 {
+            reader.seek(SeekFrom::Current(1))?;
+        }
+        // Zero terminated
+        let end: usize = text.iter().position(|&b| b == 0).unwrap_or(text.len());
+        tags.push(InfoTag {
+            id,
+            text: String::from_utf8_lossy(&text[..end]).into_owned()
+        });
+    }
+    Ok(tags)
+}
+
+impl Rmid {
+    pub fn new(smf: SMF) -> Rmid {
+        Rmid {
+            smf,
+            info: Vec::new(),
+            dls: None
+        }
+    }
+
+    pub fn read<R: Read + Seek>(reader: &mut R) -> Result<Rmid, Box<Error>> {
+        if reader.read_to_string_n(4)? != "RIFF" {
+            return Err(Box::new(NotRmidError))
+        }
+        let riff_length: u32 = reader.read_le_to_u32()?;
+        if reader.read_to_string_n(4)? != "RMID" {
+            return Err(Box::new(NotRmidError))
+        }
+        let mut smf: Option<SMF> = None;
+        let mut info: Vec<InfoTag> = Vec::new();
+        let mut dls: Option<Vec<u8>> = None;
+        // The form type is part of the RIFF length
+        let mut remaining: u64 = u64::from(riff_length).saturating_sub(4);
+        while remaining >= 8 {
+            let id: String = match reader.read_to_string_n(4) {
+                Ok(id) => id,
+                Err(_) => break  // Some files state a RIFF length longer than the file
+            };
+            let length: u32 = reader.read_le_to_u32()?;
+            // Never trust a length to allocate more than the RIFF chunk holds
+            if u64::from(length) > remaining - 8 {
+                return Err(Box::new(ChunkTooLongError))
+            }
+            let mut data: Vec<u8> = vec![0; length as usize];
+            reader.read_exact(&mut data)?;
+            let padding: u64 = u64::from(length % 2);
+            if padding == 1 {
+                let mut pad: [u8; 1] = [0];
+                let _ = reader.read_exact(&mut pad);
+            }
+            remaining = remaining.saturating_sub(8 + u64::from(length) + padding);
+            match id.as_str() {
+                "data" => smf = Some(SMF::read(&mut Cursor::new(data))?),
+                "LIST" if data.starts_with(b"INFO") => info.extend(read_info_list(&data[4..])?),
+                "RIFF" if data.starts_with(b"DLS ") => {
+                    // Kept with its RIFF header so it can be saved as a .dls file
+                    let mut bank: Vec<u8> = b"RIFF".to_vec();
+                    bank.extend_from_slice(&[length as u8, (length >> 8) as u8, (length >> 16) as u8, (length >> 24) as u8]);
+                    bank.extend_from_slice(&data);
+                    dls = Some(bank);
+                },
+                _ => {}
+            }
+        }
+        match smf {
+            Some(smf) => Ok(Rmid {
+                smf,
+                info,
+                dls
+            }),
+            None => Err(Box::new(MissingDataChunkError))
+        }
+    }
+
+    pub fn tag(&self, id: &str) -> Option<&str> {
+        self.info.iter().find(|t| t.id == id).map(|t| t.text.as_str())
+    }
+
+    // Replaces the tag with this id, or adds it
+    pub fn set_tag(&mut self, id: &str, text: &str) {
+        if let Some(tag) = self.info.iter_mut().find(|t| t.id == id) {
+            tag.text = String::from(text);
+            return;
+        }
+        self.info.push(InfoTag { id: String::from(id), text: String::from(text) });
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        let mut body: Vec<u8> = b"RMID".to_vec();
+        let mut smf: Vec<u8> = Vec::new();
+        self.smf.write(&mut smf)?;
+        write_chunk(&mut body, b"data", &smf)?;
+        if !self.info.is_empty() {
+            let mut list: Vec<u8> = b"INFO".to_vec();
+            for tag in &self.info {
+                let mut id: Vec<u8> = tag.id.as_bytes().to_vec();
+                id.resize(4, b' ');
+                let mut text: Vec<u8> = tag.text.as_bytes().to_vec();
+                text.push(0);
+                write_chunk(&mut list, &id, &text)?;
+            }
+            write_chunk(&mut body, b"LIST", &list)?;
+        }
+        if let Some(ref bank) = self.dls {
+            body.extend_from_slice(bank);
+            if bank.len() % 2 == 1 {
+                body.push(0);
+            }
+        }
+        write_chunk(writer, b"RIFF", &body)
+    }
+}
+
+impl SMF {
+    pub fn write_rmid<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        Rmid::new(self.clone()).write(writer)
+    }
+}
@@ -0,0 +1,369 @@
+use convert::parse_error;
+use convert::parse_number;
+use file::SMF;
+use file::header::SMFHeaderChunk;
+use file::header::data::MidiDivisionsType;
+use file::header::data::MidiFormat;
+use file::header::data::MidiSMTPEDivisions;
+use file::header::data::MidiTPQNDivisions;
+use file::track::SMFTrackChunk;
+use file::track::data::event::Event;
+use file::track::data::event::EventType;
+use file::track::data::event::MetaEvent;
+use file::track::data::event::MetaEventType;
+use file::track::data::event::MidiEvent;
+use file::track::data::event::MidiEventType;
+use file::track::data::event::SysexEvent;
+use file::track::data::event::SysexEventType;
+use file::track::data::event::meta::Text;
+use file::track::data::event::midi::ChannelKeyPressure;
+use file::track::data::event::midi::ControllerChange;
+use file::track::data::event::midi::NoteChange;
+use file::track::data::event::midi::PitchBend;
+use file::track::data::event::midi::PolyphonicKeyPressure;
+use file::track::data::event::midi::ProgramChange;
+use file::track::data::event::sysex::Sysex;
+use std::error::Error;
+use std::result::Result;
+
+// Names of the Text Meta Events, by sub code byte
+const TEXT_NAMES: [(u8, &str); 9] = [
+    (0x01, "Text"),
+    (0x02, "Copyright"),
+    (0x03, "TrkName"),
+    (0x04, "InstrName"),
+    (0x05, "Lyric"),
+    (0x06, "Marker"),
+    (0x07, "Cue"),
+    (0x08, "ProgName"),
+    (0x09, "DevName")
+];
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect::<Vec<String>>().join(" ")
+}
+
+fn quote(text: &str) -> String {
+    let mut out: String = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\x{:02x}", c as u32)),
+            c => out.push(c)
+        }
+    }
+    out.push('"');
+    out
+}
+
+// Text of the quoted string starting at the first " of the line
+fn unquote(line: &str) -> Option<String> {
+    let start: usize = line.find('"')?;
+    let mut chars = line[start + 1..].chars();
+    let mut out: String = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(out),
+            '\\' => match chars.next()? {
+                'n' => out.push('\n'),
+                'r' => out.push('\r'),
+                't' => out.push('\t'),
+                'x' => {
+                    let code: String = chars.by_ref().take(2).collect();
+                    out.push(u8::from_str_radix(&code, 16).ok()? as char);
+                },
+                c => out.push(c)
+            },
+            c => out.push(c)
+        }
+    }
+}
+
+// One line without the time
+fn event_text(event: &Event) -> Result<String, Box<Error>> {
+    Ok(match event.event {
+        EventType::MidiEvent(ref e) => {
+            let ch: u8 = e.channel + 1;
+            match e.event {
+                MidiEventType::NoteOff(ref n) => format!("Off ch={} n={} v={}", ch, n.key, n.velocity),
+                MidiEventType::NoteOn(ref n) => format!("On ch={} n={} v={}", ch, n.key, n.velocity),
+                MidiEventType::PolyphonicKeyPressure(ref p) => format!("PoPr ch={} n={} v={}", ch, p.key, p.pressure),
+                MidiEventType::ControllerChange(ref c) => format!("Par ch={} c={} v={}", ch, c.controller_number, c.controller_value),
+                MidiEventType::ProgramChange(ref p) => format!("PrCh ch={} p={}", ch, p.new_program_number),
+                MidiEventType::ChannelKeyPressure(ref p) => format!("ChPr ch={} v={}", ch, p.value),
+                MidiEventType::PitchBend(ref b) => format!("Pb ch={} v={}", ch, b.value)
+            }
+        },
+        EventType::SysExEvent(ref e) => match e.event {
+            SysexEventType::F0SysexEvent(ref s) => format!("SysEx {}", hex(&s.data)),
+            SysexEventType::F7SysexEvent(ref s) => format!("Arb {}", hex(&s.data))
+        },
+        EventType::MetaEvent(ref m) => match m.event {
+            MetaEventType::SequenceNumber(ref n) => format!("SeqNr {}", n.sequence_number),
+            MetaEventType::TextEvent(ref t) | MetaEventType::CopyrightNotice(ref t) | MetaEventType::SequenceTrackName(ref t)
+            | MetaEventType::InstrumentName(ref t) | MetaEventType::Lyric(ref t) | MetaEventType::Marker(ref t)
+            | MetaEventType::CuePoint(ref t) | MetaEventType::ProgramName(ref t) | MetaEventType::DeviceName(ref t) => {
+                let sub_code_byte: u8 = m.event.sub_code_byte();
+                let name: &str = TEXT_NAMES.iter().find(|&&(b, _)| b == sub_code_byte).map(|&(_, n)| n).unwrap_or("Text");
+                format!("Meta {} {}", name, quote(&t.text))
+            },
+            MetaEventType::EndOfTrack(_) => String::from("Meta TrkEnd"),
+            MetaEventType::SetTempo(ref t) => format!("Tempo {}", t.tempo),
+            MetaEventType::SMTPEOffset(ref o) => format!("SMPTE {} {} {} {} {}", o.hour, o.minute, o.seconds, o.frames, o.hundred_of_frame),
+            MetaEventType::TimeSignature(ref t) => format!("TimeSig {}/{} {} {}",
+                t.nominator, 1u32 << u32::from(t.denominator.min(31)), t.midi_ticks_per_metronome_tick, t.thing),
+            MetaEventType::KeySignature(ref k) => format!("KeySig {} {}",
+                k.number_of_sharp_flats as i8, if k.major_key { "major" } else { "minor" }),
+            MetaEventType::SequencerSpecific(_) => format!("SeqSpec {}", hex(&m.event.data_bytes()?)),
+            // MIDI Channel Prefix, MIDI Port and unknown events are written as raw data
+            _ => format!("Meta 0x{:02x} {}", m.event.sub_code_byte(), hex(&m.event.data_bytes()?))
+        }
+    })
+}
+
+fn parse_hex(tokens: &[&str], line: usize) -> Result<Vec<u8>, Box<Error>> {
+    tokens.iter().map(|t| u8::from_str_radix(t, 16).map_err(|_| parse_error(line, "Invalid hex byte"))).collect()
+}
+
+// Value of a key=value argument
+fn argument<T: ::std::str::FromStr>(tokens: &[&str], key: &str, line: usize) -> Result<T, Box<Error>> {
+    let prefix: String = format!("{}=", key);
+    match tokens.iter().find(|t| t.starts_with(&prefix)) {
+        Some(t) => parse_number(&t[prefix.len()..], line),
+        None => Err(parse_error(line, &format!("Missing {}=", key)))
+    }
+}
+
+// Data byte of a Midi Event, 0 to 127
+fn data_byte(tokens: &[&str], key: &str, line: usize) -> Result<u8, Box<Error>> {
+    let value: u8 = argument(tokens, key, line)?;
+    if value > 0x7F {
+        return Err(parse_error(line, &format!("{}= goes from 0 to 127", key)))
+    }
+    Ok(value)
+}
+
+fn parse_event(text: &str, tokens: &[&str], line: usize) -> Result<Event, Box<Error>> {
+    let args: &[&str] = &tokens[1..];
+    let midi = |event: MidiEventType| -> Result<Event, Box<Error>> {
+        let channel: u8 = argument(args, "ch", line)?;
+        if !(1..=16).contains(&channel) {
+            return Err(parse_error(line, "Channels go from 1 to 16"))
+        }
+        Ok(Event::from_midi(MidiEvent::from_type(channel - 1, event)))
+    };
+    let meta = |event: MetaEventType| -> Result<Event, Box<Error>> {
+        Ok(Event::from_meta(MetaEvent::from_type(event)?))
+    };
+    match tokens[0] {
+        "Off" => midi(MidiEventType::NoteOff(NoteChange { key: data_byte(args, "n", line)?, velocity: data_byte(args, "v", line)? })),
+        "On" => midi(MidiEventType::NoteOn(NoteChange { key: data_byte(args, "n", line)?, velocity: data_byte(args, "v", line)? })),
+        "PoPr" => midi(MidiEventType::PolyphonicKeyPressure(PolyphonicKeyPressure { key: data_byte(args, "n", line)?, pressure: data_byte(args, "v", line)? })),
+        "Par" => midi(MidiEventType::ControllerChange(ControllerChange {
+            controller_number: data_byte(args, "c", line)?,
+            controller_value: data_byte(args, "v", line)?
+        })),
+        "PrCh" => midi(MidiEventType::ProgramChange(ProgramChange { new_program_number: data_byte(args, "p", line)? })),
+        "ChPr" => midi(MidiEventType::ChannelKeyPressure(ChannelKeyPressure { value: data_byte(args, "v", line)? })),
+        "Pb" => {
+            let value: u16 = argument(args, "v", line)?;
+            if value > 0x3FFF {
+                return Err(parse_error(line, "v= goes from 0 to 16383"))
+            }
+            midi(MidiEventType::PitchBend(PitchBend { value }))
+        },
+        "SysEx" | "Arb" => {
+            let data: Vec<u8> = parse_hex(args, line)?;
+            let sysex = Sysex { length: data.len() as u32, data };
+            Ok(Event::from_sysex(SysexEvent {
+                event: if tokens[0] == "SysEx" { SysexEventType::F0SysexEvent(sysex) } else { SysexEventType::F7SysexEvent(sysex) }
+            }))
+        },
+        "SeqNr" => {
+            let number: u16 = parse_number(args.first().ok_or_else(|| parse_error(line, "Missing sequence number"))?, line)?;
+            Ok(Event::from_meta(MetaEvent::from_bytes(0x00, &[(number >> 8) as u8, number as u8])?))
+        },
+        "Tempo" => {
+            let tempo: u32 = parse_number(args.first().ok_or_else(|| parse_error(line, "Missing tempo"))?, line)?;
+            Ok(Event::from_meta(MetaEvent::from_bytes(0x51, &[(tempo >> 16) as u8, (tempo >> 8) as u8, tempo as u8])?))
+        },
+        "SMPTE" => {
+            if args.len() != 5 {
+                return Err(parse_error(line, "SMPTE takes 5 numbers"))
+            }
+            let mut data: Vec<u8> = Vec::with_capacity(5);
+            for arg in args {
+                data.push(parse_number(arg, line)?);
+            }
+            Ok(Event::from_meta(MetaEvent::from_bytes(0x54, &data)?))
+        },
+        "TimeSig" => {
+            if args.len() != 3 {
+                return Err(parse_error(line, "TimeSig takes n/d and 2 numbers"))
+            }
+            let mut parts = args[0].split('/');
+            let nominator: u8 = parse_number(parts.next().unwrap_or(""), line)?;
+            let denominator: u32 = parse_number(parts.next().unwrap_or(""), line)?;
+            if !denominator.is_power_of_two() {
+                return Err(parse_error(line, "The denominator must be a power of two"))
+            }
+            let clocks: u8 = parse_number(args[1], line)?;
+            let thirty_seconds: u8 = parse_number(args[2], line)?;
+            Ok(Event::from_meta(MetaEvent::from_bytes(0x58, &[nominator, denominator.trailing_zeros() as u8, clocks, thirty_seconds])?))
+        },
+        "KeySig" => {
+            if args.len() != 2 {
+                return Err(parse_error(line, "KeySig takes a number and major or minor"))
+            }
+            let sharps_flats: i8 = parse_number(args[0], line)?;
+            let mode: u8 = match args[1] {
+                "major" => 0,
+                "minor" => 1,
+                _ => return Err(parse_error(line, "KeySig mode must be major or minor"))
+            };
+            Ok(Event::from_meta(MetaEvent::from_bytes(0x59, &[sharps_flats as u8, mode])?))
+        },
+        "SeqSpec" => Ok(Event::from_meta(MetaEvent::from_bytes(0x7F, &parse_hex(args, line)?)?)),
+        "Meta" => {
+            let kind: &str = args.first().ok_or_else(|| parse_error(line, "Missing Meta Event type"))?;
+            if kind == "TrkEnd" {
+                return Ok(Event::end_of_track())
+            }
+            if let Some(code) = kind.strip_prefix("0x") {
+                let sub_code_byte: u8 = u8::from_str_radix(code, 16).map_err(|_| parse_error(line, "Invalid Meta Event type"))?;
+                return Ok(Event::from_meta(MetaEvent::from_bytes(sub_code_byte, &parse_hex(&args[1..], line)?)?))
+            }
+            let sub_code_byte: u8 = match TEXT_NAMES.iter().find(|&&(_, n)| n == kind) {
+                Some(&(b, _)) => b,
+                None => return Err(parse_error(line, &format!("Unknown Meta Event {}", kind)))
+            };
+            let text = Text { text: unquote(text).ok_or_else(|| parse_error(line, "Missing quoted text"))? };
+            meta(match sub_code_byte {
+                0x01 => MetaEventType::TextEvent(text),
+                0x02 => MetaEventType::CopyrightNotice(text),
+                0x03 => MetaEventType::SequenceTrackName(text),
+                0x04 => MetaEventType::InstrumentName(text),
+                0x05 => MetaEventType::Lyric(text),
+                0x06 => MetaEventType::Marker(text),
+                0x07 => MetaEventType::CuePoint(text),
+                0x08 => MetaEventType::ProgramName(text),
+                _ => MetaEventType::DeviceName(text)
+            })
+        },
+        other => Err(parse_error(line, &format!("Unknown event {}", other)))
+    }
+}
+
+impl SMF {
+    // Text in the format of mf2t: MFile, then every track between MTrk and TrkEnd, one event per line
+    // Times are absolute ticks, or ticks since the previous event written as +delta
+    pub fn to_text(&self, delta_time: bool) -> Result<String, Box<Error>> {
+        let division: String = match self.header.division_system {
+            MidiDivisionsType::TicksPerQuarterNote(ref d) => format!("{}", d.ticks_per_quarter_note),
+            MidiDivisionsType::SMTPEFrames(ref d) => format!("-{} {}", d.smtpe_frames_per_second, d.ticks_per_smtpe_frame)
+        };
+        let mut out: String = format!("MFile {} {} {}\n", self.header.format.number(), self.tracks.len(), division);
+        for track in &self.tracks {
+            out.push_str("MTrk\n");
+            let mut tick: u64 = 0;
+            for event in &track.track_events {
+                tick += u64::from(event.delta_time);
+                let time: String = if delta_time { format!("+{}", event.delta_time) } else { format!("{}", tick) };
+                out.push_str(&format!("{} {}\n", time, event_text(&event.event)?));
+            }
+            out.push_str("TrkEnd\n");
+        }
+        Ok(out)
+    }
+
+    // Reads what to_text writes, times can be absolute or +delta on any line
+    pub fn from_text(text: &str) -> Result<SMF, Box<Error>> {
+        let mut header: Option<(MidiFormat, MidiDivisionsType)> = None;
+        let mut tracks: Vec<SMFTrackChunk> = Vec::new();
+        let mut events: Option<Vec<(u64, Event)>> = None;
+        let mut tick: u64 = 0;
+        let mut ended: bool = false;
+        for (index, raw) in text.lines().enumerate() {
+            let line: usize = index + 1;
+            let content: &str = raw.trim();
+            let tokens: Vec<&str> = content.split_whitespace().collect();
+            if tokens.is_empty() {
+                continue;
+            }
+            match tokens[0] {
+                "MFile" => {
+                    if (tokens.len() < 4) | header.is_some() {
+                        return Err(parse_error(line, "MFile takes a format, a number of tracks and a division"))
+                    }
+                    let format: MidiFormat = MidiFormat::from_number(parse_number(tokens[1], line)?)
+                        .ok_or_else(|| parse_error(line, "Invalid format"))?;
+                    let division: MidiDivisionsType = if tokens[3].starts_with('-') {
+                        MidiDivisionsType::SMTPEFrames(MidiSMTPEDivisions {
+                            smtpe_frames_per_second: parse_number(&tokens[3][1..], line)?,
+                            ticks_per_smtpe_frame: parse_number(tokens.get(4).ok_or_else(|| parse_error(line, "Missing ticks per frame"))?, line)?
+                        })
+                    } else {
+                        MidiDivisionsType::TicksPerQuarterNote(MidiTPQNDivisions { ticks_per_quarter_note: parse_number(tokens[3], line)? })
+                    };
+                    header = Some((format, division));
+                },
+                "MTrk" => {
+                    if header.is_none() | events.is_some() {
+                        return Err(parse_error(line, "MTrk must follow MFile or TrkEnd"))
+                    }
+                    events = Some(Vec::new());
+                    tick = 0;
+                    ended = false;
+                },
+                "TrkEnd" => {
+                    match events.take() {
+                        Some(e) => tracks.push(SMFTrackChunk::from_absolute_events(e)?),
+                        None => return Err(parse_error(line, "TrkEnd without MTrk"))
+                    }
+                },
+                time => {
+                    let track: &mut Vec<(u64, Event)> = match events.as_mut() {
+                        Some(e) => e,
+                        None => return Err(parse_error(line, "Event outside of a track"))
+                    };
+                    if tokens.len() < 2 {
+                        return Err(parse_error(line, "Missing event"))
+                    }
+                    // Meta TrkEnd is the last event, only the TrkEnd line may follow it
+                    if ended {
+                        return Err(parse_error(line, "Event after Meta TrkEnd"))
+                    }
+                    if let Some(delta) = time.strip_prefix('+') {
+                        tick += parse_number::<u64>(delta, line)?;
+                    } else {
+                        let absolute: u64 = parse_number(time, line)?;
+                        if absolute < tick {
+                            return Err(parse_error(line, "Times must not go backwards"))
+                        }
+                        tick = absolute;
+                    }
+                    let event: Event = parse_event(content, &tokens[1..], line)?;
+                    ended = event.is_end_of_track();
+                    track.push((tick, event));
+                }
+            }
+        }
+        if events.is_some() {
+            return Err(parse_error(text.lines().count(), "Missing TrkEnd"))
+        }
+        let (format, division_system) = header.ok_or_else(|| parse_error(1, "Missing MFile"))?;
+        Ok(SMF {
+            header: SMFHeaderChunk {
+                length: 6,
+                format,
+                nb_tracks: tracks.len() as u16,
+                division_system
+            },
+            tracks
+        })
+    }
+}
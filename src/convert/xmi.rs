@@ -0,0 +1,158 @@
+use VLVRead;
+use ez_io::ReadE;
+use file::SMF;
+use file::header::SMFHeaderChunk;
+use file::header::data::MidiDivisionsType;
+use file::header::data::MidiFormat;
+use file::header::data::MidiTPQNDivisions;
+use file::track::SMFTrackChunk;
+use file::track::data::event::Event;
+use file::track::data::event::EventType;
+use file::track::data::event::MetaEvent;
+use file::track::data::event::MetaEventType;
+use file::track::data::event::MidiEvent;
+use file::track::data::event::MidiEventType;
+use file::track::data::event::meta::SetTempo;
+use file::track::data::event::meta::Text;
+use file::track::data::event::midi::NoteChange;
+use std::error::Error;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::result::Result;
+use std::fmt;
+
+#[derive(Debug)]
+pub struct NotXmiError;
+
+impl Error for NotXmiError {
+    fn description(&self) -> &str {
+        "The file has no XMIDI sequence"
+    }
+}
+
+impl fmt::Display for NotXmiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Not an XMI file")
+    }
+}
+
+// XMI ticks are fixed at 120 Hz, 60 ticks per quarter note at 500 000 microseconds per quarter note keep them as they are
+pub const XMI_TICKS_PER_QUARTER_NOTE: u16 = 60;
+pub const XMI_TEMPO: u32 = 500_000;
+
+// Loop controllers of the Audio Interface Library, the value of the start is the loop count, 0 forever
+pub const XMI_FOR_LOOP_CONTROLLER: u8 = 116;
+pub const XMI_NEXT_LOOP_CONTROLLER: u8 = 117;
+
+// Release velocity of the generated Note Offs
+const NOTE_OFF_VELOCITY: u8 = 0x40;
+
+// IFF chunks are big endian and padded to an even size, FORM, CAT  and LIST start with a type
+fn find_sequences(data: &[u8], sequences: &mut Vec<Vec<u8>>) -> Result<(), Box<Error>> {
+    let mut reader = Cursor::new(data);
+    while (reader.position() + 8) <= data.len() as u64 {
+        let id: String = reader.read_to_string_n(4)?;
+        let length: u64 = u64::from(reader.read_be_to_u32()?);
+        let start: usize = reader.position() as usize;
+        // Some files state lengths longer than the data
+        let end: usize = (start + length as usize).min(data.len());
+        let chunk: &[u8] = &data[start..end];
+        match id.as_str() {
+            "FORM" | "CAT " | "LIST" if chunk.len() >= 4 => find_sequences(&chunk[4..], sequences)?,
+            "EVNT" => sequences.push(chunk.to_vec()),
+            _ => {}
+        }
+        reader.seek(SeekFrom::Start((start as u64) + length + (length % 2)))?;
+    }
+    Ok(())
+}
+
+fn marker(text: &str) -> Result<Event, Box<Error>> {
+    Ok(Event::from_meta(MetaEvent::from_type(MetaEventType::Marker(Text { text: String::from(text) }))?))
+}
+
+// Events of an EVNT chunk at absolute ticks, with Note Offs added
+fn read_events(data: &[u8]) -> Result<Vec<(u64, Event)>, Box<Error>> {
+    let mut reader = Cursor::new(data);
+    let mut tick: u64 = 0;
+    let mut note_offs: Vec<(u64, Event)> = Vec::new();
+    let mut events: Vec<(u64, Event)> = Vec::new();
+    while reader.position() < data.len() as u64 {
+        let byte: u8 = reader.read_to_u8()?;
+        // Delays are sums of bytes under 0x80
+        if byte < 0x80u8 {
+            tick += u64::from(byte);
+            continue;
+        }
+        reader.seek(SeekFrom::Current(-1))?;
+        // No Running Status in XMI
+        let event: Event = Event::new(&mut reader, None)?;
+        if event.is_end_of_track() {
+            break;
+        }
+        let converted: Event = match event.event {
+            EventType::MidiEvent(MidiEvent { channel, event: MidiEventType::NoteOn(ref n), .. }) => {
+                // Note Ons are followed by the duration of the note
+                let duration: u64 = u64::from(reader.read_vlv()?.data);
+                if n.velocity > 0 {
+                    note_offs.push((tick + duration, Event::from_midi(MidiEvent::from_type(channel, MidiEventType::NoteOff(NoteChange {
+                        key: n.key,
+                        velocity: NOTE_OFF_VELOCITY
+                    })))));
+                }
+                event.clone()
+            },
+            EventType::MidiEvent(MidiEvent { event: MidiEventType::ControllerChange(ref c), .. }) => {
+                // The controllers are kept for their loop count, which markers cannot hold
+                match c.controller_number {
+                    XMI_FOR_LOOP_CONTROLLER => events.push((tick, marker("loopStart")?)),
+                    XMI_NEXT_LOOP_CONTROLLER => events.push((tick, marker("loopEnd")?)),
+                    _ => {}
+                }
+                event.clone()
+            },
+            // Timing is fixed, the tempo is set once at the start
+            EventType::MetaEvent(MetaEvent { event: MetaEventType::SetTempo(_), .. }) => continue,
+            _ => event.clone()
+        };
+        events.push((tick, converted));
+    }
+    // Note Offs first so that notes ending and starting at the same tick are not cut
+    note_offs.extend(events);
+    Ok(note_offs)
+}
+
+impl SMF {
+    // One Format 0 file per sequence of the XMI, the loop controllers get "loopStart" and "loopEnd" markers next to them
+    // The controllers are the same as the EMIDI ones, detect_loop finds the loop with its count
+    pub fn read_xmi<R: Read>(reader: &mut R) -> Result<Vec<SMF>, Box<Error>> {
+        let mut data: Vec<u8> = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let mut sequences: Vec<Vec<u8>> = Vec::new();
+        find_sequences(&data, &mut sequences)?;
+        if sequences.is_empty() {
+            return Err(Box::new(NotXmiError))
+        }
+        let mut files: Vec<SMF> = Vec::with_capacity(sequences.len());
+        for sequence in sequences {
+            let mut events: Vec<(u64, Event)> = vec![
+                (0, Event::from_meta(MetaEvent::from_type(MetaEventType::SetTempo(SetTempo { tempo: XMI_TEMPO }))?))
+            ];
+            events.extend(read_events(&sequence)?);
+            files.push(SMF {
+                header: SMFHeaderChunk {
+                    length: 6,
+                    format: MidiFormat::SingleTrack,
+                    nb_tracks: 1,
+                    division_system: MidiDivisionsType::TicksPerQuarterNote(MidiTPQNDivisions {
+                        ticks_per_quarter_note: XMI_TICKS_PER_QUARTER_NOTE
+                    })
+                },
+                tracks: vec![SMFTrackChunk::from_absolute_events(events)?]
+            });
+        }
+        Ok(files)
+    }
+}
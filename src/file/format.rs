@@ -0,0 +1,203 @@
+use super::SMF;
+use super::header::SMFHeaderChunk;
+use super::header::data::MidiFormat;
+use super::track::SMFTrackChunk;
+use super::track::data::event::Event;
+use super::track::data::event::EventType;
+use super::track::data::event::MetaEvent;
+use super::track::data::event::MetaEventType;
+use super::track::data::event::MidiEventType;
+use super::track::data::event::meta::Text;
+use gm::GM_PERCUSSION_CHANNEL;
+use gm::program_name;
+use std::error::Error;
+use std::result::Result;
+use std::fmt;
+
+#[derive(Debug)]
+pub struct WrongFormatError;
+
+impl Error for WrongFormatError {
+    fn description(&self) -> &str {
+        "This conversion is not possible from the format of this file"
+    }
+}
+
+impl fmt::Display for WrongFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Conversion not possible from this Midi Format")
+    }
+}
+
+#[derive(Debug)]
+pub struct NoSuchPatternError;
+
+impl Error for NoSuchPatternError {
+    fn description(&self) -> &str {
+        "There is no pattern at this index"
+    }
+}
+
+impl fmt::Display for NoSuchPatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "No pattern at this index")
+    }
+}
+
+// Name given to a track created when splitting a file by channel, the General MIDI name of its first program
+pub fn track_name_for_channel(channel: u8, first_program: Option<u8>) -> String {
+    if channel == GM_PERCUSSION_CHANNEL {
+        return String::from("Drums")
+    }
+    match first_program {
+        Some(program) => String::from(program_name(program)),
+        None => format!("Channel {}", u16::from(channel) + 1)
+    }
+}
+
+// Meta Events that describe the whole sequence and go to the conductor track
+fn is_conductor_meta(event: &MetaEventType) -> bool {
+    !matches!(*event, MetaEventType::MIDIChannelPrefix(_) | MetaEventType::MIDIPort(_) | MetaEventType::EndOfTrack(_))
+}
+
+impl SMF {
+    // Merges all tracks into a single one, Format 2 patterns are played one after the other
+    pub fn to_format_0(&self) -> Result<SMF, Box<Error>> {
+        match self.header.format {
+            MidiFormat::SingleTrack => return Ok(self.clone()),
+            MidiFormat::SimultaneousTracks => {},
+            MidiFormat::IndependentTracks => return self.concatenate_patterns()
+        }
+        let mut events: Vec<(u64, Event)> = Vec::new();
+        for (track_index, track) in self.tracks.iter().enumerate() {
+            for (tick, event) in track.absolute_events() {
+                // A single track can only have one name
+                if track_index > 0 {
+                    if let EventType::MetaEvent(MetaEvent { event: MetaEventType::SequenceTrackName(_), .. }) = event.event {
+                        continue;
+                    }
+                }
+                events.push((tick, event));
+            }
+        }
+        // Stable sort, events on the same tick stay in track order
+        events.sort_by_key(|&(tick, _)| tick);
+        // Tempo changes to the tempo already in effect are dropped
+        let mut current_tempo: Option<u32> = None;
+        let mut merged: Vec<(u64, Event)> = Vec::with_capacity(events.len());
+        for (tick, event) in events {
+            if let EventType::MetaEvent(MetaEvent { event: MetaEventType::SetTempo(ref tempo), .. }) = event.event {
+                if current_tempo == Some(tempo.tempo) {
+                    continue;
+                }
+                current_tempo = Some(tempo.tempo);
+            }
+            merged.push((tick, event));
+        }
+        let track: SMFTrackChunk = SMFTrackChunk::from_absolute_events(merged)?;
+        Ok(self.with_tracks(MidiFormat::SingleTrack, vec![track]))
+    }
+
+    // Splits a single track by channel, with a conductor track first holding tempo, meter, markers and other metas
+    // Sysex Events stay in the conductor track, Midi Port changes are copied to every channel track
+    pub fn to_format_1(&self) -> Result<SMF, Box<Error>> {
+        match self.header.format {
+            MidiFormat::SingleTrack => {},
+            MidiFormat::SimultaneousTracks => return Ok(self.clone()),
+            MidiFormat::IndependentTracks => return Err(Box::new(WrongFormatError))
+        }
+        let mut conductor: Vec<(u64, Event)> = Vec::new();
+        let mut shared: Vec<(u64, Event)> = Vec::new();
+        let mut channels: Vec<Vec<(u64, Event)>> = vec![Vec::new(); 16];
+        let mut first_programs: Vec<Option<u8>> = vec![None; 16];
+        for track in &self.tracks {
+            for (tick, event) in track.absolute_events() {
+                match event.event {
+                    EventType::MidiEvent(ref e) => {
+                        if let MidiEventType::ProgramChange(ref p) = e.event {
+                            if first_programs[e.channel as usize].is_none() {
+                                first_programs[e.channel as usize] = Some(p.new_program_number);
+                            }
+                        }
+                        channels[e.channel as usize].push((tick, event.clone()));
+                    },
+                    EventType::SysExEvent(_) => conductor.push((tick, event.clone())),
+                    EventType::MetaEvent(ref e) => {
+                        if is_conductor_meta(&e.event) {
+                            conductor.push((tick, event.clone()));
+                        } else if let MetaEventType::MIDIPort(_) = e.event {
+                            shared.push((tick, event.clone()));
+                        } else if event.is_end_of_track() {
+                            // Keep the End of Track position so that trailing silence is not lost
+                            conductor.push((tick, event.clone()));
+                        }
+                        // Channel Prefixes are dropped, every channel gets its own track
+                    }
+                }
+            }
+        }
+        let mut tracks: Vec<SMFTrackChunk> = vec![SMFTrackChunk::from_absolute_events(conductor)?];
+        for (channel, events) in channels.into_iter().enumerate() {
+            if events.is_empty() {
+                continue;
+            }
+            let name: String = track_name_for_channel(channel as u8, first_programs[channel]);
+            let mut track_events: Vec<(u64, Event)> = vec![
+                (0, Event::from_meta(MetaEvent::from_type(MetaEventType::SequenceTrackName(Text { text: name }))?))
+            ];
+            track_events.extend(shared.iter().cloned());
+            track_events.extend(events);
+            tracks.push(SMFTrackChunk::from_absolute_events(track_events)?);
+        }
+        Ok(self.with_tracks(MidiFormat::SimultaneousTracks, tracks))
+    }
+
+    // Takes one pattern out of a Format 2 file as a Format 0 file
+    pub fn extract_pattern(&self, index: usize) -> Result<SMF, Box<Error>> {
+        match self.header.format {
+            MidiFormat::IndependentTracks => {},
+            _ => return Err(Box::new(WrongFormatError))
+        }
+        let track: SMFTrackChunk = match self.tracks.get(index) {
+            Some(t) => t.clone(),
+            None => return Err(Box::new(NoSuchPatternError))
+        };
+        Ok(self.with_tracks(MidiFormat::SingleTrack, vec![track]))
+    }
+
+    // Plays all patterns of a Format 2 file one after the other in a Format 0 file
+    pub fn concatenate_patterns(&self) -> Result<SMF, Box<Error>> {
+        match self.header.format {
+            MidiFormat::IndependentTracks => {},
+            _ => return Err(Box::new(WrongFormatError))
+        }
+        let mut events: Vec<(u64, Event)> = Vec::new();
+        let mut offset: u64 = 0;
+        for track in &self.tracks {
+            let mut pattern_end: u64 = offset;
+            for (tick, event) in track.absolute_events() {
+                pattern_end = offset + tick;
+                if event.is_end_of_track() {
+                    continue;
+                }
+                events.push((offset + tick, event));
+            }
+            offset = pattern_end;
+        }
+        events.push((offset, Event::end_of_track()));
+        let track: SMFTrackChunk = SMFTrackChunk::from_absolute_events(events)?;
+        Ok(self.with_tracks(MidiFormat::SingleTrack, vec![track]))
+    }
+
+    fn with_tracks(&self, format: MidiFormat, tracks: Vec<SMFTrackChunk>) -> SMF {
+        SMF {
+            header: SMFHeaderChunk {
+                length: 6,
+                format,
+                nb_tracks: tracks.len() as u16,
+                division_system: self.header.division_system.clone()
+            },
+            tracks
+        }
+    }
+}
@@ -1,27 +1,76 @@
 // Standard Midi File Types
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum MidiFormat {
     SingleTrack,
     SimultaneousTracks,
     IndependentTracks
 }
 
+impl MidiFormat {
+    pub fn from_number(number: u16) -> Option<MidiFormat> {
+        match number {
+            0 => Some(MidiFormat::SingleTrack),
+            1 => Some(MidiFormat::SimultaneousTracks),
+            2 => Some(MidiFormat::IndependentTracks),
+            _ => None
+        }
+    }
+
+    // Number written in the header
+    pub fn number(&self) -> u16 {
+        match *self {
+            MidiFormat::SingleTrack => 0,
+            MidiFormat::SimultaneousTracks => 1,
+            MidiFormat::IndependentTracks => 2
+        }
+    }
+}
+
 // Standard Midi File Division System
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
 pub enum MidiDivisionsType {
     TicksPerQuarterNote(MidiTPQNDivisions),
     SMTPEFrames(MidiSMTPEDivisions)
 }
 
+impl MidiDivisionsType {
+    // From the division as written in the header
+    pub fn from_raw(division_info: u16) -> MidiDivisionsType {
+        if (division_info & 0b1000_0000_0000_0000u16) == 0 {
+            MidiDivisionsType::TicksPerQuarterNote(MidiTPQNDivisions { ticks_per_quarter_note: division_info })
+        } else {
+            // Stored as a negative number in the upper byte: -24, -25, -29 (for 29.97) or -30
+            MidiDivisionsType::SMTPEFrames(MidiSMTPEDivisions {
+                ticks_per_smtpe_frame: division_info & 0b0000_0000_1111_1111u16,
+                smtpe_frames_per_second: u16::from(((division_info >> 8) as u8 as i8).wrapping_neg() as u8)
+            })
+        }
+    }
+
+    // Division as written in the header
+    pub fn raw(&self) -> u16 {
+        match *self {
+            MidiDivisionsType::TicksPerQuarterNote(ref d) => d.ticks_per_quarter_note & 0b0111_1111_1111_1111u16,
+            MidiDivisionsType::SMTPEFrames(ref d) => (u16::from((d.smtpe_frames_per_second as u8 as i8).wrapping_neg() as u8) << 8)
+                | (d.ticks_per_smtpe_frame & 0b0000_0000_1111_1111u16)
+        }
+    }
+}
+
 // Ticks per Quartet Note System
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MidiTPQNDivisions {
     pub ticks_per_quarter_note: u16
 }
 
 // SMTPE System
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MidiSMTPEDivisions {
     pub ticks_per_smtpe_frame: u16,
-    pub smtpe_frames_per_second: u16
+    pub smtpe_frames_per_second: u16  // 24, 25, 29 (meaning 29.97 drop frame) or 30
 }
\ No newline at end of file
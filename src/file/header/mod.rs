@@ -3,12 +3,11 @@ pub mod data;
 use ez_io::ReadE;
 use self::data::MidiFormat;
 use self::data::MidiDivisionsType;
-use self::data::MidiTPQNDivisions;
-use self::data::MidiSMTPEDivisions;
 use std::error::Error;
 use std::io::Read;
 use std::io::Seek;
 use std::io::SeekFrom;
+use std::io::Write;
 use std::result::Result;
 use std::fmt;
 
@@ -31,7 +30,8 @@ impl fmt::Display for InvalidMidiFormatError {
 
 // Standard Midi File Header Chunk
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SMFHeaderChunk {
     pub length: u32,
     pub format: MidiFormat,
@@ -44,26 +44,12 @@ impl SMFHeaderChunk {
         assert_eq!(String::from("MThd"), reader.read_to_string_n(4)?, "Magic Number did not match");
         let length: u32 = reader.read_be_to_u32()?;
         let format_num: u16 = reader.read_be_to_u16()?;
-        let format: MidiFormat;
-        match format_num {
-            0 => format = MidiFormat::SingleTrack,
-            1 => format = MidiFormat::SimultaneousTracks,
-            2 => format = MidiFormat::IndependentTracks,
-            _ => return Err(Box::new(InvalidMidiFormatError{}))
-        }
+        let format: MidiFormat = match MidiFormat::from_number(format_num) {
+            Some(f) => f,
+            None => return Err(Box::new(InvalidMidiFormatError{}))
+        };
         let nb_tracks: u16 = reader.read_be_to_u16()?;
-        let division_info: u16 = reader.read_be_to_u16()?;
-        let division_system;
-        if (division_info & 0b1000_0000_0000_0000u16) == 0 {
-            division_system = MidiDivisionsType::TicksPerQuarterNote(
-                MidiTPQNDivisions{ ticks_per_quarter_note: division_info });
-        } else {
-            let ticks_per_smtpe_frame: u16 = division_info & 0b0000_0000_1111_1111u16;
-            let smtpe_frames_per_second: u16 = division_info & 0b0111_1111_0000_0000u16;
-            division_system = MidiDivisionsType::SMTPEFrames(
-                MidiSMTPEDivisions{ ticks_per_smtpe_frame, smtpe_frames_per_second }
-            )
-        }
+        let division_system: MidiDivisionsType = MidiDivisionsType::from_raw(reader.read_be_to_u16()?);
         // For non-standard headers
         reader.seek(SeekFrom::Current(i64::from(length) - i64::from(6)))?;
         Ok(SMFHeaderChunk {
@@ -73,4 +59,16 @@ impl SMFHeaderChunk {
             division_system
         })
     }
+
+    // Always writes a standard 6 bytes header
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        let format_num: u16 = self.format.number();
+        let division_info: u16 = self.division_system.raw();
+        writer.write_all(b"MThd")?;
+        writer.write_all(&[0, 0, 0, 6])?;
+        writer.write_all(&[(format_num >> 8) as u8, format_num as u8])?;
+        writer.write_all(&[(self.nb_tracks >> 8) as u8, self.nb_tracks as u8])?;
+        writer.write_all(&[(division_info >> 8) as u8, division_info as u8])?;
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,132 @@
+use super::SMF;
+use super::track::data::event::Event;
+use super::track::data::event::EventType;
+use super::track::data::event::MetaEvent;
+use super::track::data::event::MetaEventType;
+use super::track::data::event::MidiEventType;
+use std::error::Error;
+use std::result::Result;
+
+// Ways game music files mark the part that loops
+#[derive(Clone, Copy, PartialEq)]
+pub enum LoopConvention {
+    EMIDI,  // Controller 116 at the start (value is the loop count, 0 forever) and 117 at the end
+    Markers,  // Markers "loopStart" and "loopEnd"
+    FinalFantasy,  // Markers "[" and "]"
+    RPGMaker  // Controller 111 at the start, the loop ends with the song
+}
+
+#[derive(Clone)]
+pub struct LoopPoints {
+    pub convention: LoopConvention,
+    pub start: u64,
+    pub end: u64,
+    pub count: Option<u32>  // Number of times the loop is played, None forever
+}
+
+// Conventions in the order they are tried, the explicit ones first
+const CONVENTIONS: [LoopConvention; 4] = [
+    LoopConvention::EMIDI,
+    LoopConvention::Markers,
+    LoopConvention::FinalFantasy,
+    LoopConvention::RPGMaker
+];
+
+fn controller(event: &Event) -> Option<(u8, u8)> {
+    match event.event {
+        EventType::MidiEvent(ref e) => match e.event {
+            MidiEventType::ControllerChange(ref c) => Some((c.controller_number, c.controller_value)),
+            _ => None
+        },
+        _ => None
+    }
+}
+
+fn marker(event: &Event) -> Option<&str> {
+    match event.event {
+        EventType::MetaEvent(MetaEvent { event: MetaEventType::Marker(ref t), .. }) => Some(t.text.trim()),
+        _ => None
+    }
+}
+
+// Loop start or end of any convention
+fn is_loop_event(event: &Event) -> bool {
+    if let Some((number, _)) = controller(event) {
+        return (number == 111) | (number == 116) | (number == 117)
+    }
+    match marker(event) {
+        Some(m) => m.eq_ignore_ascii_case("loopStart") | m.eq_ignore_ascii_case("loopEnd") | (m == "[") | (m == "]"),
+        None => false
+    }
+}
+
+impl SMF {
+    // Events of every track by tick
+    fn merged_events(&self) -> Vec<(u64, Event)> {
+        let mut events: Vec<(u64, Event)> = Vec::new();
+        for track in &self.tracks {
+            events.extend(track.absolute_events());
+        }
+        events.sort_by_key(|&(tick, _)| tick);
+        events
+    }
+
+    // Loop found with one convention, the first start and the first end after it
+    pub fn detect_loop_with(&self, convention: LoopConvention) -> Option<LoopPoints> {
+        let events: Vec<(u64, Event)> = self.merged_events();
+        let is_start = |e: &Event| -> Option<Option<u32>> {
+            match convention {
+                LoopConvention::EMIDI => controller(e)
+                    .and_then(|(n, v)| if n == 116 { Some(if v == 0 { None } else { Some(u32::from(v)) }) } else { None }),
+                LoopConvention::Markers => marker(e)
+                    .and_then(|m| if m.eq_ignore_ascii_case("loopStart") { Some(None) } else { None }),
+                LoopConvention::FinalFantasy => marker(e).and_then(|m| if m == "[" { Some(None) } else { None }),
+                LoopConvention::RPGMaker => controller(e).and_then(|(n, _)| if n == 111 { Some(None) } else { None })
+            }
+        };
+        let is_end = |e: &Event| -> bool {
+            match convention {
+                LoopConvention::EMIDI => controller(e).map(|(n, _)| n == 117).unwrap_or(false),
+                LoopConvention::Markers => marker(e).map(|m| m.eq_ignore_ascii_case("loopEnd")).unwrap_or(false),
+                LoopConvention::FinalFantasy => marker(e).map(|m| m == "]").unwrap_or(false),
+                LoopConvention::RPGMaker => false
+            }
+        };
+        let (start_index, start, count) = events.iter().enumerate()
+            .filter_map(|(index, &(tick, ref e))| is_start(e).map(|count| (index, tick, count)))
+            .next()?;
+        let end: u64 = match convention {
+            LoopConvention::RPGMaker => self.end_tick(),
+            _ => events[start_index..].iter().find(|(_, e)| is_end(e)).map(|&(tick, _)| tick)?
+        };
+        if end <= start {
+            return None
+        }
+        Some(LoopPoints {
+            convention,
+            start,
+            end,
+            count
+        })
+    }
+
+    // Every convention that gives a loop, in the order they are tried by detect_loop
+    pub fn detect_loops(&self) -> Vec<LoopPoints> {
+        CONVENTIONS.iter().filter_map(|&c| self.detect_loop_with(c)).collect()
+    }
+
+    pub fn detect_loop(&self) -> Option<LoopPoints> {
+        CONVENTIONS.iter().filter_map(|&c| self.detect_loop_with(c)).next()
+    }
+
+    // Intro, the loop played iterations times, then what follows the loop
+    // Loop points of every convention are left out so that players do not loop the rendered file again
+    // points.count is not applied, pass it as iterations to play the loop as many times as the file asks
+    pub fn render_loop(&self, points: &LoopPoints, iterations: u32) -> Result<SMF, Box<Error>> {
+        let mut smf: SMF = self.clone();
+        for track in &mut smf.tracks {
+            track.retain(|_, e| !is_loop_event(e))?;
+        }
+        smf.repeat_section(points.start, points.end, iterations)
+    }
+}
@@ -0,0 +1,209 @@
+use super::SMF;
+use super::tempo::TempoMap;
+use super::track::data::event::EventType;
+use super::track::data::event::MetaEvent;
+use super::track::data::event::MetaEventType;
+
+// One sung piece of text, usually a syllable with its trailing space
+
+#[derive(Clone)]
+pub struct Syllable {
+    pub tick: u64,
+    pub seconds: f64,
+    pub text: String
+}
+
+#[derive(Clone)]
+pub struct LyricLine {
+    pub tick: u64,
+    pub seconds: f64,
+    pub new_paragraph: bool,  // The screen is cleared before this line
+    pub syllables: Vec<Syllable>
+}
+
+impl LyricLine {
+    pub fn text(&self) -> String {
+        self.syllables.iter().map(|s| s.text.as_str()).collect::<String>().trim().to_string()
+    }
+}
+
+// Soft Karaoke headers, found in Text Events starting with @
+#[derive(Clone)]
+pub struct KaraokeInfo {
+    pub version: Option<String>,  // @V
+    pub language: Option<String>,  // @L
+    pub titles: Vec<String>,  // @T, title then author and copyright
+    pub information: Vec<String>  // @I
+}
+
+#[derive(Clone)]
+pub struct Lyrics {
+    pub info: KaraokeInfo,
+    pub lines: Vec<LyricLine>
+}
+
+// Text of a Lyric or Text Event, and whether it is a Lyric
+fn text_of(event: &MetaEvent) -> Option<(&str, bool)> {
+    match event.event {
+        MetaEventType::Lyric(ref t) => Some((&t.text, true)),
+        MetaEventType::TextEvent(ref t) => Some((&t.text, false)),
+        _ => None
+    }
+}
+
+fn is_words_track(name: &str) -> bool {
+    name.trim().eq_ignore_ascii_case("words")
+}
+
+// [mm:ss.xx], minutes go past 59
+fn lrc_time(seconds: f64) -> String {
+    let hundredths: u64 = (seconds.max(0.0) * 100.0).round() as u64;
+    format!("{:02}:{:02}.{:02}", hundredths / 6000, (hundredths / 100) % 60, hundredths % 100)
+}
+
+impl SMF {
+    // Lyric Events and Soft Karaoke Text Events are merged, Text Events are taken from the Words track when there
+    // is one. Files often carry the same words in both forms, a syllable found twice on the same tick is kept once
+    // "\" starts a paragraph, "/" and line feeds start a line
+    pub fn lyrics(&self) -> Lyrics {
+        let map: TempoMap = self.tempo_map();
+        let mut info = KaraokeInfo {
+            version: None,
+            language: None,
+            titles: Vec::new(),
+            information: Vec::new()
+        };
+        let mut lyric_events: Vec<(u64, String)> = Vec::new();
+        let mut text_events: Vec<(u64, String)> = Vec::new();
+        let mut words_events: Vec<(u64, String)> = Vec::new();
+        for track in &self.tracks {
+            let events = track.absolute_events();
+            let words: bool = events.iter().any(|(_, e)| match e.event {
+                EventType::MetaEvent(MetaEvent { event: MetaEventType::SequenceTrackName(ref t), .. }) => is_words_track(&t.text),
+                _ => false
+            });
+            for (tick, event) in events {
+                let (text, is_lyric) = match event.event {
+                    EventType::MetaEvent(ref m) => match text_of(m) {
+                        Some(t) => t,
+                        None => continue
+                    },
+                    _ => continue
+                };
+                if is_lyric {
+                    lyric_events.push((tick, text.to_string()));
+                    continue;
+                }
+                if text.starts_with('@') {
+                    let value: String = text.chars().skip(2).collect::<String>().trim().to_string();
+                    match text.chars().nth(1) {
+                        Some('V') => info.version = Some(value),
+                        Some('L') => info.language = Some(value),
+                        Some('T') => info.titles.push(value),
+                        Some('I') => info.information.push(value),
+                        _ => {}
+                    }
+                    continue;
+                }
+                if words {
+                    words_events.push((tick, text.to_string()));
+                }
+                text_events.push((tick, text.to_string()));
+            }
+        }
+        let mut sources: Vec<(u64, String)> = lyric_events;
+        sources.extend(if words_events.is_empty() { text_events } else { words_events });
+        sources.sort_by_key(|&(tick, _)| tick);
+        let mut syllables: Vec<(u64, String)> = Vec::new();
+        for (tick, text) in sources {
+            let twice: bool = syllables.iter().rev().take_while(|(t, _)| *t == tick).any(|(_, s)| *s == text);
+            if !twice {
+                syllables.push((tick, text));
+            }
+        }
+        let mut lines: Vec<LyricLine> = Vec::new();
+        let mut current: Option<LyricLine> = None;
+        let mut break_pending: bool = false;
+        let mut paragraph_pending: bool = false;
+        for (tick, raw) in syllables {
+            let mut text: &str = &raw;
+            if text.starts_with('\\') {
+                paragraph_pending = true;
+                text = &text[1..];
+            } else if text.starts_with('/') {
+                break_pending = true;
+                text = &text[1..];
+            }
+            if text.starts_with('\r') | text.starts_with('\n') {
+                break_pending = true;
+            }
+            let ends_line: bool = text.ends_with('\r') | text.ends_with('\n');
+            let clean: String = text.chars().filter(|&c| (c != '\r') & (c != '\n')).collect();
+            if !clean.is_empty() {
+                if break_pending | paragraph_pending {
+                    if let Some(line) = current.take() {
+                        lines.push(line);
+                    }
+                }
+                let seconds: f64 = map.tick_to_seconds(tick);
+                let line = current.get_or_insert_with(|| LyricLine {
+                    tick,
+                    seconds,
+                    new_paragraph: paragraph_pending,
+                    syllables: Vec::new()
+                });
+                line.syllables.push(Syllable { tick, seconds, text: clean });
+                break_pending = false;
+                paragraph_pending = false;
+            }
+            if ends_line {
+                break_pending = true;
+            }
+        }
+        if let Some(line) = current {
+            lines.push(line);
+        }
+        Lyrics {
+            info,
+            lines
+        }
+    }
+}
+
+impl Lyrics {
+    // Tags from the Soft Karaoke headers, then one timed line per lyric line
+    pub fn to_lrc(&self) -> String {
+        let mut out: String = self.tags();
+        for line in &self.lines {
+            out.push_str(&format!("[{}]{}\n", lrc_time(line.seconds), line.text()));
+        }
+        out
+    }
+
+    // Enhanced LRC, every syllable gets its own <mm:ss.xx> time
+    pub fn to_enhanced_lrc(&self) -> String {
+        let mut out: String = self.tags();
+        for line in &self.lines {
+            out.push_str(&format!("[{}]", lrc_time(line.seconds)));
+            for syllable in &line.syllables {
+                out.push_str(&format!("<{}>{}", lrc_time(syllable.seconds), syllable.text));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    fn tags(&self) -> String {
+        let mut out: String = String::new();
+        if let Some(title) = self.info.titles.first() {
+            out.push_str(&format!("[ti:{}]\n", title));
+        }
+        if let Some(artist) = self.info.titles.get(1) {
+            out.push_str(&format!("[ar:{}]\n", artist));
+        }
+        if let Some(ref language) = self.info.language {
+            out.push_str(&format!("[la:{}]\n", language));
+        }
+        out
+    }
+}
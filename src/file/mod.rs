@@ -1,16 +1,28 @@
 pub mod header;
 pub mod track;
+pub mod resolution;
+pub mod format;
+pub mod tempo;
+pub mod slice;
+pub mod loops;
+pub mod lyrics;
 
+use convert::rmid::Rmid;
+use convert::rmid::is_rmid;
 use self::header::SMFHeaderChunk;
+use self::header::data::MidiDivisionsType;
+use self::resolution::NotTicksPerQuarterNoteError;
 use self::track::SMFTrackChunk;
 use std::error::Error;
 use std::io::Read;
 use std::io::Seek;
+use std::io::Write;
 use std::result::Result;
 
 // Represents the Standard Midi File
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SMF {
     pub header: SMFHeaderChunk,
     pub tracks: Vec<SMFTrackChunk>
@@ -18,7 +30,11 @@ pub struct SMF {
 
 impl SMF {
     // Function for creating an SMF structure
+    // RMID files are unwrapped, use Rmid::read to get their INFO tags and DLS bank
     pub fn read<R: Read + Seek>(reader: &mut R) -> Result<SMF, Box<Error>> {
+        if is_rmid(reader)? {
+            return Ok(Rmid::read(reader)?.smf)
+        }
         let header: SMFHeaderChunk = SMFHeaderChunk::read(reader)?;
         let mut tracks: Vec<SMFTrackChunk> = Vec::with_capacity(header.nb_tracks as usize);
         for _ in 0..header.nb_tracks {
@@ -29,4 +45,24 @@ impl SMF {
             tracks
         })
     }
+
+    // Fails for files using SMTPE divisions
+    pub fn ticks_per_quarter_note(&self) -> Result<u16, Box<Error>> {
+        match self.header.division_system {
+            MidiDivisionsType::TicksPerQuarterNote(ref d) => Ok(d.ticks_per_quarter_note),
+            MidiDivisionsType::SMTPEFrames(_) => Err(Box::new(NotTicksPerQuarterNoteError))
+        }
+    }
+
+    // Writes the whole file, the number of tracks and their lengths are computed on the fly
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        let mut header: SMFHeaderChunk = self.header.clone();
+        header.length = 6;
+        header.nb_tracks = self.tracks.len() as u16;
+        header.write(writer)?;
+        for track in &self.tracks {
+            track.write(writer)?;
+        }
+        Ok(())
+    }
 }
\ No newline at end of file
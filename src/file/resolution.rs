@@ -0,0 +1,112 @@
+use super::SMF;
+use super::header::data::MidiDivisionsType;
+use std::error::Error;
+use std::result::Result;
+use std::fmt;
+
+#[derive(Debug)]
+pub struct NotTicksPerQuarterNoteError;
+
+impl Error for NotTicksPerQuarterNoteError {
+    fn description(&self) -> &str {
+        "This operation needs a file using the Ticks per Quarter Note division system"
+    }
+}
+
+impl fmt::Display for NotTicksPerQuarterNoteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "File does not use Ticks per Quarter Note divisions")
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidResolutionError;
+
+impl Error for InvalidResolutionError {
+    fn description(&self) -> &str {
+        "Ticks per Quarter Note must be between 1 and 32767"
+    }
+}
+
+impl fmt::Display for InvalidResolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid Ticks per Quarter Note value")
+    }
+}
+
+// An event that did not land exactly on its original position after rescaling
+
+#[derive(Clone)]
+pub struct MovedEvent {
+    pub track: usize,
+    pub index: usize,
+    pub old_tick: u64,
+    pub new_tick: u64,
+    pub error: f64  // new_tick minus the exact rescaled position, in new ticks
+}
+
+// What happened to the events during a resolution conversion
+
+#[derive(Clone)]
+pub struct ResolutionReport {
+    pub old_ticks_per_quarter_note: u16,
+    pub new_ticks_per_quarter_note: u16,
+    pub moved: Vec<MovedEvent>,
+    pub collapsed: Vec<MovedEvent>  // Events that had their own tick and now share it with the previous event
+}
+
+// Rounds old_tick * new_ppq / old_ppq to the nearest tick, halves go up
+pub fn rescale_tick(old_tick: u64, old_ppq: u16, new_ppq: u16) -> u64 {
+    let numerator: u64 = old_tick * u64::from(new_ppq) * 2 + u64::from(old_ppq);
+    numerator / (u64::from(old_ppq) * 2)
+}
+
+impl SMF {
+    // Rescales every delta time to a new Ticks per Quarter Note value
+    // Positions are rounded from absolute ticks so rounding errors never accumulate along a track
+    pub fn convert_resolution(&mut self, new_ppq: u16) -> Result<ResolutionReport, Box<Error>> {
+        if (new_ppq == 0) | (new_ppq > 0x7FFFu16) {
+            return Err(Box::new(InvalidResolutionError))
+        }
+        let old_ppq: u16 = self.ticks_per_quarter_note()?;
+        if old_ppq == 0 {
+            return Err(Box::new(InvalidResolutionError))
+        }
+        let mut moved: Vec<MovedEvent> = Vec::new();
+        let mut collapsed: Vec<MovedEvent> = Vec::new();
+        for (track_index, track) in self.tracks.iter_mut().enumerate() {
+            let old_ticks: Vec<u64> = track.absolute_ticks();
+            let mut previous_new_tick: u64 = 0;
+            for (index, track_event) in track.track_events.iter_mut().enumerate() {
+                let old_tick: u64 = old_ticks[index];
+                let new_tick: u64 = rescale_tick(old_tick, old_ppq, new_ppq);
+                let exact: f64 = (old_tick as f64) * f64::from(new_ppq) / f64::from(old_ppq);
+                let report = MovedEvent {
+                    track: track_index,
+                    index,
+                    old_tick,
+                    new_tick,
+                    error: (new_tick as f64) - exact
+                };
+                if (track_event.delta_time > 0) & (new_tick == previous_new_tick) {
+                    collapsed.push(report.clone());
+                }
+                if new_tick * u64::from(old_ppq) != old_tick * u64::from(new_ppq) {
+                    moved.push(report);
+                }
+                track_event.delta_time = (new_tick - previous_new_tick) as u32;
+                previous_new_tick = new_tick;
+            }
+            track.update_length()?;
+        }
+        if let MidiDivisionsType::TicksPerQuarterNote(ref mut d) = self.header.division_system {
+            d.ticks_per_quarter_note = new_ppq;
+        }
+        Ok(ResolutionReport {
+            old_ticks_per_quarter_note: old_ppq,
+            new_ticks_per_quarter_note: new_ppq,
+            moved,
+            collapsed
+        })
+    }
+}
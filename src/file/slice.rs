@@ -0,0 +1,274 @@
+use super::SMF;
+use super::format::WrongFormatError;
+use super::header::SMFHeaderChunk;
+use super::header::data::MidiDivisionsType;
+use super::header::data::MidiFormat;
+use super::resolution::NotTicksPerQuarterNoteError;
+use super::tempo::DEFAULT_TEMPO;
+use super::track::SMFTrackChunk;
+use super::track::data::event::Event;
+use super::track::data::event::EventType;
+use super::track::data::event::MetaEvent;
+use super::track::data::event::MetaEventType;
+use super::track::data::event::MidiEvent;
+use super::track::data::event::MidiEventType;
+use super::track::data::event::meta::SetTempo;
+use super::track::data::event::midi::NoteChange;
+use super::track::notes::Note;
+use state::MidiState;
+use std::error::Error;
+use std::result::Result;
+use std::fmt;
+
+#[derive(Debug)]
+pub struct InvalidRangeError;
+
+impl Error for InvalidRangeError {
+    fn description(&self) -> &str {
+        "The end of the range must be after its start"
+    }
+}
+
+impl fmt::Display for InvalidRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid range")
+    }
+}
+
+// State of a track right before the start of a slice, replayed at the start of the slice
+struct Chase {
+    metas: Vec<Event>,
+    sysex: Vec<Event>,
+    state: MidiState
+}
+
+impl Chase {
+    fn new() -> Chase {
+        Chase {
+            metas: Vec::new(),
+            sysex: Vec::new(),
+            state: MidiState::new()
+        }
+    }
+
+    fn feed(&mut self, event: &Event) {
+        match event.event {
+            EventType::MidiEvent(ref e) => self.state.feed(e),
+            EventType::SysExEvent(_) => self.sysex.push(event.clone()),
+            EventType::MetaEvent(ref e) => {
+                match e.event {
+                    MetaEventType::SequenceTrackName(_) | MetaEventType::InstrumentName(_) | MetaEventType::MIDIPort(_)
+                    | MetaEventType::SetTempo(_) | MetaEventType::TimeSignature(_) | MetaEventType::KeySignature(_) => {
+                        self.metas.retain(|m| match m.event {
+                            EventType::MetaEvent(ref old) => old.sub_code_byte != e.sub_code_byte,
+                            _ => true
+                        });
+                        self.metas.push(event.clone());
+                    },
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    // Metas first, then Sysex, then the state of every channel
+    fn events(&self) -> Vec<Event> {
+        let mut events: Vec<Event> = self.metas.clone();
+        events.extend(self.sysex.iter().cloned());
+        events.extend(self.state.to_events());
+        events
+    }
+}
+
+fn note_event(note: &Note, on: bool) -> Event {
+    let change: NoteChange = NoteChange {
+        key: note.key,
+        velocity: if on { note.velocity } else { note.off_velocity }
+    };
+    let event: MidiEventType = if on { MidiEventType::NoteOn(change) } else { MidiEventType::NoteOff(change) };
+    Event::from_midi(MidiEvent::from_type(note.channel, event))
+}
+
+// Cuts a track to [start, end), the state at start is chased and notes crossing the bounds are restarted or closed
+pub fn slice_track(track: &SMFTrackChunk, start: u64, end: u64) -> Result<SMFTrackChunk, Box<Error>> {
+    if end <= start {
+        return Err(Box::new(InvalidRangeError))
+    }
+    let notes: Vec<Note> = track.notes();
+    // Releases on start of notes that started before, they are not played again
+    let skipped_offs: Vec<usize> = notes.iter()
+        .filter(|n| (n.start < start) & (n.end == start))
+        .filter_map(|n| n.off_index)
+        .collect();
+    let ticks: Vec<u64> = track.absolute_ticks();
+    let mut chase: Chase = Chase::new();
+    let mut in_range: Vec<(u64, Event)> = Vec::new();
+    for (index, track_event) in track.track_events.iter().enumerate() {
+        let tick: u64 = ticks[index];
+        if track_event.event.is_end_of_track() | (tick >= end) | skipped_offs.contains(&index) {
+            continue;
+        }
+        if tick < start {
+            chase.feed(&track_event.event);
+        } else {
+            in_range.push((tick - start, track_event.event.clone()));
+        }
+    }
+    let mut output: Vec<(u64, Event)> = chase.events().into_iter().map(|e| (0, e)).collect();
+    // Notes already sounding at start are played again
+    for note in &notes {
+        if (note.start < start) & (note.end > start) {
+            output.push((0, note_event(note, true)));
+        }
+    }
+    output.extend(in_range);
+    // Notes still sounding at end are released there
+    for note in &notes {
+        if (note.start < end) & (note.end >= end) {
+            output.push((end - start, note_event(note, false)));
+        }
+    }
+    output.push((end - start, Event::end_of_track()));
+    SMFTrackChunk::from_absolute_events(output)
+}
+
+impl SMF {
+    // Tick where the longest track ends
+    pub fn end_tick(&self) -> u64 {
+        self.tracks.iter().map(|t| t.end_tick()).max().unwrap_or(0)
+    }
+
+    // Keeps [start, end) of every track, the excerpt starts at tick 0 with tempo, meter, key, programs,
+    // controllers and pitch bends as they were at start
+    pub fn slice_ticks(&self, start: u64, end: u64) -> Result<SMF, Box<Error>> {
+        let mut tracks: Vec<SMFTrackChunk> = Vec::with_capacity(self.tracks.len());
+        for track in &self.tracks {
+            tracks.push(slice_track(track, start, end)?);
+        }
+        Ok(SMF {
+            header: self.header.clone(),
+            tracks
+        })
+    }
+
+    // Same as slice_ticks, with bounds in seconds following the tempo map
+    pub fn slice_seconds(&self, start: f64, end: f64) -> Result<SMF, Box<Error>> {
+        let map = self.tempo_map();
+        self.slice_ticks(map.seconds_to_tick(start), map.seconds_to_tick(end))
+    }
+
+    // Drops everything before start and after end, same as slice_ticks
+    pub fn trim(&mut self, start: u64, end: u64) -> Result<(), Box<Error>> {
+        *self = self.slice_ticks(start, end)?;
+        Ok(())
+    }
+
+    // Plays other after this file, other is converted to this resolution and its tempo map is appended
+    // Two Format 0 files give a Format 0 file, anything else gives a Format 1 file
+    // Tracks are paired by index after the conversion to Format 1: track n of other follows track n of this file,
+    // whatever their names or channels, and the file with fewer tracks leaves the extra ones silent on its side
+    pub fn concatenate(&self, other: &SMF) -> Result<SMF, Box<Error>> {
+        let mut other: SMF = other.clone();
+        match (&self.header.division_system, &other.header.division_system) {
+            (MidiDivisionsType::TicksPerQuarterNote(a), MidiDivisionsType::TicksPerQuarterNote(b)) => {
+                if a.ticks_per_quarter_note != b.ticks_per_quarter_note {
+                    other.convert_resolution(a.ticks_per_quarter_note)?;
+                }
+            },
+            (MidiDivisionsType::SMTPEFrames(a), MidiDivisionsType::SMTPEFrames(b)) => {
+                if (a.smtpe_frames_per_second != b.smtpe_frames_per_second) | (a.ticks_per_smtpe_frame != b.ticks_per_smtpe_frame) {
+                    return Err(Box::new(NotTicksPerQuarterNoteError))
+                }
+            },
+            _ => return Err(Box::new(NotTicksPerQuarterNoteError))
+        }
+        let (first, second, format) = match (&self.header.format, &other.header.format) {
+            (&MidiFormat::IndependentTracks, _) | (_, &MidiFormat::IndependentTracks) => return Err(Box::new(WrongFormatError)),
+            (&MidiFormat::SingleTrack, &MidiFormat::SingleTrack) => (self.clone(), other, MidiFormat::SingleTrack),
+            _ => (self.to_format_1()?, other.to_format_1()?, MidiFormat::SimultaneousTracks)
+        };
+        let offset: u64 = first.end_tick();
+        let end_tempo: u32 = first.tempo_map().tempo_at(offset);
+        let second_has_tempo: bool = second.tracks.iter()
+            .any(|t| t.absolute_events().iter().any(|&(tick, ref e)| (tick == 0) & is_set_tempo(e)));
+        let nb_tracks: usize = first.tracks.len().max(second.tracks.len());
+        let mut tracks: Vec<SMFTrackChunk> = Vec::with_capacity(nb_tracks);
+        for index in 0..nb_tracks {
+            let mut events: Vec<(u64, Event)> = match first.tracks.get(index) {
+                Some(t) => t.absolute_events().into_iter().filter(|(_, e)| !e.is_end_of_track()).collect(),
+                None => Vec::new()
+            };
+            let has_name: bool = events.iter().any(|(_, e)| is_track_name(e));
+            // The second file starts with its own tempo, which may be the default one
+            if (index == 0) & !second_has_tempo & (end_tempo != DEFAULT_TEMPO) {
+                events.push((offset, Event::from_meta(MetaEvent::from_type(MetaEventType::SetTempo(SetTempo { tempo: DEFAULT_TEMPO }))?)));
+            }
+            if let Some(t) = second.tracks.get(index) {
+                for (tick, event) in t.absolute_events() {
+                    if has_name & is_track_name(&event) {
+                        continue;
+                    }
+                    events.push((offset + tick, event));
+                }
+            } else {
+                events.push((offset, Event::end_of_track()));
+            }
+            tracks.push(SMFTrackChunk::from_absolute_events(events)?);
+        }
+        Ok(SMF {
+            header: SMFHeaderChunk {
+                length: 6,
+                format,
+                nb_tracks: tracks.len() as u16,
+                division_system: first.header.division_system.clone()
+            },
+            tracks
+        })
+    }
+
+    // Plays [start, end) times times in a row, the rest of the file follows
+    // With times at 0 the section is cut out, what is left of a file that was only the section is empty tracks
+    pub fn repeat_section(&self, start: u64, end: u64, times: u32) -> Result<SMF, Box<Error>> {
+        if end <= start {
+            return Err(Box::new(InvalidRangeError))
+        }
+        let total: u64 = self.end_tick();
+        let section: SMF = self.slice_ticks(start, end)?;
+        let mut pieces: Vec<SMF> = Vec::new();
+        if start > 0 {
+            pieces.push(self.slice_ticks(0, start)?);
+        }
+        for _ in 0..times {
+            pieces.push(section.clone());
+        }
+        if end < total {
+            pieces.push(self.slice_ticks(end, total)?);
+        }
+        let mut pieces = pieces.into_iter();
+        let mut result: SMF = match pieces.next() {
+            Some(p) => p,
+            None => {
+                let mut tracks: Vec<SMFTrackChunk> = Vec::with_capacity(self.tracks.len());
+                for _ in &self.tracks {
+                    tracks.push(SMFTrackChunk::from_absolute_events(Vec::new())?);
+                }
+                return Ok(SMF {
+                    header: self.header.clone(),
+                    tracks
+                })
+            }
+        };
+        for piece in pieces {
+            result = result.concatenate(&piece)?;
+        }
+        Ok(result)
+    }
+}
+
+fn is_set_tempo(event: &Event) -> bool {
+    matches!(event.event, EventType::MetaEvent(MetaEvent { event: MetaEventType::SetTempo(_), .. }))
+}
+
+fn is_track_name(event: &Event) -> bool {
+    matches!(event.event, EventType::MetaEvent(MetaEvent { event: MetaEventType::SequenceTrackName(_), .. }))
+}
@@ -0,0 +1,144 @@
+use super::SMF;
+use super::header::data::MidiDivisionsType;
+use super::header::data::MidiFormat;
+use super::track::data::event::EventType;
+use super::track::data::event::MetaEvent;
+use super::track::data::event::MetaEventType;
+
+// Tempo used until the first Set Tempo, 120 BPM
+pub const DEFAULT_TEMPO: u32 = 500_000;
+
+// Set Tempo at an absolute tick
+
+#[derive(Clone)]
+pub struct TempoChange {
+    pub tick: u64,
+    pub tempo: u32,  // Microseconds per quarter note
+    pub micros: f64  // Time at which this tempo starts
+}
+
+// Converts between ticks and time, following every Set Tempo of a file
+
+#[derive(Clone)]
+pub struct TempoMap {
+    pub division_system: MidiDivisionsType,
+    pub changes: Vec<TempoChange>
+}
+
+// Real number of frames per second of an SMTPE division
+pub fn smtpe_frame_rate(frames_per_second: u16) -> f64 {
+    if frames_per_second == 29 {
+        30_000.0 / 1001.0
+    } else {
+        f64::from(frames_per_second)
+    }
+}
+
+impl TempoMap {
+    // Set Tempos are taken from every track, except for Format 2 where only the first pattern is used
+    pub fn from_smf(smf: &SMF) -> TempoMap {
+        let mut raw: Vec<(u64, u32)> = Vec::new();
+        let tracks = match smf.header.format {
+            MidiFormat::IndependentTracks => &smf.tracks[..smf.tracks.len().min(1)],
+            _ => &smf.tracks[..]
+        };
+        for track in tracks {
+            for (tick, event) in track.absolute_events() {
+                if let EventType::MetaEvent(MetaEvent { event: MetaEventType::SetTempo(ref t), .. }) = event.event {
+                    raw.push((tick, t.tempo));
+                }
+            }
+        }
+        TempoMap::new(smf.header.division_system.clone(), raw)
+    }
+
+    // Builds a map from (tick, tempo) pairs, in any order
+    pub fn new(division_system: MidiDivisionsType, mut raw: Vec<(u64, u32)>) -> TempoMap {
+        raw.sort_by_key(|&(tick, _)| tick);
+        let mut map = TempoMap {
+            division_system,
+            changes: vec![TempoChange { tick: 0, tempo: DEFAULT_TEMPO, micros: 0.0 }]
+        };
+        for (tick, tempo) in raw {
+            let micros: f64 = map.tick_to_micros(tick);
+            // A later change on the same tick replaces the earlier one
+            if map.changes.last().map(|c| c.tick) == Some(tick) {
+                map.changes.pop();
+            }
+            map.changes.push(TempoChange { tick, tempo, micros });
+        }
+        map
+    }
+
+    // Tempo in effect at this tick
+    pub fn tempo_at(&self, tick: u64) -> u32 {
+        self.change_at_tick(tick).tempo
+    }
+
+    pub fn tick_to_micros(&self, tick: u64) -> f64 {
+        let change: &TempoChange = self.change_at_tick(tick);
+        change.micros + ((tick - change.tick) as f64) * self.micros_per_tick(change.tempo)
+    }
+
+    // Position between two ticks, tempo changes only happen on whole ticks
+    pub fn fractional_tick_to_micros(&self, tick: f64) -> f64 {
+        if tick <= 0.0 {
+            return 0.0
+        }
+        let whole: u64 = tick.floor() as u64;
+        let change: &TempoChange = self.change_at_tick(whole);
+        self.tick_to_micros(whole) + (tick - whole as f64) * self.micros_per_tick(change.tempo)
+    }
+
+    pub fn tick_to_seconds(&self, tick: u64) -> f64 {
+        self.tick_to_micros(tick) / 1_000_000.0
+    }
+
+    // Rounded to the nearest tick
+    pub fn micros_to_tick(&self, micros: f64) -> u64 {
+        if micros <= 0.0 {
+            return 0
+        }
+        let mut change: &TempoChange = &self.changes[0];
+        for c in &self.changes {
+            if c.micros > micros {
+                break;
+            }
+            change = c;
+        }
+        change.tick + ((micros - change.micros) / self.micros_per_tick(change.tempo)).round() as u64
+    }
+
+    pub fn seconds_to_tick(&self, seconds: f64) -> u64 {
+        self.micros_to_tick(seconds * 1_000_000.0)
+    }
+
+    // Duration of one tick with this tempo, SMTPE divisions ignore the tempo
+    pub fn micros_per_tick(&self, tempo: u32) -> f64 {
+        match self.division_system {
+            MidiDivisionsType::TicksPerQuarterNote(ref d) => {
+                f64::from(tempo) / f64::from(d.ticks_per_quarter_note.max(1))
+            },
+            MidiDivisionsType::SMTPEFrames(ref d) => {
+                1_000_000.0 / (smtpe_frame_rate(d.smtpe_frames_per_second) * f64::from(d.ticks_per_smtpe_frame.max(1)))
+            }
+        }
+    }
+
+    fn change_at_tick(&self, tick: u64) -> &TempoChange {
+        let mut change: &TempoChange = &self.changes[0];
+        for c in &self.changes {
+            if c.tick > tick {
+                break;
+            }
+            change = c;
+        }
+        change
+    }
+}
+
+impl SMF {
+    pub fn tempo_map(&self) -> TempoMap {
+        TempoMap::from_smf(self)
+    }
+}
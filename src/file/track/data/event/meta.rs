@@ -1,10 +1,13 @@
 use super::super::super::super::super::VLVRead;
+use super::super::super::super::super::VLVWrite;
 use ez_io::ReadE;
 use std::io::Read;
+use std::io::Write;
 use std::error::Error;
 use std::result::Result;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SequenceNumber {
     pub sequence_number: u16
 }
@@ -16,9 +19,15 @@ impl SequenceNumber {
             sequence_number
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_all(&[(self.sequence_number >> 8) as u8, self.sequence_number as u8])?;
+        Ok(())
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Text {
     pub text: String
 }
@@ -32,9 +41,15 @@ impl Text {
             text
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_all(self.text.as_bytes())?;
+        Ok(())
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MIDIChannelPrefix {
     pub channel: u8
 }
@@ -46,9 +61,15 @@ impl MIDIChannelPrefix {
             channel
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_all(&[self.channel])?;
+        Ok(())
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MIDIPort {
     pub port: u8
 }
@@ -60,26 +81,40 @@ impl MIDIPort {
             port
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_all(&[self.port])?;
+        Ok(())
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EndOfTrack {}
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SetTempo {
     pub tempo: u32
 }
 
 impl SetTempo {
     pub fn read<R: Read>(reader: &mut R) -> Result<SetTempo, Box<Error>> {
-        let tempo: u32 = u32::from(reader.read_be_to_u16()?) + u32::from(reader.read_to_u8()?);
+        // 24 bits, microseconds per quarter note
+        let tempo: u32 = (u32::from(reader.read_be_to_u16()?) << 8) | u32::from(reader.read_to_u8()?);
         Ok(SetTempo {
             tempo
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_all(&[(self.tempo >> 16) as u8, (self.tempo >> 8) as u8, self.tempo as u8])?;
+        Ok(())
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SMTPEOffset {
     pub hour: u8,
     pub minute: u8,
@@ -103,13 +138,20 @@ impl SMTPEOffset {
             hundred_of_frame
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_all(&[self.hour, self.minute, self.seconds, self.frames, self.hundred_of_frame])?;
+        Ok(())
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TimeSignature {
     pub nominator: u8,
     pub denominator: u8,  // Expressed as a power of two
     pub midi_ticks_per_metronome_tick: u8,
+    #[cfg_attr(feature = "serde", serde(rename = "thirty_second_notes_per_quarter_note"))]
     pub thing: u8
 }
 
@@ -126,9 +168,15 @@ impl TimeSignature {
             thing
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_all(&[self.nominator, self.denominator, self.midi_ticks_per_metronome_tick, self.thing])?;
+        Ok(())
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct KeySignature {
     pub number_of_sharp_flats: u8,
     pub major_key: bool
@@ -144,9 +192,16 @@ impl KeySignature {
             major_key: major_key_bool
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        let major_key: u8 = if self.major_key { 0 } else { 1 };
+        writer.write_all(&[self.number_of_sharp_flats, major_key])?;
+        Ok(())
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SequencerSpecific {
     pub length: u32,
     pub id: u32,
@@ -171,7 +226,33 @@ impl SequencerSpecific {
             data
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_vlv(self.id)?;
+        writer.write_all(&self.data)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Unknown {
+    pub sub_code_byte: u8,
+    pub data: Vec<u8>
 }
 
-#[derive(Clone)]
-pub struct Unknown {}
\ No newline at end of file
+impl Unknown {
+    pub fn read<R: Read>(reader: &mut R, sub_code_byte: u8, length: u32) -> Result<Unknown, Box<Error>> {
+        let mut data: Vec<u8> = vec![0; length as usize];
+        reader.read_exact(&mut data)?;
+        Ok(Unknown {
+            sub_code_byte,
+            data
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_all(&self.data)?;
+        Ok(())
+    }
+}
\ No newline at end of file
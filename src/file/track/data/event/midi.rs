@@ -1,10 +1,11 @@
 use ez_io::ReadE;
 use std::io::Read;
+use std::io::Write;
 use std::error::Error;
 use std::result::Result;
-use std::mem::transmute;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NoteChange {
     pub key: u8,
     pub velocity: u8
@@ -12,20 +13,25 @@ pub struct NoteChange {
 
 impl NoteChange {
     pub fn read<R: Read>(reader: &mut R, running_status_byte: Option<u8>) -> Result<NoteChange, Box<Error>> {
-        let key: u8;
-        match running_status_byte {
-            Some(x) => key = x,
-            None    => key = reader.read_to_u8()?,
-        }
+        let key: u8 = match running_status_byte {
+            Some(x) => x,
+            None    => reader.read_to_u8()?
+        };
         let velocity: u8 = reader.read_to_u8()?;
         Ok(NoteChange{
             key,
             velocity
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_all(&[self.key, self.velocity])?;
+        Ok(())
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PolyphonicKeyPressure {
     pub key: u8,
     pub pressure: u8
@@ -33,20 +39,25 @@ pub struct PolyphonicKeyPressure {
 
 impl PolyphonicKeyPressure {
     pub fn read<R: Read>(reader: &mut R, running_status_byte: Option<u8>) -> Result<PolyphonicKeyPressure, Box<Error>> {
-        let key: u8;
-        match running_status_byte {
-            Some(x) => key = x,
-            None    => key = reader.read_to_u8()?,
-        }
+        let key: u8 = match running_status_byte {
+            Some(x) => x,
+            None    => reader.read_to_u8()?
+        };
         let pressure: u8 = reader.read_to_u8()?;
         Ok(PolyphonicKeyPressure {
             key,
             pressure
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_all(&[self.key, self.pressure])?;
+        Ok(())
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ControllerChange {
     pub controller_number: u8,
     pub controller_value: u8
@@ -54,69 +65,137 @@ pub struct ControllerChange {
 
 impl ControllerChange {
     pub fn read<R: Read>(reader: &mut R, running_status_byte: Option<u8>) -> Result<ControllerChange, Box<Error>> {
-        let controller_number: u8;
-        match running_status_byte {
-            Some(x) => controller_number = x,
-            None    => controller_number = reader.read_to_u8()?,
-        }
+        let controller_number: u8 = match running_status_byte {
+            Some(x) => x,
+            None    => reader.read_to_u8()?
+        };
         let controller_value: u8 = reader.read_to_u8()?;
         Ok(ControllerChange {
             controller_number,
             controller_value
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_all(&[self.controller_number, self.controller_value])?;
+        Ok(())
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ProgramChange {
     pub new_program_number: u8,
 }
 
 impl ProgramChange {
     pub fn read<R: Read>(reader: &mut R, running_status_byte: Option<u8>) -> Result<ProgramChange, Box<Error>> {
-        let new_program_number: u8;
-        match running_status_byte {
-            Some(x) => new_program_number = x,
-            None    => new_program_number = reader.read_to_u8()?,
-        }
+        let new_program_number: u8 = match running_status_byte {
+            Some(x) => x,
+            None    => reader.read_to_u8()?
+        };
         Ok(ProgramChange {
             new_program_number
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_all(&[self.new_program_number])?;
+        Ok(())
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ChannelKeyPressure {
     pub value: u8
 }
 
 impl ChannelKeyPressure {
     pub fn read<R: Read>(reader: &mut R, running_status_byte: Option<u8>) -> Result<ChannelKeyPressure, Box<Error>> {
-        let value: u8;
-        match running_status_byte {
-            Some(x) => value = x,
-            None    => value = reader.read_to_u8()?,
-        }
+        let value: u8 = match running_status_byte {
+            Some(x) => x,
+            None    => reader.read_to_u8()?
+        };
         Ok(ChannelKeyPressure {
             value
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_all(&[self.value])?;
+        Ok(())
+    }
 }
 
-#[derive(Clone)]
+// Value of a Pitch Bend that does not bend
+pub const PITCH_BEND_CENTER: u16 = 0x2000;
+
+// Bend range of General MIDI devices until RPN 0 is received, in cents
+pub const DEFAULT_PITCH_BEND_RANGE: f64 = 200.0;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PitchBend {
     pub value: u16
 }
 
 impl PitchBend {
     pub fn read<R: Read>(reader: &mut R, running_status_byte: Option<u8>) -> Result<PitchBend, Box<Error>> {
-        let value: u16;
-        match running_status_byte {
-            Some(x) => value = unsafe {transmute::<[u8; 2], u16>([x, reader.read_to_u8()?])},
-            None    => value = reader.read_be_to_u16()?,
-        }
+        let lsb: u8 = match running_status_byte {
+            Some(x) => x,
+            None    => reader.read_to_u8()?
+        };
+        let msb: u8 = reader.read_to_u8()?;
+        // 14 bits, LSB first
+        let value: u16 = (u16::from(msb & 0x7Fu8) << 7) | u16::from(lsb & 0x7Fu8);
         Ok(PitchBend {
             value
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_all(&[(self.value & 0x7Fu16) as u8, ((self.value >> 7) & 0x7Fu16) as u8])?;
+        Ok(())
+    }
+
+    // No bend, 0x2000
+    pub fn center() -> PitchBend {
+        PitchBend {
+            value: PITCH_BEND_CENTER
+        }
+    }
+
+    // Offset from the center, clamped to -8192..8191
+    pub fn from_offset(offset: i32) -> PitchBend {
+        let offset: i32 = offset.clamp(-8192, 8191);
+        PitchBend {
+            value: (offset + i32::from(PITCH_BEND_CENTER)) as u16
+        }
+    }
+
+    // -1.0 is the lowest value, the highest one is 8191 / 8192 so the scale stays linear
+    pub fn from_normalized(normalized: f64) -> PitchBend {
+        PitchBend::from_offset((normalized * 8192.0).round() as i32)
+    }
+
+    // Bend reaching this many cents on a channel whose bend range is range_cents (200 by default)
+    pub fn from_cents(cents: f64, range_cents: f64) -> PitchBend {
+        if range_cents <= 0.0 {
+            return PitchBend::center()
+        }
+        PitchBend::from_normalized(cents / range_cents)
+    }
+
+    pub fn offset(&self) -> i16 {
+        (self.value & 0x3FFFu16) as i16 - PITCH_BEND_CENTER as i16
+    }
+
+    pub fn normalized(&self) -> f64 {
+        f64::from(self.offset()) / 8192.0
+    }
+
+    pub fn cents(&self, range_cents: f64) -> f64 {
+        self.normalized() * range_cents
+    }
 }
\ No newline at end of file
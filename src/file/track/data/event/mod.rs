@@ -3,15 +3,18 @@ pub mod sysex;
 pub mod meta;
 
 use super::super::super::super::VLVRead;
+use super::super::super::super::VLVWrite;
 use ez_io::ReadE;
 use self::midi::*;
 use self::sysex::*;
 use self::meta::*;
 use std::error::Error;
 use std::fmt;
+use std::io::Cursor;
 use std::io::Read;
 use std::io::Seek;
 use std::io::SeekFrom;
+use std::io::Write;
 use std::result::Result;
 
 #[derive(Debug)]
@@ -44,7 +47,9 @@ impl fmt::Display for UnknownEventError {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
 pub enum MidiEventType {
     NoteOff(NoteChange),
     NoteOn(NoteChange),
@@ -55,7 +60,23 @@ pub enum MidiEventType {
     PitchBend(PitchBend)
 }
 
-#[derive(Clone)]
+impl MidiEventType {
+    // Upper nibble of the status byte for this kind of event
+    pub fn code_byte(&self) -> u8 {
+        match *self {
+            MidiEventType::NoteOff(_) => 0x80u8,
+            MidiEventType::NoteOn(_) => 0x90u8,
+            MidiEventType::PolyphonicKeyPressure(_) => 0xA0u8,
+            MidiEventType::ControllerChange(_) => 0xB0u8,
+            MidiEventType::ProgramChange(_) => 0xC0u8,
+            MidiEventType::ChannelKeyPressure(_) => 0xD0u8,
+            MidiEventType::PitchBend(_) => 0xE0u8
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MidiEvent {
     pub code_byte: u8,
     pub channel: u8,
@@ -63,9 +84,17 @@ pub struct MidiEvent {
 }
 
 impl MidiEvent {
+    pub fn from_type(channel: u8, event: MidiEventType) -> MidiEvent {
+        MidiEvent {
+            code_byte: event.code_byte(),
+            channel: channel & 0x0Fu8,
+            event
+        }
+    }
+
     pub fn read<R: Read>(reader: &mut R, code_byte: u8, running_status_byte: Option<u8>) -> Result<MidiEvent, Box<Error>> {
-        let code_byte: u8 = code_byte & 0xF0u8;
         let channel: u8 = code_byte & 0x0Fu8;
+        let code_byte: u8 = code_byte & 0xF0u8;
         if code_byte == 0x80u8 {
             // Note Off
             Ok(MidiEvent {
@@ -73,13 +102,20 @@ impl MidiEvent {
                 channel,
                 event: MidiEventType::NoteOff(NoteChange::read(reader, running_status_byte)?)
             })
-        } else if (code_byte == 0x90u8) || (code_byte == 0xA0u8) {
-            // Note On || Polyphonic Key Pressure
+        } else if code_byte == 0x90u8 {
+            // Note On
             Ok(MidiEvent {
                 code_byte,
                 channel,
                 event: MidiEventType::NoteOn(NoteChange::read(reader, running_status_byte)?)
             })
+        } else if code_byte == 0xA0u8 {
+            // Polyphonic Key Pressure
+            Ok(MidiEvent {
+                code_byte,
+                channel,
+                event: MidiEventType::PolyphonicKeyPressure(PolyphonicKeyPressure::read(reader, running_status_byte)?)
+            })
         } else if code_byte == 0xB0u8 {
             // Controller Change
             Ok(MidiEvent {
@@ -112,16 +148,32 @@ impl MidiEvent {
             Err(Box::new(UnknownEventError))
         }
     }
+
+    // Only writes the data bytes, the status byte is written by the Event
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        match self.event {
+            MidiEventType::NoteOff(ref e) => e.write(writer),
+            MidiEventType::NoteOn(ref e) => e.write(writer),
+            MidiEventType::PolyphonicKeyPressure(ref e) => e.write(writer),
+            MidiEventType::ControllerChange(ref e) => e.write(writer),
+            MidiEventType::ProgramChange(ref e) => e.write(writer),
+            MidiEventType::ChannelKeyPressure(ref e) => e.write(writer),
+            MidiEventType::PitchBend(ref e) => e.write(writer)
+        }
+    }
 }
 
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
 pub enum SysexEventType {
     F0SysexEvent(Sysex),
     F7SysexEvent(Sysex)
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SysexEvent {
     pub event: SysexEventType
 }
@@ -143,10 +195,20 @@ impl SysexEvent {
             Err(Box::new(UnknownEventError))
         }
     }
+
+    // Only writes the length and data, the F0 or F7 byte is written by the Event
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        match self.event {
+            SysexEventType::F0SysexEvent(ref e) => e.write(writer),
+            SysexEventType::F7SysexEvent(ref e) => e.write(writer)
+        }
+    }
 }
 
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
 pub enum MetaEventType {
     SequenceNumber(SequenceNumber),
     TextEvent(Text),
@@ -169,7 +231,61 @@ pub enum MetaEventType {
     Unknown(Unknown)
 }
 
-#[derive(Clone)]
+impl MetaEventType {
+    pub fn sub_code_byte(&self) -> u8 {
+        match *self {
+            MetaEventType::SequenceNumber(_) => 0x00u8,
+            MetaEventType::TextEvent(_) => 0x01u8,
+            MetaEventType::CopyrightNotice(_) => 0x02u8,
+            MetaEventType::SequenceTrackName(_) => 0x03u8,
+            MetaEventType::InstrumentName(_) => 0x04u8,
+            MetaEventType::Lyric(_) => 0x05u8,
+            MetaEventType::Marker(_) => 0x06u8,
+            MetaEventType::CuePoint(_) => 0x07u8,
+            MetaEventType::ProgramName(_) => 0x08u8,
+            MetaEventType::DeviceName(_) => 0x09u8,
+            MetaEventType::MIDIChannelPrefix(_) => 0x20u8,
+            MetaEventType::MIDIPort(_) => 0x21u8,
+            MetaEventType::EndOfTrack(_) => 0x2Fu8,
+            MetaEventType::SetTempo(_) => 0x51u8,
+            MetaEventType::SMTPEOffset(_) => 0x54u8,
+            MetaEventType::TimeSignature(_) => 0x58u8,
+            MetaEventType::KeySignature(_) => 0x59u8,
+            MetaEventType::SequencerSpecific(_) => 0x7Fu8,
+            MetaEventType::Unknown(ref e) => e.sub_code_byte
+        }
+    }
+
+    // The data bytes of this event as they are written in a file, without sub code byte and length
+    pub fn data_bytes(&self) -> Result<Vec<u8>, Box<Error>> {
+        let mut data: Vec<u8> = Vec::new();
+        match *self {
+            MetaEventType::SequenceNumber(ref e) => e.write(&mut data)?,
+            MetaEventType::TextEvent(ref e) => e.write(&mut data)?,
+            MetaEventType::CopyrightNotice(ref e) => e.write(&mut data)?,
+            MetaEventType::SequenceTrackName(ref e) => e.write(&mut data)?,
+            MetaEventType::InstrumentName(ref e) => e.write(&mut data)?,
+            MetaEventType::Lyric(ref e) => e.write(&mut data)?,
+            MetaEventType::Marker(ref e) => e.write(&mut data)?,
+            MetaEventType::CuePoint(ref e) => e.write(&mut data)?,
+            MetaEventType::ProgramName(ref e) => e.write(&mut data)?,
+            MetaEventType::DeviceName(ref e) => e.write(&mut data)?,
+            MetaEventType::MIDIChannelPrefix(ref e) => e.write(&mut data)?,
+            MetaEventType::MIDIPort(ref e) => e.write(&mut data)?,
+            MetaEventType::EndOfTrack(_) => {},
+            MetaEventType::SetTempo(ref e) => e.write(&mut data)?,
+            MetaEventType::SMTPEOffset(ref e) => e.write(&mut data)?,
+            MetaEventType::TimeSignature(ref e) => e.write(&mut data)?,
+            MetaEventType::KeySignature(ref e) => e.write(&mut data)?,
+            MetaEventType::SequencerSpecific(ref e) => e.write(&mut data)?,
+            MetaEventType::Unknown(ref e) => e.write(&mut data)?
+        }
+        Ok(data)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MetaEvent {
     pub sub_code_byte: u8,
     pub length: u32,
@@ -177,6 +293,23 @@ pub struct MetaEvent {
 }
 
 impl MetaEvent {
+    pub fn from_type(event: MetaEventType) -> Result<MetaEvent, Box<Error>> {
+        let length: u32 = event.data_bytes()?.len() as u32;
+        Ok(MetaEvent {
+            sub_code_byte: event.sub_code_byte(),
+            length,
+            event
+        })
+    }
+
+    // Parses the data bytes of a Meta Event the way they would be read from a file
+    pub fn from_bytes(sub_code_byte: u8, data: &[u8]) -> Result<MetaEvent, Box<Error>> {
+        let mut bytes: Vec<u8> = vec![sub_code_byte];
+        bytes.write_vlv(data.len() as u32)?;
+        bytes.extend_from_slice(data);
+        MetaEvent::read(&mut Cursor::new(bytes))
+    }
+
     pub fn read<R: Read + Seek>(reader: &mut R) -> Result<MetaEvent, Box<Error>> {
         let sub_code_byte: u8 = reader.read_to_u8()?;
         let length: u32 = reader.read_vlv()?.data;
@@ -245,9 +378,8 @@ impl MetaEvent {
             // Sequencer-Specific
             event = MetaEventType::SequencerSpecific(meta::SequencerSpecific::read(reader, length)?);
         } else {
-            // Unknown, skip this stuff
-            event = MetaEventType::Unknown(meta::Unknown {});
-            to_skip = length;
+            // Unknown, keep the raw data
+            event = MetaEventType::Unknown(meta::Unknown::read(reader, sub_code_byte, length)?);
         }
         reader.seek(SeekFrom::Current(i64::from(to_skip)))?;
         Ok(MetaEvent {
@@ -256,28 +388,85 @@ impl MetaEvent {
             event
         })
     }
+
+    // Writes the sub code byte, length and data, the length is computed from the data
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        let data: Vec<u8> = self.event.data_bytes()?;
+        writer.write_all(&[self.sub_code_byte])?;
+        writer.write_vlv(data.len() as u32)?;
+        writer.write_all(&data)?;
+        Ok(())
+    }
 }
 
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
 pub enum EventType {
     MidiEvent(MidiEvent),
     SysExEvent(SysexEvent),
     MetaEvent(MetaEvent)
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Event {
     pub code_byte: u8,
     pub event: EventType
 }
 
 impl Event {
+    pub fn from_midi(event: MidiEvent) -> Event {
+        Event {
+            code_byte: event.code_byte | event.channel,
+            event: EventType::MidiEvent(event)
+        }
+    }
+
+    pub fn from_sysex(event: SysexEvent) -> Event {
+        let code_byte: u8 = match event.event {
+            SysexEventType::F0SysexEvent(_) => 0xF0u8,
+            SysexEventType::F7SysexEvent(_) => 0xF7u8
+        };
+        Event {
+            code_byte,
+            event: EventType::SysExEvent(event)
+        }
+    }
+
+    pub fn from_meta(event: MetaEvent) -> Event {
+        Event {
+            code_byte: 0xFFu8,
+            event: EventType::MetaEvent(event)
+        }
+    }
+
+    pub fn end_of_track() -> Event {
+        Event::from_meta(MetaEvent {
+            sub_code_byte: 0x2Fu8,
+            length: 0,
+            event: MetaEventType::EndOfTrack(EndOfTrack {})
+        })
+    }
+
+    pub fn is_end_of_track(&self) -> bool {
+        matches!(self.event, EventType::MetaEvent(MetaEvent { event: MetaEventType::EndOfTrack(_), .. }))
+    }
+
+    // Channel of a Midi Event, None for Sysex and Meta Events
+    pub fn channel(&self) -> Option<u8> {
+        match self.event {
+            EventType::MidiEvent(ref e) => Some(e.channel),
+            _ => None
+        }
+    }
+
     pub fn new<R: Read + Seek>(reader: &mut R, last_event: Option<Event>) -> Result<Event, Box<Error>> {
         let event;
         let mut code_byte: u8 = reader.read_to_u8()?;
         let mut running_status_byte: Option<u8> = None;
-        if (code_byte & 0b1000_0000u8 == 0u8) & (!last_event.is_some()) {  // Running Status
+        if (code_byte & 0b1000_0000u8 == 0u8) & last_event.is_none() {  // Running Status
             return Err(Box::new(NoPreviousEvent))
         } else if (code_byte & 0b1000_0000u8 == 0u8) & (last_event.is_some()) {
             running_status_byte = Some(code_byte);
@@ -305,4 +494,14 @@ impl Event {
             Err(Box::new(UnknownEventError))
         }
     }
+
+    // Always writes the full status byte, no Running Status
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_all(&[self.code_byte])?;
+        match self.event {
+            EventType::MidiEvent(ref e) => e.write(writer),
+            EventType::SysExEvent(ref e) => e.write(writer),
+            EventType::MetaEvent(ref e) => e.write(writer)
+        }
+    }
 }
\ No newline at end of file
@@ -1,8 +1,11 @@
 use std::io::Read;
+use std::io::Write;
 use std::error::Error;
 use super::super::super::super::super::VLVRead;
+use super::super::super::super::super::VLVWrite;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Sysex {
     pub length: u32,
     pub data: Vec<u8>
@@ -18,4 +21,10 @@ impl Sysex {
             data
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_vlv(self.data.len() as u32)?;
+        writer.write_all(&self.data)?;
+        Ok(())
+    }
 }
\ No newline at end of file
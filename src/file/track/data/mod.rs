@@ -3,12 +3,15 @@ pub mod event;
 use std::io::Read;
 use std::error::Error;
 use super::super::super::VLVRead;
+use super::super::super::VLVWrite;
 use self::event::Event;
 use std::io::Seek;
+use std::io::Write;
 
 // Represents the combination of a delta_time and an SMFEvent
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TrackEvent {
     pub delta_time: u32,
     pub event: Event
@@ -24,4 +27,9 @@ impl TrackEvent {
             event
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_vlv(self.delta_time)?;
+        self.event.write(writer)
+    }
 }
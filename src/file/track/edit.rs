@@ -0,0 +1,126 @@
+use super::SMFTrackChunk;
+use super::data::event::Event;
+use std::error::Error;
+use std::result::Result;
+use std::fmt;
+
+#[derive(Debug)]
+pub struct NoSuchEventError;
+
+impl Error for NoSuchEventError {
+    fn description(&self) -> &str {
+        "There is no Track Event at this index"
+    }
+}
+
+impl fmt::Display for NoSuchEventError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "No Track Event at this index")
+    }
+}
+
+// All these edits keep delta times and the length field consistent, and leave exactly one End of Track at the end
+// The End of Track is moved later if an event is placed after it, it never moves earlier on its own
+
+impl SMFTrackChunk {
+    // Inserts an Event at an absolute tick, after the events already on that tick, returns its index
+    pub fn insert_at_tick(&mut self, tick: u64, event: Event) -> Result<usize, Box<Error>> {
+        let (mut events, end_tick) = self.split_end_of_track();
+        let end_tick: u64 = if tick > end_tick { tick } else { end_tick };
+        if event.is_end_of_track() {
+            self.rebuild(events, end_tick)?;
+            return Ok(self.track_events.len() - 1)
+        }
+        let index: usize = events.iter().take_while(|(t, _)| *t <= tick).count();
+        events.insert(index, (tick, event));
+        self.rebuild(events, end_tick)?;
+        Ok(index)
+    }
+
+    // Removes the Track Event at this index, the following event keeps its absolute position
+    // Removing the End of Track puts a new one right after the last event
+    pub fn remove_at(&mut self, index: usize) -> Result<(u64, Event), Box<Error>> {
+        if index >= self.track_events.len() {
+            return Err(Box::new(NoSuchEventError))
+        }
+        let mut events: Vec<(u64, Event)> = self.absolute_events();
+        let removed: (u64, Event) = events.remove(index);
+        let end_tick: u64 = if removed.1.is_end_of_track() & (index == self.track_events.len() - 1) {
+            0
+        } else {
+            self.end_tick()
+        };
+        self.rebuild(events.into_iter().filter(|(_, e)| !e.is_end_of_track()).collect(), end_tick)?;
+        Ok(removed)
+    }
+
+    // Removes all events matching the predicate (End of Track excluded), returns them with their absolute ticks
+    pub fn remove_where<F: FnMut(u64, &Event) -> bool>(&mut self, mut predicate: F) -> Result<Vec<(u64, Event)>, Box<Error>> {
+        let (events, end_tick) = self.split_end_of_track();
+        let mut kept: Vec<(u64, Event)> = Vec::with_capacity(events.len());
+        let mut removed: Vec<(u64, Event)> = Vec::new();
+        for (tick, event) in events {
+            if predicate(tick, &event) {
+                removed.push((tick, event));
+            } else {
+                kept.push((tick, event));
+            }
+        }
+        self.rebuild(kept, end_tick)?;
+        Ok(removed)
+    }
+
+    // Keeps only the events matching the predicate (End of Track always kept)
+    pub fn retain<F: FnMut(u64, &Event) -> bool>(&mut self, mut predicate: F) -> Result<(), Box<Error>> {
+        self.remove_where(|tick, event| !predicate(tick, event))?;
+        Ok(())
+    }
+
+    // Moves the Track Event at this index to another absolute tick, after the events already there, returns its new index
+    pub fn move_event(&mut self, index: usize, new_tick: u64) -> Result<usize, Box<Error>> {
+        if index >= self.track_events.len() {
+            return Err(Box::new(NoSuchEventError))
+        }
+        if self.track_events[index].event.is_end_of_track() {
+            let (events, _) = self.split_end_of_track();
+            let last_tick: u64 = events.last().map(|(t, _)| *t).unwrap_or(0);
+            let end_tick: u64 = if new_tick > last_tick { new_tick } else { last_tick };
+            self.rebuild(events, end_tick)?;
+            return Ok(self.track_events.len() - 1)
+        }
+        let end_tick: u64 = self.end_tick();
+        let mut events: Vec<(u64, Event)> = self.absolute_events();
+        let (_, event) = events.remove(index);
+        let mut events: Vec<(u64, Event)> = events.into_iter().filter(|(_, e)| !e.is_end_of_track()).collect();
+        let new_index: usize = events.iter().take_while(|(t, _)| *t <= new_tick).count();
+        events.insert(new_index, (new_tick, event));
+        self.rebuild(events, if new_tick > end_tick { new_tick } else { end_tick })?;
+        Ok(new_index)
+    }
+
+    // Makes sure there is exactly one End of Track, at the end, and that the length is right
+    pub fn ensure_end_of_track(&mut self) -> Result<(), Box<Error>> {
+        let (events, end_tick) = self.split_end_of_track();
+        self.rebuild(events, end_tick)
+    }
+
+    // Absolute tick of the end of this track
+    pub fn end_tick(&self) -> u64 {
+        self.absolute_ticks().last().cloned().unwrap_or(0)
+    }
+
+    // Events without any End of Track, and the tick where the track ends
+    fn split_end_of_track(&self) -> (Vec<(u64, Event)>, u64) {
+        let end_tick: u64 = self.end_tick();
+        let events: Vec<(u64, Event)> = self.absolute_events().into_iter()
+            .filter(|(_, e)| !e.is_end_of_track())
+            .collect();
+        (events, end_tick)
+    }
+
+    fn rebuild(&mut self, mut events: Vec<(u64, Event)>, end_tick: u64) -> Result<(), Box<Error>> {
+        events.push((end_tick, Event::end_of_track()));
+        *self = SMFTrackChunk::from_absolute_events(events)?;
+        Ok(())
+    }
+}
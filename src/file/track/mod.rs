@@ -1,5 +1,8 @@
 pub mod data;
+pub mod edit;
+pub mod notes;
 
+use VLVTooBigError;
 use ez_io::ReadE;
 use self::data::TrackEvent;
 use self::data::event::Event;
@@ -7,12 +10,14 @@ use std::error::Error;
 use std::io::Read;
 use std::io::Seek;
 use std::io::SeekFrom;
+use std::io::Write;
 use std::result::Result;
 
 
 // Standard Midi File Track Chunk
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SMFTrackChunk {
     pub length: u32,
     pub track_events: Vec<TrackEvent>
@@ -46,4 +51,88 @@ impl SMFTrackChunk {
             track_events
         })
     }
+
+    // The length written is computed from the events, not taken from the length field
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        let data: Vec<u8> = self.events_to_bytes()?;
+        writer.write_all(b"MTrk")?;
+        let length: u32 = data.len() as u32;
+        writer.write_all(&[(length >> 24) as u8, (length >> 16) as u8, (length >> 8) as u8, length as u8])?;
+        writer.write_all(&data)?;
+        Ok(())
+    }
+
+    // Recomputes the length field after the events have been modified
+    pub fn update_length(&mut self) -> Result<(), Box<Error>> {
+        self.length = self.events_to_bytes()?.len() as u32;
+        Ok(())
+    }
+
+    // Absolute position in ticks of every Track Event, from the start of the track
+    pub fn absolute_ticks(&self) -> Vec<u64> {
+        let mut ticks: Vec<u64> = Vec::with_capacity(self.track_events.len());
+        let mut current: u64 = 0;
+        for track_event in &self.track_events {
+            current += u64::from(track_event.delta_time);
+            ticks.push(current);
+        }
+        ticks
+    }
+
+    // Every Event paired with its absolute position in ticks
+    pub fn absolute_events(&self) -> Vec<(u64, Event)> {
+        self.absolute_ticks().into_iter()
+            .zip(self.track_events.iter().map(|t| t.event.clone()))
+            .collect()
+    }
+
+    // Builds a track from Events at absolute positions, they are sorted (keeping the order of events on the same tick)
+    // All End of Track events are replaced by a single one, at the last tick where one was or after the last event
+    // Fails if two consecutive events are too far apart for a delta time
+    pub fn from_absolute_events(mut events: Vec<(u64, Event)>) -> Result<SMFTrackChunk, Box<Error>> {
+        events.sort_by_key(|&(tick, _)| tick);
+        let mut end_tick: u64 = 0;
+        let mut track_events: Vec<TrackEvent> = Vec::with_capacity(events.len() + 1);
+        let mut previous_tick: u64 = 0;
+        for (tick, event) in events {
+            if tick > end_tick {
+                end_tick = tick;
+            }
+            if event.is_end_of_track() {
+                continue;
+            }
+            track_events.push(TrackEvent {
+                delta_time: delta_time(previous_tick, tick)?,
+                event
+            });
+            previous_tick = tick;
+        }
+        track_events.push(TrackEvent {
+            delta_time: delta_time(previous_tick, end_tick)?,
+            event: Event::end_of_track()
+        });
+        let mut track = SMFTrackChunk {
+            length: 0,
+            track_events
+        };
+        track.update_length()?;
+        Ok(track)
+    }
+
+    fn events_to_bytes(&self) -> Result<Vec<u8>, Box<Error>> {
+        let mut data: Vec<u8> = Vec::new();
+        for track_event in &self.track_events {
+            track_event.write(&mut data)?;
+        }
+        Ok(data)
+    }
+}
+
+// Delta times are written as VLVs, which hold at most 28 bits
+fn delta_time(previous_tick: u64, tick: u64) -> Result<u32, Box<Error>> {
+    let delta: u64 = tick - previous_tick;
+    if delta > 0x0FFF_FFFFu64 {
+        return Err(Box::new(VLVTooBigError{}))
+    }
+    Ok(delta as u32)
 }
\ No newline at end of file
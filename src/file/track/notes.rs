@@ -0,0 +1,126 @@
+use super::SMFTrackChunk;
+use super::data::event::Event;
+use super::data::event::EventType;
+use super::data::event::MidiEventType;
+use std::error::Error;
+use std::result::Result;
+
+// A Note On paired with the Note Off (or Note On with a velocity of 0) that releases it
+
+#[derive(Clone)]
+pub struct Note {
+    pub channel: u8,
+    pub key: u8,
+    pub velocity: u8,
+    pub off_velocity: u8,
+    pub start: u64,
+    pub end: u64,
+    pub on_index: usize,
+    pub off_index: Option<usize>  // None when the note is never released, it then ends with the track
+}
+
+// Note On with a velocity of 0 is a Note Off
+pub fn is_note_off(event: &Event) -> bool {
+    match event.event {
+        EventType::MidiEvent(ref e) => match e.event {
+            MidiEventType::NoteOff(_) => true,
+            MidiEventType::NoteOn(ref n) => n.velocity == 0,
+            _ => false
+        },
+        _ => false
+    }
+}
+
+pub fn is_note_on(event: &Event) -> bool {
+    match event.event {
+        EventType::MidiEvent(ref e) => match e.event {
+            MidiEventType::NoteOn(ref n) => n.velocity > 0,
+            _ => false
+        },
+        _ => false
+    }
+}
+
+impl SMFTrackChunk {
+    // Pairs every Note On with its release, overlapping notes on the same key are released first in, first out
+    pub fn notes(&self) -> Vec<Note> {
+        let ticks: Vec<u64> = self.absolute_ticks();
+        let end_tick: u64 = ticks.last().cloned().unwrap_or(0);
+        let mut notes: Vec<Note> = Vec::new();
+        // Index in notes of the sounding notes, for each channel and key
+        let mut sounding: Vec<Vec<usize>> = vec![Vec::new(); 16 * 128];
+        for (index, track_event) in self.track_events.iter().enumerate() {
+            if let EventType::MidiEvent(ref midi_event) = track_event.event.event {
+                let (key, velocity) = match midi_event.event {
+                    MidiEventType::NoteOn(ref n) => (n.key, n.velocity),
+                    MidiEventType::NoteOff(ref n) => (n.key, 0),
+                    _ => continue
+                };
+                let slot: usize = (midi_event.channel as usize & 0x0F) * 128 + (key as usize & 0x7F);
+                if is_note_on(&track_event.event) {
+                    sounding[slot].push(notes.len());
+                    notes.push(Note {
+                        channel: midi_event.channel,
+                        key,
+                        velocity,
+                        off_velocity: 0,
+                        start: ticks[index],
+                        end: end_tick,
+                        on_index: index,
+                        off_index: None
+                    });
+                } else if !sounding[slot].is_empty() {
+                    let note_index: usize = sounding[slot].remove(0);
+                    let note: &mut Note = &mut notes[note_index];
+                    note.end = ticks[index];
+                    note.off_index = Some(index);
+                    if let MidiEventType::NoteOff(ref n) = midi_event.event {
+                        note.off_velocity = n.velocity;
+                    }
+                }
+            }
+        }
+        notes
+    }
+
+    // Writes back start, end and velocities of notes taken from notes(), other events keep their position
+    // On a same tick, releases are placed before everything else so that a note never cuts the one following it
+    pub fn update_notes(&mut self, notes: &[Note]) -> Result<(), Box<Error>> {
+        let mut events: Vec<(u64, Event)> = self.absolute_events();
+        for note in notes {
+            if note.on_index >= events.len() {
+                continue;
+            }
+            events[note.on_index].0 = note.start;
+            set_velocity(&mut events[note.on_index].1, note.velocity);
+            if let Some(off_index) = note.off_index {
+                if off_index < events.len() {
+                    let end: u64 = if note.end < note.start { note.start } else { note.end };
+                    events[off_index].0 = end;
+                }
+            }
+        }
+        // Releases go first on their tick, unless the note starts on that same tick
+        let mut ranks: Vec<u8> = vec![1; events.len()];
+        for note in self.notes() {
+            if let Some(off_index) = note.off_index {
+                ranks[off_index] = if events[off_index].0 > events[note.on_index].0 { 0 } else { 2 };
+            }
+        }
+        let mut ranked: Vec<(u8, (u64, Event))> = ranks.into_iter().zip(events).collect();
+        ranked.sort_by_key(|&(rank, (tick, _))| (tick, rank));
+        *self = SMFTrackChunk::from_absolute_events(ranked.into_iter().map(|(_, e)| e).collect())?;
+        Ok(())
+    }
+}
+
+fn set_velocity(event: &mut Event, velocity: u8) {
+    if let EventType::MidiEvent(ref mut midi_event) = event.event {
+        if let MidiEventType::NoteOn(ref mut n) = midi_event.event {
+            // Never turn a Note On into a Note Off
+            if (n.velocity > 0) & (velocity > 0) {
+                n.velocity = velocity & 0x7Fu8;
+            }
+        }
+    }
+}
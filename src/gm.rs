@@ -0,0 +1,509 @@
+use file::track::data::event::midi::ControllerChange;
+use file::track::data::event::midi::NoteChange;
+use file::track::data::event::midi::ProgramChange;
+
+// General MIDI Level 1 instruments, indexed by program number (0 based)
+pub const GM_PROGRAM_NAMES: [&str; 128] = [
+    // Piano
+    "Acoustic Grand Piano", "Bright Acoustic Piano", "Electric Grand Piano", "Honky-tonk Piano",
+    "Electric Piano 1", "Electric Piano 2", "Harpsichord", "Clavi",
+    // Chromatic Percussion
+    "Celesta", "Glockenspiel", "Music Box", "Vibraphone",
+    "Marimba", "Xylophone", "Tubular Bells", "Dulcimer",
+    // Organ
+    "Drawbar Organ", "Percussive Organ", "Rock Organ", "Church Organ",
+    "Reed Organ", "Accordion", "Harmonica", "Tango Accordion",
+    // Guitar
+    "Acoustic Guitar (nylon)", "Acoustic Guitar (steel)", "Electric Guitar (jazz)", "Electric Guitar (clean)",
+    "Electric Guitar (muted)", "Overdriven Guitar", "Distortion Guitar", "Guitar Harmonics",
+    // Bass
+    "Acoustic Bass", "Electric Bass (finger)", "Electric Bass (pick)", "Fretless Bass",
+    "Slap Bass 1", "Slap Bass 2", "Synth Bass 1", "Synth Bass 2",
+    // Strings
+    "Violin", "Viola", "Cello", "Contrabass",
+    "Tremolo Strings", "Pizzicato Strings", "Orchestral Harp", "Timpani",
+    // Ensemble
+    "String Ensemble 1", "String Ensemble 2", "Synth Strings 1", "Synth Strings 2",
+    "Choir Aahs", "Voice Oohs", "Synth Voice", "Orchestra Hit",
+    // Brass
+    "Trumpet", "Trombone", "Tuba", "Muted Trumpet",
+    "French Horn", "Brass Section", "Synth Brass 1", "Synth Brass 2",
+    // Reed
+    "Soprano Sax", "Alto Sax", "Tenor Sax", "Baritone Sax",
+    "Oboe", "English Horn", "Bassoon", "Clarinet",
+    // Pipe
+    "Piccolo", "Flute", "Recorder", "Pan Flute",
+    "Blown Bottle", "Shakuhachi", "Whistle", "Ocarina",
+    // Synth Lead
+    "Lead 1 (square)", "Lead 2 (sawtooth)", "Lead 3 (calliope)", "Lead 4 (chiff)",
+    "Lead 5 (charang)", "Lead 6 (voice)", "Lead 7 (fifths)", "Lead 8 (bass + lead)",
+    // Synth Pad
+    "Pad 1 (new age)", "Pad 2 (warm)", "Pad 3 (polysynth)", "Pad 4 (choir)",
+    "Pad 5 (bowed)", "Pad 6 (metallic)", "Pad 7 (halo)", "Pad 8 (sweep)",
+    // Synth Effects
+    "FX 1 (rain)", "FX 2 (soundtrack)", "FX 3 (crystal)", "FX 4 (atmosphere)",
+    "FX 5 (brightness)", "FX 6 (goblins)", "FX 7 (echoes)", "FX 8 (sci-fi)",
+    // Ethnic
+    "Sitar", "Banjo", "Shamisen", "Koto",
+    "Kalimba", "Bag pipe", "Fiddle", "Shanai",
+    // Percussive
+    "Tinkle Bell", "Agogo", "Steel Drums", "Woodblock",
+    "Taiko Drum", "Melodic Tom", "Synth Drum", "Reverse Cymbal",
+    // Sound Effects
+    "Guitar Fret Noise", "Breath Noise", "Seashore", "Bird Tweet",
+    "Telephone Ring", "Helicopter", "Applause", "Gunshot"
+];
+
+// Families of 8 programs
+pub const GM_FAMILY_NAMES: [&str; 16] = [
+    "Piano", "Chromatic Percussion", "Organ", "Guitar",
+    "Bass", "Strings", "Ensemble", "Brass",
+    "Reed", "Pipe", "Synth Lead", "Synth Pad",
+    "Synth Effects", "Ethnic", "Percussive", "Sound Effects"
+];
+
+// General MIDI percussion on channel 10, keys 35 to 81 are GM1, GM2 adds 27 to 34 and 82 to 87
+pub const GM_PERCUSSION_FIRST_KEY: u8 = 27;
+pub const GM_PERCUSSION_NAMES: [&str; 61] = [
+    "High Q", "Slap", "Scratch Push", "Scratch Pull",
+    "Sticks", "Square Click", "Metronome Click", "Metronome Bell",
+    "Acoustic Bass Drum", "Bass Drum 1", "Side Stick", "Acoustic Snare",
+    "Hand Clap", "Electric Snare", "Low Floor Tom", "Closed Hi Hat",
+    "High Floor Tom", "Pedal Hi-Hat", "Low Tom", "Open Hi-Hat",
+    "Low-Mid Tom", "Hi-Mid Tom", "Crash Cymbal 1", "High Tom",
+    "Ride Cymbal 1", "Chinese Cymbal", "Ride Bell", "Tambourine",
+    "Splash Cymbal", "Cowbell", "Crash Cymbal 2", "Vibraslap",
+    "Ride Cymbal 2", "Hi Bongo", "Low Bongo", "Mute Hi Conga",
+    "Open Hi Conga", "Low Conga", "High Timbale", "Low Timbale",
+    "High Agogo", "Low Agogo", "Cabasa", "Maracas",
+    "Short Whistle", "Long Whistle", "Short Guiro", "Long Guiro",
+    "Claves", "Hi Wood Block", "Low Wood Block", "Mute Cuica",
+    "Open Cuica", "Mute Triangle", "Open Triangle", "Shaker",
+    "Jingle Bell", "Belltree", "Castanets", "Mute Surdo",
+    "Open Surdo"
+];
+
+// Channel used for percussion by General MIDI, 0 based
+pub const GM_PERCUSSION_CHANNEL: u8 = 9;
+
+// GM2 melodic sounds use bank MSB 121 and select variations with the bank LSB
+pub const GM2_MELODIC_BANK_MSB: u8 = 121;
+pub const GM2_PERCUSSION_BANK_MSB: u8 = 120;
+
+// (program, bank LSB, name)
+const GM2_VARIATIONS: [(u8, u8, &str); 123] = [
+    (0, 1, "Acoustic Grand Piano (wide)"), (0, 2, "Acoustic Grand Piano (dark)"),
+    (1, 1, "Bright Acoustic Piano (wide)"),
+    (2, 1, "Electric Grand Piano (wide)"),
+    (3, 1, "Honky-tonk Piano (wide)"),
+    (4, 1, "Detuned Electric Piano 1"), (4, 2, "Electric Piano 1 (velocity mix)"), (4, 3, "60's Electric Piano"),
+    (5, 1, "Detuned Electric Piano 2"), (5, 2, "Electric Piano 2 (velocity mix)"), (5, 3, "EP Legend"), (5, 4, "EP Phase"),
+    (6, 1, "Harpsichord (octave mix)"), (6, 2, "Harpsichord (wide)"), (6, 3, "Harpsichord (with key off)"),
+    (7, 1, "Pulse Clavi"),
+    (14, 1, "Church Bell"), (14, 2, "Carillon"),
+    (16, 1, "Detuned Drawbar Organ"), (16, 2, "Italian 60's Organ"), (16, 3, "Drawbar Organ 2"),
+    (17, 1, "Detuned Percussive Organ"), (17, 2, "Percussive Organ 2"),
+    (19, 1, "Church Organ (octave mix)"), (19, 2, "Detuned Church Organ"),
+    (20, 1, "Puff Organ"),
+    (21, 1, "Accordion 2"),
+    (24, 1, "Ukulele"), (24, 2, "Acoustic Guitar (nylon + key off)"), (24, 3, "Acoustic Guitar (nylon 2)"),
+    (25, 1, "12-Strings Guitar"), (25, 2, "Mandolin"), (25, 3, "Steel Guitar with Body Sound"),
+    (26, 1, "Electric Guitar (pedal steel)"),
+    (27, 1, "Electric Guitar (detuned clean)"), (27, 2, "Mid Tone Guitar"),
+    (28, 1, "Electric Guitar (funky cutting)"), (28, 2, "Electric Guitar (muted velo-sw)"), (28, 3, "Jazz Man"),
+    (29, 1, "Guitar Pinch"),
+    (30, 1, "Electric Guitar (feedback)"), (30, 2, "Distortion Rhythm Guitar"),
+    (31, 1, "Guitar Feedback"),
+    (33, 1, "Finger Slap Bass"),
+    (38, 1, "Synth Bass (warm)"), (38, 2, "Synth Bass 3 (resonance)"), (38, 3, "Clavi Bass"), (38, 4, "Hammer"),
+    (39, 1, "Synth Bass 4 (attack)"), (39, 2, "Synth Bass (rubber)"), (39, 3, "Attack Pulse"),
+    (40, 1, "Violin (slow attack)"),
+    (48, 1, "Strings and Brass"), (48, 2, "60s Strings"),
+    (50, 1, "Synth Strings 3"),
+    (52, 1, "Choir Aahs 2"),
+    (53, 1, "Humming"),
+    (54, 1, "Analog Voice"),
+    (55, 1, "Bass Hit Plus"), (55, 2, "6th Hit"), (55, 3, "Euro Hit"),
+    (56, 1, "Dark Trumpet Soft"),
+    (57, 1, "Trombone 2"), (57, 2, "Bright Trombone"),
+    (59, 1, "Muted Trumpet 2"),
+    (60, 1, "French Horn 2"),
+    (61, 1, "Brass 2"),
+    (62, 1, "Synth Brass 3"), (62, 2, "Analog Brass 1"), (62, 3, "Jump Brass"),
+    (63, 1, "Synth Brass 4"), (63, 2, "Analog Brass 2"),
+    (80, 1, "Square Wave"), (80, 2, "Sine Wave"),
+    (81, 1, "Saw Wave"), (81, 2, "Doctor Solo"), (81, 3, "Natural Lead"), (81, 4, "Sequenced Saw"),
+    (89, 1, "Sine Pad"),
+    (91, 1, "Itopia"),
+    (98, 1, "Synth Mallet"),
+    (102, 1, "Echo Bell"), (102, 2, "Echo Pan"),
+    (104, 1, "Sitar 2"),
+    (107, 1, "Taisho Koto"),
+    (115, 1, "Castanets"),
+    (116, 1, "Concert Bass Drum"),
+    (117, 1, "Melodic Tom 2"),
+    (118, 1, "Rhythm Box Tom"), (118, 2, "Electric Drum"),
+    (120, 1, "Guitar Cutting Noise"), (120, 2, "Acoustic Bass String Slap"),
+    (121, 1, "Flute Key Click"),
+    (122, 1, "Rain"), (122, 2, "Thunder"), (122, 3, "Wind"), (122, 4, "Stream"), (122, 5, "Bubble"),
+    (123, 1, "Dog"), (123, 2, "Horse Gallop"), (123, 3, "Bird Tweet 2"),
+    (124, 1, "Telephone Ring 2"), (124, 2, "Door Creaking"), (124, 3, "Door"), (124, 4, "Scratch"), (124, 5, "Wind Chime"),
+    (125, 1, "Car Engine"), (125, 2, "Car Stop"), (125, 3, "Car Pass"), (125, 4, "Car Crash"), (125, 5, "Siren"),
+    (125, 6, "Train"), (125, 7, "Jetplane"), (125, 8, "Starship"), (125, 9, "Burst Noise"),
+    (126, 1, "Laughing"), (126, 2, "Screaming"), (126, 3, "Punch"), (126, 4, "Heart Beat"), (126, 5, "Footsteps"),
+    (127, 1, "Machine Gun"), (127, 2, "Lasergun"), (127, 3, "Explosion")
+];
+
+// Roland GS variation tones, (program, bank MSB, name)
+const GS_VARIATIONS: [(u8, u8, &str); 68] = [
+    (0, 8, "Piano 1w"), (0, 16, "Piano 1d"),
+    (1, 8, "Piano 2w"),
+    (2, 8, "Piano 3w"),
+    (3, 8, "Honky-tonk w"),
+    (4, 8, "Detuned EP 1"),
+    (5, 8, "Detuned EP 2"),
+    (6, 8, "Coupled Hps."),
+    (14, 8, "Church Bell"),
+    (16, 8, "Detuned Or.1"),
+    (17, 8, "Detuned Or.2"),
+    (19, 8, "Church Org.2"),
+    (21, 8, "Accordion It"),
+    (24, 8, "Ukulele"),
+    (25, 8, "12-str.Gt"), (25, 16, "Mandolin"),
+    (26, 8, "Hawaiian Gt."),
+    (27, 8, "Chorus Gt."),
+    (28, 8, "Funk Gt."),
+    (30, 8, "Feedback Gt."),
+    (31, 8, "Gt. Feedback"),
+    (38, 8, "Synth Bass 3"),
+    (39, 8, "Synth Bass 4"),
+    (48, 8, "Orchestra"),
+    (50, 8, "Syn.Strings3"),
+    (61, 8, "Brass 2"),
+    (62, 8, "Synth Brass3"),
+    (63, 8, "Synth Brass4"),
+    (80, 8, "Sine Wave"),
+    (81, 8, "Doctor Solo"),
+    (107, 8, "Taisho Koto"),
+    (115, 8, "Castanets"),
+    (116, 8, "Concert BD"),
+    (117, 8, "Melo. Tom 2"),
+    (118, 8, "808 Tom"),
+    (120, 1, "Gt.Cut Noise"), (120, 2, "String Slap"),
+    (121, 1, "Fl.Key Click"),
+    (122, 1, "Rain"), (122, 2, "Thunder"), (122, 3, "Wind"), (122, 4, "Stream"), (122, 5, "Bubble"),
+    (123, 1, "Dog"), (123, 2, "Horse-Gallop"), (123, 3, "Bird 2"),
+    (124, 1, "Telephone 2"), (124, 2, "DoorCreaking"), (124, 3, "Door"), (124, 4, "Scratch"), (124, 5, "Wind Chimes"),
+    (125, 1, "Car-Engine"), (125, 2, "Car-Stop"), (125, 3, "Car-Pass"), (125, 4, "Car-Crash"), (125, 5, "Siren"),
+    (125, 6, "Train"), (125, 7, "Jetplane"), (125, 8, "Starship"), (125, 9, "Burst Noise"),
+    (126, 1, "Laughing"), (126, 2, "Screaming"), (126, 3, "Punch"), (126, 4, "Heart Beat"), (126, 5, "Footsteps"),
+    (127, 1, "Machine Gun"), (127, 2, "Lasergun"), (127, 3, "Explosion")
+];
+
+// Roland GS drum kits, selected by program change on the percussion channel
+const GS_DRUM_KITS: [(u8, &str); 10] = [
+    (0, "Standard"), (8, "Room"), (16, "Power"), (24, "Electronic"), (25, "TR-808"),
+    (32, "Jazz"), (40, "Brush"), (48, "Orchestra"), (56, "SFX"), (127, "CM-64/32L")
+];
+
+// Yamaha XG variation voices on bank MSB 0, (program, bank LSB, name)
+const XG_VARIATIONS: [(u8, u8, &str); 258] = [
+    (0, 1, "GrndPnoK"), (0, 18, "MelloGrP"), (0, 40, "PianoStr"), (0, 41, "Dream"),
+    (1, 1, "BritPnoK"),
+    (2, 1, "ElGrPnoK"), (2, 32, "Det.CP80"), (2, 40, "LayerCP1"), (2, 41, "LayerCP2"),
+    (3, 1, "HnkyTnkK"),
+    (4, 1, "El.Pno1K"), (4, 18, "MelloEP1"), (4, 32, "Chor.EP1"), (4, 40, "HardEl.P"), (4, 45, "VX El.P1"), (4, 64, "60sEl.P"),
+    (5, 1, "El.Pno2K"), (5, 32, "Chor.EP2"), (5, 33, "DX Hard"), (5, 34, "DXLegend"), (5, 40, "DX Phase"), (5, 41, "DX+Analg"),
+    (5, 42, "DXKotoEP"), (5, 45, "VX El.P2"),
+    (6, 1, "Harpsi.K"), (6, 25, "Harpsi.2"), (6, 35, "Harpsi.3"),
+    (7, 1, "Clavi.K"), (7, 27, "ClaviWah"), (7, 64, "PulseClv"), (7, 65, "PierceCl"),
+    (10, 64, "Orgel"),
+    (11, 1, "VibesK"), (11, 45, "HardVibe"),
+    (12, 1, "MarimbaK"), (12, 64, "SineMrmb"), (12, 97, "Balimba"), (12, 98, "Log Drum"),
+    (14, 96, "ChrchBel"), (14, 97, "Carillon"),
+    (15, 35, "Dulcimr2"), (15, 96, "Cimbalom"), (15, 97, "Santur"),
+    (16, 32, "DetDrwOr"), (16, 33, "60sDrOr1"), (16, 34, "60sDrOr2"), (16, 35, "70sDrOr1"), (16, 36, "DrawOrg2"),
+    (16, 37, "60sDrOr3"), (16, 38, "EvenBar"), (16, 40, "16+2\"2/3"), (16, 64, "Organ Ba"), (16, 65, "70sDrOr2"),
+    (16, 66, "CheezOrg"), (16, 67, "DrawOrg3"),
+    (24, 16, "NylonGt2"), (24, 25, "NylonGt3"), (24, 43, "VelGtHrm"), (24, 96, "Ukulele"),
+    (25, 16, "SteelGt2"), (25, 35, "12StrGtr"), (25, 40, "Nyln&Stl"), (25, 41, "Stl&Body"), (25, 96, "Mandolin"),
+    (26, 18, "MelloGtr"), (26, 32, "JazzAmp"),
+    (27, 32, "ChorusGt"),
+    (28, 40, "FunkGtr1"), (28, 41, "MuteStlG"), (28, 45, "FunkGtr2"), (28, 47, "Jazz Man"),
+    (29, 43, "Gt.Pinch"),
+    (30, 40, "FeedbkGt"), (30, 41, "FeedbGt2"),
+    (31, 65, "GtFeedbk"), (31, 66, "GtrHrmo2"),
+    (32, 40, "JazzRthm"), (32, 45, "VXUprght"),
+    (33, 18, "FingrDrk"), (33, 27, "FlangeBa"), (33, 40, "Ba&DstEG"), (33, 43, "FngrSlap"), (33, 45, "FngBass2"),
+    (33, 65, "ModAlem"),
+    (34, 28, "MutePkBa"),
+    (35, 32, "Fretles2"), (35, 33, "Fretles3"), (35, 34, "Fretles4"), (35, 96, "SynFretl"), (35, 97, "Smooth"),
+    (36, 27, "ResoSlap"), (36, 32, "PunchThm"),
+    (37, 43, "VeloSlap"),
+    (38, 18, "SynBa1Dk"), (38, 20, "FastResB"), (38, 24, "AcidBass"), (38, 35, "Clv Bass"), (38, 40, "TeknoBa"),
+    (38, 64, "Oscar"), (38, 65, "SqrBass"), (38, 66, "RubberBa"), (38, 96, "Hammer"),
+    (39, 6, "MelloSB1"), (39, 12, "Seq Bass"), (39, 18, "ClkSynBa"), (39, 19, "SynBa2Dk"), (39, 32, "SmthBa 2"),
+    (39, 40, "ModulrBa"), (39, 41, "DX Bass"), (39, 64, "X WireBa"),
+    (40, 8, "SlowVln"),
+    (44, 8, "SlowTrSt"), (44, 40, "Susp Str"),
+    (46, 40, "YangChin"),
+    (48, 3, "S.Strngs"), (48, 8, "SlowStr"), (48, 24, "ArcoStr"), (48, 35, "60sStrng"), (48, 40, "Orchestr"),
+    (48, 41, "Orchstr2"), (48, 42, "TremOrch"), (48, 45, "VeloStr"),
+    (49, 3, "S.SlwStr"), (49, 8, "LegatoSt"), (49, 40, "Warm Str"), (49, 41, "Kingdom"), (49, 64, "70s Str"),
+    (49, 65, "Str Ens3"),
+    (50, 27, "ResoStr"), (50, 64, "SynStr4"), (50, 65, "SS Str"),
+    (52, 3, "S.Choir"), (52, 16, "Ch.Aahs2"), (52, 32, "MelChoir"), (52, 40, "ChoirStr"),
+    (54, 40, "SynVox2"), (54, 41, "Choral"), (54, 64, "AnaVoice"),
+    (55, 35, "OrchHit2"), (55, 64, "Impact"),
+    (56, 16, "Trumpet2"), (56, 17, "BriteTrp"), (56, 32, "WarmTrp"),
+    (57, 18, "Trmbone2"),
+    (58, 16, "Tuba 2"),
+    (60, 6, "FrHrSolo"), (60, 32, "FrHorn2"), (60, 37, "HornOrch"),
+    (61, 35, "Tp&TbSec"), (61, 40, "BrssSec2"), (61, 41, "HiBrass"), (61, 42, "MelloBrs"),
+    (62, 12, "QuackBr"), (62, 20, "RezSynBr"), (62, 24, "PolyBrss"), (62, 27, "SynBras3"), (62, 32, "JumpBrss"),
+    (62, 45, "AnaVelBr"), (62, 64, "AnaBrss1"),
+    (63, 18, "Soft Brs"), (63, 40, "SynBras4"), (63, 41, "ChoirBrs"), (63, 45, "VelBras2"), (63, 64, "AnaBras2"),
+    (65, 8, "AltoSax2"), (65, 40, "Sax Sect"), (65, 43, "HyprAlto"),
+    (66, 40, "BrthTnSx"), (66, 41, "SoftTenr"), (66, 64, "TnrSax2"),
+    (80, 6, "Square 2"), (80, 8, "LMSquare"), (80, 18, "Hollow"), (80, 19, "Shmoog"), (80, 64, "Mellow"),
+    (80, 65, "SoloSine"), (80, 66, "SineLead"),
+    (81, 6, "Saw 2"), (81, 8, "ThickSaw"), (81, 18, "DynaSaw"), (81, 19, "DigiSaw"), (81, 20, "Big Lead"),
+    (81, 24, "HeavySyn"), (81, 25, "WaspySyn"), (81, 40, "PulseSaw"), (81, 41, "Dr. Lead"), (81, 45, "VeloLead"),
+    (81, 96, "Seq Ana"),
+    (82, 64, "PureLead"),
+    (83, 64, "Rubby"),
+    (84, 64, "DistLead"),
+    (87, 16, "Big&Low"), (87, 64, "Fat&Prky"), (87, 65, "SoftWurl"),
+    (88, 64, "Fantasy2"),
+    (89, 16, "ThickPad"), (89, 17, "Soft Pad"), (89, 18, "SinePad"), (89, 64, "Horn Pad"), (89, 65, "RotarStr"),
+    (90, 64, "PolyPd80"), (90, 65, "ClickPad"), (90, 66, "Ana Pad"), (90, 67, "SquarPad"),
+    (91, 64, "Heaven2"),
+    (93, 64, "Tine Pad"), (93, 65, "Pan Pad"),
+    (95, 20, "Shwimmer"), (95, 27, "Converge"), (95, 64, "PolarPad"), (95, 66, "Celstial"),
+    (96, 45, "ClaviPad"), (96, 64, "HrmoRain"), (96, 65, "AfrcnWnd"), (96, 66, "Carib"),
+    (97, 27, "Prologue"), (97, 64, "Ancestrl"),
+    (98, 12, "SynDrCmp"), (98, 14, "Popcorn"), (98, 18, "TinyBell"), (98, 35, "RndGlock"), (98, 40, "GlockChi"),
+    (98, 41, "ClearBel"), (98, 42, "ChorBell"), (98, 64, "SynMalet"), (98, 65, "SftCryst"), (98, 66, "LoudGlok"),
+    (98, 67, "XmasBell"), (98, 68, "VibeBell"), (98, 69, "DigiBell"), (98, 70, "AirBells"), (98, 71, "BellHarp"),
+    (98, 72, "Gamelmba"),
+    (99, 18, "WarmAtms"), (99, 19, "HollwRls"), (99, 40, "NylonEP"), (99, 64, "NylnHarp"), (99, 65, "Harp Vox"),
+    (99, 66, "AtmosPad"), (99, 67, "Planet"),
+    (100, 64, "FantaBel"), (100, 96, "Smokey"),
+    (101, 64, "GobSyn"), (101, 65, "50sSciFi"), (101, 66, "Ring Pad"), (101, 67, "Ritual"), (101, 68, "ToHeaven"),
+    (101, 70, "Night"), (101, 71, "Glisten"), (101, 96, "BelChoir"),
+    (102, 8, "EchoPad2"), (102, 14, "Echo Pan"), (102, 64, "EchoBell"), (102, 65, "Big Pan"), (102, 66, "SynPiano"),
+    (102, 67, "Creation"), (102, 68, "Stardust"), (102, 69, "Reso Pan")
+];
+
+pub fn program_name(program: u8) -> &'static str {
+    GM_PROGRAM_NAMES[(program & 0x7Fu8) as usize]
+}
+
+pub fn program_family(program: u8) -> &'static str {
+    GM_FAMILY_NAMES[((program & 0x7Fu8) / 8) as usize]
+}
+
+pub fn percussion_name(key: u8) -> Option<&'static str> {
+    if key < GM_PERCUSSION_FIRST_KEY {
+        return None
+    }
+    GM_PERCUSSION_NAMES.get((key - GM_PERCUSSION_FIRST_KEY) as usize).cloned()
+}
+
+// GM2 name for a program and bank, bank LSB 0 or unknown variations give the GM1 name
+pub fn gm2_program_name(program: u8, bank_lsb: u8) -> &'static str {
+    GM2_VARIATIONS.iter()
+        .find(|&&(p, lsb, _)| (p == program) & (lsb == bank_lsb))
+        .map(|&(_, _, name)| name)
+        .unwrap_or_else(|| program_name(program))
+}
+
+// GS name for a program and bank MSB, unknown variations fall back to the capital tone like GS synthesizers do
+pub fn gs_program_name(program: u8, bank_msb: u8) -> &'static str {
+    GS_VARIATIONS.iter()
+        .find(|&&(p, msb, _)| (p == program) & (msb == bank_msb))
+        .map(|&(_, _, name)| name)
+        .unwrap_or_else(|| program_name(program))
+}
+
+pub fn gs_drum_kit_name(program: u8) -> Option<&'static str> {
+    GS_DRUM_KITS.iter().find(|&&(p, _)| p == program).map(|&(_, name)| name)
+}
+
+// XG name for a program and bank LSB on bank MSB 0, unknown variations fall back to the GM voice like XG synthesizers do
+pub fn xg_program_name(program: u8, bank_lsb: u8) -> &'static str {
+    XG_VARIATIONS.iter()
+        .find(|&&(p, lsb, _)| (p == program) & (lsb == bank_lsb))
+        .map(|&(_, _, name)| name)
+        .unwrap_or_else(|| program_name(program))
+}
+
+// What a Yamaha XG bank select points to
+#[derive(Clone, Copy, PartialEq)]
+pub enum XGBankType {
+    Normal,  // MSB 0, the LSB selects a variation of the GM voice
+    SFXVoice,  // MSB 64
+    SFXKit,  // MSB 126
+    DrumKit,  // MSB 127
+    Unknown
+}
+
+pub fn xg_bank_type(bank_msb: u8) -> XGBankType {
+    match bank_msb {
+        0 => XGBankType::Normal,
+        64 => XGBankType::SFXVoice,
+        126 => XGBankType::SFXKit,
+        127 => XGBankType::DrumKit,
+        _ => XGBankType::Unknown
+    }
+}
+
+pub fn controller_name(controller_number: u8) -> Option<&'static str> {
+    let name: &str = match controller_number {
+        0 => "Bank Select",
+        1 => "Modulation Wheel",
+        2 => "Breath Controller",
+        4 => "Foot Controller",
+        5 => "Portamento Time",
+        6 => "Data Entry",
+        7 => "Channel Volume",
+        8 => "Balance",
+        10 => "Pan",
+        11 => "Expression Controller",
+        12 => "Effect Control 1",
+        13 => "Effect Control 2",
+        16 => "General Purpose Controller 1",
+        17 => "General Purpose Controller 2",
+        18 => "General Purpose Controller 3",
+        19 => "General Purpose Controller 4",
+        32 => "Bank Select (LSB)",
+        33 => "Modulation Wheel (LSB)",
+        34 => "Breath Controller (LSB)",
+        36 => "Foot Controller (LSB)",
+        37 => "Portamento Time (LSB)",
+        38 => "Data Entry (LSB)",
+        39 => "Channel Volume (LSB)",
+        40 => "Balance (LSB)",
+        42 => "Pan (LSB)",
+        43 => "Expression Controller (LSB)",
+        44 => "Effect Control 1 (LSB)",
+        45 => "Effect Control 2 (LSB)",
+        48 => "General Purpose Controller 1 (LSB)",
+        49 => "General Purpose Controller 2 (LSB)",
+        50 => "General Purpose Controller 3 (LSB)",
+        51 => "General Purpose Controller 4 (LSB)",
+        64 => "Sustain Pedal",
+        65 => "Portamento On/Off",
+        66 => "Sostenuto",
+        67 => "Soft Pedal",
+        68 => "Legato Footswitch",
+        69 => "Hold 2",
+        70 => "Sound Variation",
+        71 => "Timbre/Harmonic Intensity",
+        72 => "Release Time",
+        73 => "Attack Time",
+        74 => "Brightness",
+        75 => "Decay Time",
+        76 => "Vibrato Rate",
+        77 => "Vibrato Depth",
+        78 => "Vibrato Delay",
+        79 => "Sound Controller 10",
+        80 => "General Purpose Controller 5",
+        81 => "General Purpose Controller 6",
+        82 => "General Purpose Controller 7",
+        83 => "General Purpose Controller 8",
+        84 => "Portamento Control",
+        88 => "High Resolution Velocity Prefix",
+        91 => "Reverb Send Level",
+        92 => "Tremolo Depth",
+        93 => "Chorus Send Level",
+        94 => "Celeste Depth",
+        95 => "Phaser Depth",
+        96 => "Data Increment",
+        97 => "Data Decrement",
+        98 => "Non-Registered Parameter Number (LSB)",
+        99 => "Non-Registered Parameter Number (MSB)",
+        100 => "Registered Parameter Number (LSB)",
+        101 => "Registered Parameter Number (MSB)",
+        120 => "All Sound Off",
+        121 => "Reset All Controllers",
+        122 => "Local Control",
+        123 => "All Notes Off",
+        124 => "Omni Mode Off",
+        125 => "Omni Mode On",
+        126 => "Mono Mode On",
+        127 => "Poly Mode On",
+        _ => return None
+    };
+    Some(name)
+}
+
+// Reverse lookups ignore case and surrounding spaces
+
+fn same_name(a: &str, b: &str) -> bool {
+    a.trim().to_lowercase() == b.trim().to_lowercase()
+}
+
+pub fn program_by_name(name: &str) -> Option<u8> {
+    GM_PROGRAM_NAMES.iter().position(|n| same_name(n, name)).map(|p| p as u8)
+}
+
+// First program of a family
+pub fn family_by_name(name: &str) -> Option<u8> {
+    GM_FAMILY_NAMES.iter().position(|n| same_name(n, name)).map(|f| (f * 8) as u8)
+}
+
+pub fn percussion_key_by_name(name: &str) -> Option<u8> {
+    GM_PERCUSSION_NAMES.iter().position(|n| same_name(n, name)).map(|k| k as u8 + GM_PERCUSSION_FIRST_KEY)
+}
+
+pub fn controller_by_name(name: &str) -> Option<u8> {
+    (0..128u8).find(|&n| controller_name(n).map(|c| same_name(c, name)).unwrap_or(false))
+}
+
+// (program, bank LSB) of a GM2 sound, GM1 names give LSB 0
+pub fn gm2_program_by_name(name: &str) -> Option<(u8, u8)> {
+    GM2_VARIATIONS.iter()
+        .find(|&&(_, _, n)| same_name(n, name))
+        .map(|&(p, lsb, _)| (p, lsb))
+        .or_else(|| program_by_name(name).map(|p| (p, 0)))
+}
+
+// (program, bank MSB) of a GS tone, GM1 names give MSB 0
+pub fn gs_program_by_name(name: &str) -> Option<(u8, u8)> {
+    GS_VARIATIONS.iter()
+        .find(|&&(_, _, n)| same_name(n, name))
+        .map(|&(p, msb, _)| (p, msb))
+        .or_else(|| program_by_name(name).map(|p| (p, 0)))
+}
+
+// (program, bank LSB) of an XG voice on bank MSB 0, GM1 names give LSB 0
+pub fn xg_program_by_name(name: &str) -> Option<(u8, u8)> {
+    XG_VARIATIONS.iter()
+        .find(|&&(_, _, n)| same_name(n, name))
+        .map(|&(p, lsb, _)| (p, lsb))
+        .or_else(|| program_by_name(name).map(|p| (p, 0)))
+}
+
+impl ProgramChange {
+    pub fn gm_name(&self) -> &'static str {
+        program_name(self.new_program_number)
+    }
+
+    pub fn gm_family(&self) -> &'static str {
+        program_family(self.new_program_number)
+    }
+}
+
+impl ControllerChange {
+    pub fn name(&self) -> Option<&'static str> {
+        controller_name(self.controller_number)
+    }
+}
+
+impl NoteChange {
+    // Only meaningful on the percussion channel
+    pub fn percussion_name(&self) -> Option<&'static str> {
+        percussion_name(self.key)
+    }
+}
@@ -1,8 +1,15 @@
+// Positions are read with seek(SeekFrom::Current(0)) throughout
+#![allow(clippy::seek_from_current)]
+
 extern crate ez_io;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
 
 use ez_io::ReadE;
 use std::error::Error;
 use std::io::Read;
+use std::io::Write;
 use std::result::Result;
 use std::fmt;
 
@@ -11,7 +18,7 @@ pub struct VLVTooBigError;
 
 impl Error for VLVTooBigError {
     fn description(&self) -> &str {
-        "Trying to read a VLV bigger than 4 bytes"
+        "Trying to read or write a VLV bigger than 4 bytes"
     }
 }
 
@@ -33,7 +40,7 @@ pub trait VLVRead: Read {
         let mut counter: u8 = 0;
         loop {
             let current = self.read_to_u8()?;
-            out += u32::from(current) & 0b0111_1111u32;
+            out = (out << 7) | (u32::from(current) & 0b0111_1111u32);
             if current & 0b1000_0000u8 == 0 {
                 break;
             }
@@ -52,4 +59,32 @@ pub trait VLVRead: Read {
 // Implement the VLVRead trait to anything that has the Read trait
 impl<R: Read + ?Sized> VLVRead for R {}
 
-pub mod file;
\ No newline at end of file
+// Makes it easy to write VLVs
+pub trait VLVWrite: Write {
+    fn write_vlv(&mut self, data: u32) -> Result<(), Box<Error>> {
+        if data > 0x0FFF_FFFFu32 {
+            return Err(Box::new(VLVTooBigError{}))
+        }
+        let mut bytes: Vec<u8> = vec![(data & 0b0111_1111u32) as u8];
+        let mut rest: u32 = data >> 7;
+        while rest > 0 {
+            bytes.insert(0, ((rest & 0b0111_1111u32) as u8) | 0b1000_0000u8);
+            rest >>= 7;
+        }
+        self.write_all(&bytes)?;
+        Ok(())
+    }
+}
+
+// Implement the VLVWrite trait to anything that has the Write trait
+impl<W: Write + ?Sized> VLVWrite for W {}
+
+pub mod file;
+pub mod transform;
+pub mod state;
+pub mod controller;
+pub mod gm;
+pub mod pitch;
+pub mod live;
+pub mod sync;
+pub mod convert;
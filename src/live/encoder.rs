@@ -0,0 +1,52 @@
+use super::LiveMessage;
+use std::error::Error;
+use std::io::Write;
+use std::result::Result;
+
+// Writes messages one after the other, leaving out the status byte of Channel messages repeating the previous one
+// Real Time messages do not change the running status, Sysex and System Common messages cancel it
+
+#[derive(Clone)]
+pub struct LiveEncoder {
+    pub running_status: bool,
+    last_status: Option<u8>
+}
+
+impl LiveEncoder {
+    pub fn new(running_status: bool) -> LiveEncoder {
+        LiveEncoder {
+            running_status,
+            last_status: None
+        }
+    }
+
+    // Next message is written with its status byte, to call when the receiver may have lost track
+    pub fn reset(&mut self) {
+        self.last_status = None;
+    }
+
+    pub fn write<W: Write>(&mut self, message: &LiveMessage, writer: &mut W) -> Result<(), Box<Error>> {
+        let status: u8 = message.status_byte();
+        if status >= 0xF8u8 {
+            return message.write(writer)
+        }
+        if status >= 0xF0u8 {
+            self.last_status = None;
+            return message.write(writer)
+        }
+        if !self.running_status | (self.last_status != Some(status)) {
+            writer.write_all(&[status])?;
+        }
+        self.last_status = Some(status);
+        writer.write_all(&message.data_bytes()?)?;
+        Ok(())
+    }
+
+    pub fn encode(&mut self, messages: &[LiveMessage]) -> Result<Vec<u8>, Box<Error>> {
+        let mut bytes: Vec<u8> = Vec::new();
+        for message in messages {
+            self.write(message, &mut bytes)?;
+        }
+        Ok(bytes)
+    }
+}
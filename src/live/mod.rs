@@ -0,0 +1,158 @@
+use file::track::data::event::Event;
+use file::track::data::event::EventType;
+use file::track::data::event::MidiEvent;
+use file::track::data::event::SysexEvent;
+use file::track::data::event::SysexEventType;
+use file::track::data::event::sysex::Sysex;
+use std::error::Error;
+use std::io::Write;
+use std::result::Result;
+use std::fmt;
+
+pub mod parser;
+pub mod encoder;
+pub mod recorder;
+pub mod player;
+
+#[derive(Debug)]
+pub struct MetaEventNotLiveError;
+
+impl Error for MetaEventNotLiveError {
+    fn description(&self) -> &str {
+        "Meta Events only exist in files and cannot be sent on a MIDI cable"
+    }
+}
+
+impl fmt::Display for MetaEventNotLiveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Meta Event has no live counterpart")
+    }
+}
+
+#[derive(Debug)]
+pub struct NotASingleMessageError;
+
+impl Error for NotASingleMessageError {
+    fn description(&self) -> &str {
+        "The Sysex Event does not hold exactly one complete message, like a Sysex split in packets"
+    }
+}
+
+impl fmt::Display for NotASingleMessageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sysex Event is not a single message")
+    }
+}
+
+// A complete message as sent on a MIDI 1.0 cable
+
+#[derive(Clone)]
+pub enum LiveMessage {
+    // Channel Voice and Channel Mode messages
+    Channel(MidiEvent),
+    // Data between F0 and F7, both excluded
+    SysEx(Vec<u8>),
+    // System Common
+    MTCQuarterFrame(u8),  // Message type in the upper nibble, value in the lower one
+    SongPositionPointer(u16),  // In MIDI beats (sixteenth notes) since the start of the song
+    SongSelect(u8),
+    TuneRequest,
+    // System Real Time
+    TimingClock,
+    Start,
+    Continue,
+    Stop,
+    ActiveSensing,
+    SystemReset,
+    // F4, F5, F9 and FD, status byte only
+    Undefined(u8)
+}
+
+impl LiveMessage {
+    pub fn status_byte(&self) -> u8 {
+        match *self {
+            LiveMessage::Channel(ref e) => e.event.code_byte() | (e.channel & 0x0Fu8),
+            LiveMessage::SysEx(_) => 0xF0u8,
+            LiveMessage::MTCQuarterFrame(_) => 0xF1u8,
+            LiveMessage::SongPositionPointer(_) => 0xF2u8,
+            LiveMessage::SongSelect(_) => 0xF3u8,
+            LiveMessage::TuneRequest => 0xF6u8,
+            LiveMessage::TimingClock => 0xF8u8,
+            LiveMessage::Start => 0xFAu8,
+            LiveMessage::Continue => 0xFBu8,
+            LiveMessage::Stop => 0xFCu8,
+            LiveMessage::ActiveSensing => 0xFEu8,
+            LiveMessage::SystemReset => 0xFFu8,
+            LiveMessage::Undefined(status) => status
+        }
+    }
+
+    pub fn is_real_time(&self) -> bool {
+        self.status_byte() >= 0xF8u8
+    }
+
+    // Bytes following the status byte, F7 included for Sysex
+    pub fn data_bytes(&self) -> Result<Vec<u8>, Box<Error>> {
+        let mut data: Vec<u8> = Vec::new();
+        match *self {
+            LiveMessage::Channel(ref e) => e.write(&mut data)?,
+            LiveMessage::SysEx(ref d) => {
+                data.extend(d.iter().map(|b| b & 0x7Fu8));
+                data.push(0xF7u8);
+            },
+            LiveMessage::MTCQuarterFrame(v) | LiveMessage::SongSelect(v) => data.push(v & 0x7Fu8),
+            LiveMessage::SongPositionPointer(v) => data.extend_from_slice(&[(v & 0x7Fu16) as u8, ((v >> 7) & 0x7Fu16) as u8]),
+            _ => {}
+        }
+        Ok(data)
+    }
+
+    // Complete message with its status byte, running status is handled by the LiveEncoder
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<Error>> {
+        writer.write_all(&[self.status_byte()])?;
+        writer.write_all(&self.data_bytes()?)?;
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Box<Error>> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.write(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    // Sysex go in F0 Events, other System messages in F7 (escape) Events holding their raw bytes
+    pub fn to_event(&self) -> Result<Event, Box<Error>> {
+        let event: SysexEventType = match *self {
+            LiveMessage::Channel(ref e) => return Ok(Event::from_midi(e.clone())),
+            LiveMessage::SysEx(_) => {
+                let data: Vec<u8> = self.data_bytes()?;
+                SysexEventType::F0SysexEvent(Sysex { length: data.len() as u32, data })
+            },
+            _ => {
+                let data: Vec<u8> = self.to_bytes()?;
+                SysexEventType::F7SysexEvent(Sysex { length: data.len() as u32, data })
+            }
+        };
+        Ok(Event::from_sysex(SysexEvent { event }))
+    }
+
+    // F0 Events must hold a whole Sysex ending with F7, F7 Events must hold exactly one message
+    pub fn from_event(event: &Event) -> Result<LiveMessage, Box<Error>> {
+        let bytes: Vec<u8> = match event.event {
+            EventType::MidiEvent(ref e) => return Ok(LiveMessage::Channel(e.clone())),
+            EventType::MetaEvent(_) => return Err(Box::new(MetaEventNotLiveError)),
+            EventType::SysExEvent(SysexEvent { event: SysexEventType::F0SysexEvent(ref s) }) => {
+                let mut bytes: Vec<u8> = vec![0xF0u8];
+                bytes.extend_from_slice(&s.data);
+                bytes
+            },
+            EventType::SysExEvent(SysexEvent { event: SysexEventType::F7SysexEvent(ref s) }) => s.data.clone()
+        };
+        let mut parser = parser::LiveParser::new();
+        let mut messages: Vec<LiveMessage> = parser.feed_bytes(&bytes);
+        if (messages.len() != 1) | (parser.dropped_bytes != 0) | parser.is_receiving() {
+            return Err(Box::new(NotASingleMessageError))
+        }
+        Ok(messages.remove(0))
+    }
+}
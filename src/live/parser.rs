@@ -0,0 +1,145 @@
+use super::LiveMessage;
+use file::track::data::event::MidiEvent;
+
+// Number of data bytes following a status byte, None for bytes that are not a status or start a Sysex
+pub fn data_length(status: u8) -> Option<usize> {
+    match status {
+        0x80..=0xBF | 0xE0..=0xEF => Some(2),
+        0xC0..=0xDF => Some(1),
+        0xF1 | 0xF3 => Some(1),
+        0xF2 => Some(2),
+        0xF4..=0xF6 | 0xF8..=0xFF => Some(0),
+        _ => None
+    }
+}
+
+// Turns bytes received one at a time into messages
+// Real Time messages are returned as soon as they arrive, even in the middle of another message or of a Sysex
+// A Sysex cut by any other status byte is dropped, so are data bytes without a status
+
+#[derive(Clone)]
+pub struct LiveParser {
+    status: Option<u8>,  // Status of the message being received, kept between Channel messages for running status
+    data: Vec<u8>,
+    sysex: Option<Vec<u8>>,
+    pub dropped_bytes: u64  // Stray data bytes and bytes of interrupted Sysex messages
+}
+
+impl LiveParser {
+    pub fn new() -> LiveParser {
+        LiveParser {
+            status: None,
+            data: Vec::new(),
+            sysex: None,
+            dropped_bytes: 0
+        }
+    }
+
+    // Forgets the running status and any partial message, like after a cable was plugged in
+    pub fn reset(&mut self) {
+        self.status = None;
+        self.data.clear();
+        if let Some(sysex) = self.sysex.take() {
+            self.dropped_bytes += sysex.len() as u64 + 1;
+        }
+    }
+
+    // True in the middle of a message, running status alone does not count
+    pub fn is_receiving(&self) -> bool {
+        self.sysex.is_some() | !self.data.is_empty() | self.status.map(|s| s >= 0xF0u8).unwrap_or(false)
+    }
+
+    pub fn feed(&mut self, byte: u8) -> Option<LiveMessage> {
+        if byte >= 0xF8u8 {
+            return Some(real_time(byte))
+        }
+        if byte < 0x80u8 {
+            return self.feed_data(byte)
+        }
+        // Any other status byte ends a Sysex
+        let sysex: Option<Vec<u8>> = self.sysex.take();
+        self.dropped_bytes += self.data.len() as u64;
+        self.data.clear();
+        if byte == 0xF7u8 {
+            self.status = None;
+            if sysex.is_none() {
+                self.dropped_bytes += 1;
+            }
+            return sysex.map(LiveMessage::SysEx)
+        }
+        if let Some(sysex) = sysex {
+            self.dropped_bytes += sysex.len() as u64 + 1;
+        }
+        match byte {
+            0xF0 => {
+                self.status = None;
+                self.sysex = Some(Vec::new());
+                None
+            },
+            0xF4 | 0xF5 => {
+                self.status = None;
+                Some(LiveMessage::Undefined(byte))
+            },
+            0xF6 => {
+                self.status = None;
+                Some(LiveMessage::TuneRequest)
+            },
+            _ => {
+                self.status = Some(byte);
+                None
+            }
+        }
+    }
+
+    pub fn feed_bytes(&mut self, bytes: &[u8]) -> Vec<LiveMessage> {
+        bytes.iter().filter_map(|&b| self.feed(b)).collect()
+    }
+
+    fn feed_data(&mut self, byte: u8) -> Option<LiveMessage> {
+        if let Some(ref mut sysex) = self.sysex {
+            sysex.push(byte);
+            return None
+        }
+        let status: u8 = match self.status {
+            Some(s) => s,
+            None => {
+                self.dropped_bytes += 1;
+                return None
+            }
+        };
+        self.data.push(byte);
+        if Some(self.data.len()) != data_length(status) {
+            return None
+        }
+        let data: Vec<u8> = self.data.split_off(0);
+        if status < 0xF0u8 {
+            // Running status, the next data bytes reuse this status
+            return MidiEvent::read(&mut &data[..], status, None).ok().map(LiveMessage::Channel)
+        }
+        self.status = None;
+        match status {
+            0xF1 => Some(LiveMessage::MTCQuarterFrame(data[0])),
+            0xF2 => Some(LiveMessage::SongPositionPointer(u16::from(data[0]) | (u16::from(data[1]) << 7))),
+            0xF3 => Some(LiveMessage::SongSelect(data[0])),
+            _ => None
+        }
+    }
+}
+
+impl Default for LiveParser {
+    fn default() -> LiveParser {
+        LiveParser::new()
+    }
+}
+
+fn real_time(byte: u8) -> LiveMessage {
+    match byte {
+        0xF8 => LiveMessage::TimingClock,
+        0xFA => LiveMessage::Start,
+        0xFB => LiveMessage::Continue,
+        0xFC => LiveMessage::Stop,
+        0xFE => LiveMessage::ActiveSensing,
+        0xFF => LiveMessage::SystemReset,
+        _ => LiveMessage::Undefined(byte)
+    }
+}
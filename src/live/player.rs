@@ -0,0 +1,380 @@
+use super::LiveMessage;
+use file::SMF;
+use file::tempo::TempoMap;
+use file::track::data::event::MidiEvent;
+use file::track::data::event::MidiEventType;
+use file::track::data::event::midi::ControllerChange;
+use file::track::data::event::midi::NoteChange;
+use file::track::data::event::midi::PolyphonicKeyPressure;
+use gm::GM_PERCUSSION_CHANNEL;
+use std::error::Error;
+use std::result::Result;
+use std::time::Instant;
+
+// Where played messages go, a MIDI port, a synthesizer or a test buffer
+pub trait MidiSink {
+    fn send(&mut self, message: &LiveMessage) -> Result<(), Box<Error>>;
+}
+
+impl MidiSink for Vec<LiveMessage> {
+    fn send(&mut self, message: &LiveMessage) -> Result<(), Box<Error>> {
+        self.push(message.clone());
+        Ok(())
+    }
+}
+
+// Monotonic time in microseconds
+pub trait Clock {
+    fn now(&self) -> u64;
+}
+
+pub struct SystemClock {
+    origin: Instant
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock {
+            origin: Instant::now()
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> SystemClock {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        let elapsed = self.origin.elapsed();
+        elapsed.as_secs() * 1_000_000 + u64::from(elapsed.subsec_micros())
+    }
+}
+
+// Clock that only moves when told to, for tests and offline rendering
+#[derive(Clone)]
+pub struct ManualClock {
+    pub time: u64
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock {
+            time: 0
+        }
+    }
+
+    pub fn advance(&mut self, micros: u64) {
+        self.time += micros;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> ManualClock {
+        ManualClock::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> u64 {
+        self.time
+    }
+}
+
+// Clocks count whole microseconds, positions closer than this to the clock are due
+const TOLERANCE: f64 = 0.5;
+
+// A message of the file with its position
+struct Scheduled {
+    tick: u64,
+    micros: f64,
+    message: LiveMessage
+}
+
+// A note that was sent and not released yet, with the key it was sent with
+#[derive(Clone, Copy)]
+struct SoundingNote {
+    channel: u8,
+    key: u8,
+    sent_key: u8
+}
+
+// Plays a file on a sink following its tempo map, update has to be called regularly to send the messages that are due
+// Meta Events are not sent, and neither are Sysex Events that do not hold a complete message
+
+pub struct Player<C: Clock, S: MidiSink> {
+    pub clock: C,
+    pub sink: S,
+    smf: SMF,
+    map: TempoMap,
+    timeline: Vec<Scheduled>,
+    end_tick: u64,
+    next: usize,
+    song_micros: f64,  // Position in the file when the clock read anchor
+    anchor: u64,
+    playing: bool,
+    tempo_scale: f64,
+    transpose: i8,
+    loop_region: Option<(u64, u64)>,
+    sounding: Vec<SoundingNote>
+}
+
+impl<C: Clock, S: MidiSink> Player<C, S> {
+    pub fn new(smf: SMF, clock: C, sink: S) -> Player<C, S> {
+        let map: TempoMap = smf.tempo_map();
+        let mut events: Vec<(u64, LiveMessage)> = Vec::new();
+        for track in &smf.tracks {
+            for (tick, event) in track.absolute_events() {
+                if let Ok(message) = LiveMessage::from_event(&event) {
+                    events.push((tick, message));
+                }
+            }
+        }
+        events.sort_by_key(|&(tick, _)| tick);
+        let timeline: Vec<Scheduled> = events.into_iter()
+            .map(|(tick, message)| Scheduled { tick, micros: map.tick_to_micros(tick), message })
+            .collect();
+        let end_tick: u64 = smf.end_tick();
+        let anchor: u64 = clock.now();
+        Player {
+            clock,
+            sink,
+            smf,
+            map,
+            timeline,
+            end_tick,
+            next: 0,
+            song_micros: 0.0,
+            anchor,
+            playing: false,
+            tempo_scale: 1.0,
+            transpose: 0,
+            loop_region: None,
+            sounding: Vec::new()
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    // Position in microseconds of file time, tempo scaling excluded
+    pub fn position_micros(&self) -> f64 {
+        if self.playing {
+            self.song_micros + (self.clock.now().saturating_sub(self.anchor) as f64) * self.tempo_scale
+        } else {
+            self.song_micros
+        }
+    }
+
+    pub fn position_tick(&self) -> u64 {
+        self.map.micros_to_tick(self.position_micros())
+    }
+
+    pub fn play(&mut self) {
+        if !self.playing {
+            self.anchor = self.clock.now();
+            self.playing = true;
+        }
+    }
+
+    // Stops where it is, sounding notes are released and playing again goes on from there
+    pub fn pause(&mut self) -> Result<(), Box<Error>> {
+        self.song_micros = self.position_micros();
+        self.playing = false;
+        self.release_notes()
+    }
+
+    // Releases every note, sends All Notes Off on every channel and goes back to the start
+    pub fn stop(&mut self) -> Result<(), Box<Error>> {
+        self.playing = false;
+        self.release_notes()?;
+        for channel in 0..16u8 {
+            self.send_controller(channel, 123, 0)?;
+        }
+        self.song_micros = 0.0;
+        self.next = 0;
+        Ok(())
+    }
+
+    // Jumps to a tick, programs, controllers and pitch bends are sent as they are at this tick
+    pub fn seek(&mut self, tick: u64) -> Result<(), Box<Error>> {
+        self.release_notes()?;
+        self.jump(tick);
+        self.chase(tick)?;
+        Ok(())
+    }
+
+    // Plays [start, end) over and over, None plays through
+    pub fn set_loop(&mut self, region: Option<(u64, u64)>) {
+        self.loop_region = region.and_then(|(start, end)| if end > start { Some((start, end)) } else { None });
+    }
+
+    // 2.0 plays twice as fast
+    pub fn set_tempo_scale(&mut self, scale: f64) {
+        if scale <= 0.0 {
+            return;
+        }
+        self.song_micros = self.position_micros();
+        self.anchor = self.clock.now();
+        self.tempo_scale = scale;
+    }
+
+    // Applies to notes sent from now on, the percussion channel is not transposed
+    pub fn set_transpose(&mut self, semitones: i8) {
+        self.transpose = semitones;
+    }
+
+    // Clock time when the next message is due, None when nothing is left to play
+    pub fn next_due(&self) -> Option<u64> {
+        if !self.playing {
+            return None
+        }
+        let mut target: f64 = match self.timeline.get(self.next) {
+            Some(s) => s.micros,
+            None => self.map.tick_to_micros(self.end_tick)
+        };
+        if let Some((_, end)) = self.loop_region {
+            target = target.min(self.map.tick_to_micros(end));
+        }
+        let wait: f64 = ((target - self.song_micros) / self.tempo_scale).max(0.0);
+        Some(self.anchor + wait.round() as u64)
+    }
+
+    // Sends every message due by now, returns how many were sent
+    // Playback stops by itself at the end of the file
+    pub fn update(&mut self) -> Result<usize, Box<Error>> {
+        let mut sent: usize = 0;
+        if !self.playing {
+            return Ok(sent)
+        }
+        loop {
+            let now: f64 = self.position_micros();
+            match self.loop_region {
+                Some((start, end)) => {
+                    let end_micros: f64 = self.map.tick_to_micros(end);
+                    sent += self.send_due(now.min(end_micros), Some(end))?;
+                    if now + TOLERANCE < end_micros {
+                        break;
+                    }
+                    // The time past the loop end is carried over to the loop start, at most one loop length of it
+                    // so that a position far after the loop, like after a seek, does not replay the loop many times
+                    let loop_micros: f64 = end_micros - self.map.tick_to_micros(start);
+                    let overshoot: f64 = if loop_micros > 0.0 { (now - end_micros).max(0.0) % loop_micros } else { 0.0 };
+                    self.release_notes()?;
+                    self.jump(start);
+                    sent += self.chase(start)?;
+                    self.song_micros += overshoot;
+                },
+                None => {
+                    sent += self.send_due(now, None)?;
+                    if (self.next >= self.timeline.len()) & (now + TOLERANCE >= self.map.tick_to_micros(self.end_tick)) {
+                        self.song_micros = now;
+                        self.playing = false;
+                        self.release_notes()?;
+                    }
+                    break;
+                }
+            }
+        }
+        Ok(sent)
+    }
+
+    fn jump(&mut self, tick: u64) {
+        self.next = self.timeline.iter().position(|s| s.tick >= tick).unwrap_or(self.timeline.len());
+        self.song_micros = self.map.tick_to_micros(tick);
+        self.anchor = self.clock.now();
+    }
+
+    // Sends the programs, controllers and pitch bends as they are at this tick, returns how many were sent
+    fn chase(&mut self, tick: u64) -> Result<usize, Box<Error>> {
+        let mut sent: usize = 0;
+        for event in self.smf.state_at(tick).to_events() {
+            if let Ok(message) = LiveMessage::from_event(&event) {
+                self.sink.send(&message)?;
+                sent += 1;
+            }
+        }
+        Ok(sent)
+    }
+
+    fn send_due(&mut self, until: f64, before_tick: Option<u64>) -> Result<usize, Box<Error>> {
+        let mut sent: usize = 0;
+        while self.next < self.timeline.len() {
+            let (tick, micros) = (self.timeline[self.next].tick, self.timeline[self.next].micros);
+            if (micros > until + TOLERANCE) | before_tick.map(|end| tick >= end).unwrap_or(false) {
+                break;
+            }
+            let message: LiveMessage = self.timeline[self.next].message.clone();
+            self.next += 1;
+            if let Some(message) = self.prepare(message) {
+                self.sink.send(&message)?;
+                sent += 1;
+            }
+        }
+        Ok(sent)
+    }
+
+    // Transposes notes and keeps track of the sounding ones, None for notes pushed out of range
+    fn prepare(&mut self, message: LiveMessage) -> Option<LiveMessage> {
+        let event: MidiEvent = match message {
+            LiveMessage::Channel(e) => e,
+            other => return Some(other)
+        };
+        let channel: u8 = event.channel;
+        let event_type: MidiEventType = match event.event {
+            MidiEventType::NoteOn(ref n) if n.velocity > 0 => {
+                let sent_key: u8 = self.transposed(channel, n.key)?;
+                self.sounding.push(SoundingNote { channel, key: n.key, sent_key });
+                MidiEventType::NoteOn(NoteChange { key: sent_key, velocity: n.velocity })
+            },
+            MidiEventType::NoteOn(ref n) | MidiEventType::NoteOff(ref n) => {
+                // Released with the key it was played with, even if the transposition changed since
+                let position = self.sounding.iter().position(|s| (s.channel == channel) & (s.key == n.key))?;
+                let sounding: SoundingNote = self.sounding.remove(position);
+                let change = NoteChange { key: sounding.sent_key, velocity: n.velocity };
+                match event.event {
+                    MidiEventType::NoteOn(_) => MidiEventType::NoteOn(change),
+                    _ => MidiEventType::NoteOff(change)
+                }
+            },
+            MidiEventType::PolyphonicKeyPressure(ref p) => {
+                let key: u8 = self.sounding.iter()
+                    .find(|s| (s.channel == channel) & (s.key == p.key))
+                    .map(|s| s.sent_key)
+                    .unwrap_or(p.key);
+                MidiEventType::PolyphonicKeyPressure(PolyphonicKeyPressure { key, pressure: p.pressure })
+            },
+            ref other => other.clone()
+        };
+        Some(LiveMessage::Channel(MidiEvent::from_type(channel, event_type)))
+    }
+
+    fn transposed(&self, channel: u8, key: u8) -> Option<u8> {
+        if channel == GM_PERCUSSION_CHANNEL {
+            return Some(key)
+        }
+        let key: i16 = i16::from(key) + i16::from(self.transpose);
+        if !(0..=127).contains(&key) {
+            None
+        } else {
+            Some(key as u8)
+        }
+    }
+
+    fn release_notes(&mut self) -> Result<(), Box<Error>> {
+        let sounding: Vec<SoundingNote> = self.sounding.split_off(0);
+        for note in sounding {
+            let off = MidiEvent::from_type(note.channel, MidiEventType::NoteOff(NoteChange { key: note.sent_key, velocity: 0 }));
+            self.sink.send(&LiveMessage::Channel(off))?;
+        }
+        Ok(())
+    }
+
+    fn send_controller(&mut self, channel: u8, controller_number: u8, controller_value: u8) -> Result<(), Box<Error>> {
+        let event = MidiEventType::ControllerChange(ControllerChange { controller_number, controller_value });
+        self.sink.send(&LiveMessage::Channel(MidiEvent::from_type(channel, event)))
+    }
+}
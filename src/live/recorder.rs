@@ -0,0 +1,128 @@
+use super::LiveMessage;
+use file::SMF;
+use file::header::SMFHeaderChunk;
+use file::header::data::MidiDivisionsType;
+use file::header::data::MidiFormat;
+use file::header::data::MidiTPQNDivisions;
+use file::resolution::InvalidResolutionError;
+use file::tempo::DEFAULT_TEMPO;
+use file::track::SMFTrackChunk;
+use file::track::data::event::Event;
+use file::track::data::event::MetaEvent;
+use file::track::data::event::MetaEventType;
+use file::track::data::event::meta::SetTempo;
+use std::error::Error;
+use std::result::Result;
+
+#[derive(Clone)]
+pub struct RecorderOptions {
+    pub ticks_per_quarter_note: u16,
+    pub tempo: u32,  // Microseconds per quarter note, written at tick 0
+    pub split_channels: bool,  // Format 1 with a conductor track and one track per channel, Format 0 otherwise
+    pub keep_real_time: bool  // Clock, Start, Stop, Active Sensing... are stored in F7 Events
+}
+
+impl RecorderOptions {
+    pub fn new(ticks_per_quarter_note: u16, tempo: u32) -> RecorderOptions {
+        RecorderOptions {
+            ticks_per_quarter_note,
+            tempo,
+            split_channels: false,
+            keep_real_time: false
+        }
+    }
+
+    // One tick per millisecond, for recordings that do not follow a beat
+    pub fn tempo_free() -> RecorderOptions {
+        RecorderOptions::new(1000, 1_000_000)
+    }
+}
+
+// Collects messages with their arrival time in microseconds, from any clock, and turns them into a file
+
+#[derive(Clone)]
+pub struct Recorder {
+    pub options: RecorderOptions,
+    start: Option<u64>,
+    end: Option<u64>,
+    messages: Vec<(u64, LiveMessage)>
+}
+
+impl Recorder {
+    pub fn new(options: RecorderOptions) -> Recorder {
+        Recorder {
+            options,
+            start: None,
+            end: None,
+            messages: Vec::new()
+        }
+    }
+
+    // Time of tick 0, the first recorded message otherwise
+    pub fn start(&mut self, timestamp: u64) {
+        self.start = Some(timestamp);
+    }
+
+    // Time of the End of Track, the last recorded message otherwise
+    pub fn stop(&mut self, timestamp: u64) {
+        self.end = Some(timestamp);
+    }
+
+    pub fn record(&mut self, timestamp: u64, message: LiveMessage) {
+        if message.is_real_time() & !self.options.keep_real_time {
+            return;
+        }
+        if self.start.is_none() {
+            self.start = Some(timestamp);
+        }
+        self.messages.push((timestamp, message));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.start = None;
+        self.end = None;
+        self.messages.clear();
+    }
+
+    // Messages received before the start are put on tick 0
+    pub fn to_smf(&self) -> Result<SMF, Box<Error>> {
+        let ppq: u16 = self.options.ticks_per_quarter_note;
+        if (ppq == 0) | (ppq > 0x7FFFu16) {
+            return Err(Box::new(InvalidResolutionError))
+        }
+        let tempo: u32 = if self.options.tempo == 0 { DEFAULT_TEMPO } else { self.options.tempo };
+        let start: u64 = self.start.unwrap_or(0);
+        let to_tick = |timestamp: u64| -> u64 {
+            let micros: u64 = timestamp.saturating_sub(start);
+            // Rounded to the nearest tick
+            (micros * u64::from(ppq) * 2 + u64::from(tempo)) / (u64::from(tempo) * 2)
+        };
+        let mut events: Vec<(u64, Event)> = vec![
+            (0, Event::from_meta(MetaEvent::from_type(MetaEventType::SetTempo(SetTempo { tempo }))?))
+        ];
+        for &(timestamp, ref message) in &self.messages {
+            events.push((to_tick(timestamp), message.to_event()?));
+        }
+        if let Some(end) = self.end {
+            events.push((to_tick(end), Event::end_of_track()));
+        }
+        let smf = SMF {
+            header: SMFHeaderChunk {
+                length: 6,
+                format: MidiFormat::SingleTrack,
+                nb_tracks: 1,
+                division_system: MidiDivisionsType::TicksPerQuarterNote(MidiTPQNDivisions { ticks_per_quarter_note: ppq })
+            },
+            tracks: vec![SMFTrackChunk::from_absolute_events(events)?]
+        };
+        if self.options.split_channels {
+            smf.to_format_1()
+        } else {
+            Ok(smf)
+        }
+    }
+}
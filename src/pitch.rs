@@ -0,0 +1,223 @@
+use file::track::data::event::meta::KeySignature;
+use file::track::data::event::midi::NoteChange;
+use std::error::Error;
+use std::result::Result;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug)]
+pub struct InvalidPitchNameError;
+
+impl Error for InvalidPitchNameError {
+    fn description(&self) -> &str {
+        "A pitch name is a letter from A to G, optional accidentals (#, b, x) and an octave number, within keys 0 to 127"
+    }
+}
+
+impl fmt::Display for InvalidPitchNameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid pitch name")
+    }
+}
+
+// Frequency of A4 (key 69) used by default
+pub const DEFAULT_A4: f64 = 440.0;
+
+// Octave number of middle C (key 60) used by default, Yamaha uses 3
+pub const DEFAULT_MIDDLE_C_OCTAVE: i8 = 4;
+
+const LETTERS: [char; 7] = ['C', 'D', 'E', 'F', 'G', 'A', 'B'];
+const LETTER_CLASSES: [u8; 7] = [0, 2, 4, 5, 7, 9, 11];
+const SHARP_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+const FLAT_NAMES: [&str; 12] = ["C", "Db", "D", "Eb", "E", "F", "Gb", "G", "Ab", "A", "Bb", "B"];
+// Letters in the order they get sharps in key signatures, flats use the reverse order
+const SHARP_ORDER: [char; 7] = ['F', 'C', 'G', 'D', 'A', 'E', 'B'];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Accidental {
+    Sharp,
+    Flat
+}
+
+// How names are written and read
+#[derive(Clone, Copy)]
+pub struct PitchNaming {
+    pub middle_c_octave: i8,
+    pub accidental: Accidental  // Used for notes outside of the diatonic scale
+}
+
+impl PitchNaming {
+    pub fn new() -> PitchNaming {
+        PitchNaming {
+            middle_c_octave: DEFAULT_MIDDLE_C_OCTAVE,
+            accidental: Accidental::Sharp
+        }
+    }
+}
+
+impl Default for PitchNaming {
+    fn default() -> PitchNaming {
+        PitchNaming::new()
+    }
+}
+
+// A MIDI key, 60 is middle C
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Pitch {
+    pub key: u8
+}
+
+impl Pitch {
+    pub fn new(key: u8) -> Pitch {
+        Pitch {
+            key: key & 0x7Fu8
+        }
+    }
+
+    // 0 is C, 11 is B
+    pub fn pitch_class(&self) -> u8 {
+        self.key % 12
+    }
+
+    pub fn octave(&self, middle_c_octave: i8) -> i8 {
+        (self.key / 12) as i8 - 5 + middle_c_octave
+    }
+
+    pub fn name(&self, naming: &PitchNaming) -> String {
+        let names = match naming.accidental {
+            Accidental::Sharp => &SHARP_NAMES,
+            Accidental::Flat => &FLAT_NAMES
+        };
+        format!("{}{}", names[self.pitch_class() as usize], self.octave(naming.middle_c_octave))
+    }
+
+    // Notes of the scale use the letters of the key (E# in C# major, Cb in Gb major), others follow the direction of the key
+    pub fn name_in_key(&self, key_signature: &KeySignature, middle_c_octave: i8) -> String {
+        let sharps_flats: i8 = key_signature.sharps_flats();
+        for (letter_index, &letter) in LETTERS.iter().enumerate() {
+            let alteration: i8 = letter_alteration(letter, sharps_flats);
+            let class: i8 = LETTER_CLASSES[letter_index] as i8 + alteration;
+            if (class + 12) % 12 == self.pitch_class() as i8 {
+                // B# and Cb are written with the octave of their letter
+                let octave_shift: i8 = if class < 0 { 1 } else if class > 11 { -1 } else { 0 };
+                return format!("{}{}{}", letter, accidental_text(alteration), self.octave(middle_c_octave) + octave_shift)
+            }
+        }
+        let naming = PitchNaming {
+            middle_c_octave,
+            accidental: if sharps_flats < 0 { Accidental::Flat } else { Accidental::Sharp }
+        };
+        self.name(&naming)
+    }
+
+    // Letter, accidentals (#, b, x, ♯, ♭) then octave, like "C#4", "Db4" or "Bb-1"
+    pub fn parse(name: &str, middle_c_octave: i8) -> Result<Pitch, Box<Error>> {
+        let mut chars = name.trim().chars().peekable();
+        let letter: char = match chars.next() {
+            Some(c) => c.to_ascii_uppercase(),
+            None => return Err(Box::new(InvalidPitchNameError))
+        };
+        let letter_index: usize = match LETTERS.iter().position(|&l| l == letter) {
+            Some(i) => i,
+            None => return Err(Box::new(InvalidPitchNameError))
+        };
+        let mut alteration: i32 = 0;
+        loop {
+            match chars.peek() {
+                Some(&'#') | Some(&'♯') => alteration += 1,
+                Some(&'x') | Some(&'𝄪') => alteration += 2,
+                Some(&'b') | Some(&'♭') => alteration -= 1,
+                _ => break
+            }
+            chars.next();
+        }
+        let octave_text: String = chars.collect();
+        let octave: i32 = match octave_text.parse::<i32>() {
+            Ok(o) => o,
+            Err(_) => return Err(Box::new(InvalidPitchNameError))
+        };
+        let key: i32 = (octave - i32::from(middle_c_octave) + 5) * 12 + i32::from(LETTER_CLASSES[letter_index]) + alteration;
+        if !(0..=127).contains(&key) {
+            return Err(Box::new(InvalidPitchNameError))
+        }
+        Ok(Pitch::new(key as u8))
+    }
+
+    pub fn frequency(&self, a4: f64) -> f64 {
+        self.frequency_with_cents(a4, 0.0)
+    }
+
+    // Frequency moved by an offset in cents, like the one given by a pitch bend
+    pub fn frequency_with_cents(&self, a4: f64, cents: f64) -> f64 {
+        a4 * 2f64.powf((f64::from(self.key) - 69.0 + cents / 100.0) / 12.0)
+    }
+
+    // Nearest key and the offset in cents from it, None outside of keys 0 to 127
+    pub fn from_frequency(frequency: f64, a4: f64) -> Option<(Pitch, f64)> {
+        if (frequency <= 0.0) | (a4 <= 0.0) {
+            return None
+        }
+        let exact: f64 = 69.0 + 12.0 * (frequency / a4).log2();
+        let key: f64 = exact.round();
+        if !(0.0..=127.0).contains(&key) {
+            return None
+        }
+        Some((Pitch::new(key as u8), (exact - key) * 100.0))
+    }
+}
+
+// -1, 0 or 1 for a letter in the key with this many sharps (positive) or flats (negative)
+fn letter_alteration(letter: char, sharps_flats: i8) -> i8 {
+    let position: i8 = SHARP_ORDER.iter().position(|&l| l == letter).unwrap_or(0) as i8;
+    if position < sharps_flats {
+        1
+    } else if 6 - position < -sharps_flats {
+        -1
+    } else {
+        0
+    }
+}
+
+fn accidental_text(alteration: i8) -> &'static str {
+    match alteration {
+        1 => "#",
+        -1 => "b",
+        _ => ""
+    }
+}
+
+// Default naming, sharps and middle C as C4
+impl fmt::Display for Pitch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name(&PitchNaming::new()))
+    }
+}
+
+impl FromStr for Pitch {
+    type Err = Box<Error>;
+
+    fn from_str(s: &str) -> Result<Pitch, Box<Error>> {
+        Pitch::parse(s, DEFAULT_MIDDLE_C_OCTAVE)
+    }
+}
+
+impl KeySignature {
+    // Positive for sharps, negative for flats
+    pub fn sharps_flats(&self) -> i8 {
+        self.number_of_sharp_flats as i8
+    }
+
+    // Pitch class of the tonic, 0 is C
+    pub fn tonic(&self) -> u8 {
+        let major: i16 = ((i16::from(self.sharps_flats()) * 7) % 12 + 12) % 12;
+        let tonic: i16 = if self.major_key { major } else { (major + 9) % 12 };
+        tonic as u8
+    }
+}
+
+impl NoteChange {
+    pub fn pitch(&self) -> Pitch {
+        Pitch::new(self.key)
+    }
+}
@@ -0,0 +1,243 @@
+use controller::ControllerDecoder;
+use controller::DataChange;
+use controller::RPN_PITCH_BEND_SENSITIVITY;
+use controller::pitch_bend_sensitivity_cents;
+use controller::encode_parameter;
+use file::SMF;
+use file::track::data::event::Event;
+use file::track::data::event::EventType;
+use file::track::data::event::MidiEvent;
+use file::track::data::event::MidiEventType;
+use file::track::data::event::midi::ChannelKeyPressure;
+use file::track::data::event::midi::ControllerChange;
+use file::track::data::event::midi::DEFAULT_PITCH_BEND_RANGE;
+use file::track::data::event::midi::PitchBend;
+use file::track::data::event::midi::ProgramChange;
+
+// Everything a channel remembers from the events it received
+
+#[derive(Clone)]
+pub struct ChannelState {
+    pub program: Option<u8>,
+    pub controllers: Vec<Option<u8>>,  // Indexed by controller number, bank select is in 0 and 32
+    pub pitch_bend: Option<u16>,
+    pub channel_pressure: Option<u8>,
+    pub parameters: ControllerDecoder,  // Selected parameter and RPN / NRPN values
+    pub sounding_notes: Vec<(u8, u8)>  // Key and velocity, in the order they were played
+}
+
+// Controllers that are not remembered as values: Data Entry, increments, parameter selection and channel mode messages
+pub fn is_state_controller(controller_number: u8) -> bool {
+    !matches!(controller_number, 6 | 38 | 96..=101 | 120..=127)
+}
+
+// Controllers kept by Reset All Controllers: bank, volume, pan and effect depths
+fn survives_reset(controller_number: u8) -> bool {
+    matches!(controller_number, 0 | 32 | 7 | 39 | 10 | 42 | 91..=95)
+}
+
+impl ChannelState {
+    pub fn new() -> ChannelState {
+        ChannelState {
+            program: None,
+            controllers: vec![None; 128],
+            pitch_bend: None,
+            channel_pressure: None,
+            parameters: ControllerDecoder::new(),
+            sounding_notes: Vec::new()
+        }
+    }
+
+    pub fn feed(&mut self, event: &MidiEventType) {
+        match *event {
+            MidiEventType::NoteOn(ref n) => {
+                if n.velocity > 0 {
+                    self.sounding_notes.push((n.key, n.velocity));
+                } else {
+                    self.release(n.key);
+                }
+            },
+            MidiEventType::NoteOff(ref n) => self.release(n.key),
+            MidiEventType::PolyphonicKeyPressure(_) => {},
+            MidiEventType::ControllerChange(ref c) => self.feed_controller(c.controller_number, c.controller_value),
+            MidiEventType::ProgramChange(ref p) => self.program = Some(p.new_program_number),
+            MidiEventType::ChannelKeyPressure(ref p) => self.channel_pressure = Some(p.value),
+            MidiEventType::PitchBend(ref b) => self.pitch_bend = Some(b.value)
+        }
+    }
+
+    pub fn controller(&self, controller_number: u8) -> Option<u8> {
+        self.controllers[(controller_number & 0x7Fu8) as usize]
+    }
+
+    // Bank from controllers 0 (MSB) and 32 (LSB)
+    pub fn bank(&self) -> Option<u16> {
+        match (self.controller(0), self.controller(32)) {
+            (None, None) => None,
+            (msb, lsb) => Some((u16::from(msb.unwrap_or(0)) << 7) | u16::from(lsb.unwrap_or(0)))
+        }
+    }
+
+    pub fn rpn(&self, parameter: u16) -> Option<u16> {
+        self.parameters.rpn(parameter)
+    }
+
+    pub fn nrpn(&self, parameter: u16) -> Option<u16> {
+        self.parameters.nrpn(parameter)
+    }
+
+    // From RPN 0 (Pitch Bend Sensitivity) when it was received, the General MIDI default otherwise
+    pub fn pitch_bend_range_cents(&self) -> f64 {
+        self.rpn(RPN_PITCH_BEND_SENSITIVITY)
+            .map(pitch_bend_sensitivity_cents)
+            .unwrap_or(DEFAULT_PITCH_BEND_RANGE)
+    }
+
+    // Current bend in cents, using the bend range of this channel
+    pub fn pitch_bend_cents(&self) -> f64 {
+        match self.pitch_bend {
+            Some(value) => PitchBend { value }.cents(self.pitch_bend_range_cents()),
+            None => 0.0
+        }
+    }
+
+    // Events bringing a reset channel to this state, notes excluded
+    // Bank and program come first, parameters are set then deselected so later Data Entries do not change them
+    pub fn to_events(&self, channel: u8) -> Vec<Event> {
+        let mut events: Vec<Event> = Vec::new();
+        for &number in &[0u8, 32u8] {
+            if let Some(value) = self.controller(number) {
+                events.push(controller_event(channel, number, value));
+            }
+        }
+        if let Some(program) = self.program {
+            events.push(midi_event(channel, MidiEventType::ProgramChange(ProgramChange { new_program_number: program })));
+        }
+        for number in 0..128u8 {
+            if (number == 0) | (number == 32) | !is_state_controller(number) {
+                continue;
+            }
+            if let Some(value) = self.controller(number) {
+                events.push(controller_event(channel, number, value));
+            }
+        }
+        for &(values, registered) in &[(&self.parameters.rpn_values, true), (&self.parameters.nrpn_values, false)] {
+            for p in values.iter() {
+                for c in encode_parameter(registered, p.parameter, p.value, DataChange::Fine, false) {
+                    events.push(controller_event(channel, c.controller_number, c.controller_value));
+                }
+            }
+        }
+        if !self.parameters.rpn_values.is_empty() | !self.parameters.nrpn_values.is_empty() {
+            events.push(controller_event(channel, 101, 127));
+            events.push(controller_event(channel, 100, 127));
+        }
+        if let Some(value) = self.pitch_bend {
+            events.push(midi_event(channel, MidiEventType::PitchBend(PitchBend { value })));
+        }
+        if let Some(value) = self.channel_pressure {
+            events.push(midi_event(channel, MidiEventType::ChannelKeyPressure(ChannelKeyPressure { value })));
+        }
+        events
+    }
+
+    fn release(&mut self, key: u8) {
+        if let Some(position) = self.sounding_notes.iter().position(|&(k, _)| k == key) {
+            self.sounding_notes.remove(position);
+        }
+    }
+
+    fn feed_controller(&mut self, number: u8, value: u8) {
+        match number {
+            // Data Entry, increments and parameter selection
+            6 | 38 | 96..=101 => {
+                self.parameters.feed(&ControllerChange { controller_number: number, controller_value: value });
+            },
+            // All Sound Off, All Notes Off and mode changes that imply it
+            120 | 123..=127 => self.sounding_notes.clear(),
+            // Reset All Controllers
+            121 => {
+                for n in 0..128u8 {
+                    if !survives_reset(n) {
+                        self.controllers[n as usize] = None;
+                    }
+                }
+                self.pitch_bend = None;
+                self.channel_pressure = None;
+                self.parameters.reset();
+            },
+            122 => {},
+            _ => self.controllers[(number & 0x7Fu8) as usize] = Some(value)
+        }
+    }
+}
+
+impl Default for ChannelState {
+    fn default() -> ChannelState {
+        ChannelState::new()
+    }
+}
+
+fn controller_event(channel: u8, controller_number: u8, controller_value: u8) -> Event {
+    midi_event(channel, MidiEventType::ControllerChange(ControllerChange { controller_number, controller_value }))
+}
+
+fn midi_event(channel: u8, event: MidiEventType) -> Event {
+    Event::from_midi(MidiEvent::from_type(channel, event))
+}
+
+// State of all 16 channels
+
+#[derive(Clone)]
+pub struct MidiState {
+    pub channels: Vec<ChannelState>
+}
+
+impl MidiState {
+    pub fn new() -> MidiState {
+        MidiState {
+            channels: vec![ChannelState::new(); 16]
+        }
+    }
+
+    pub fn feed(&mut self, event: &MidiEvent) {
+        self.channels[(event.channel & 0x0Fu8) as usize].feed(&event.event);
+    }
+
+    // Sysex and Meta Events are ignored
+    pub fn feed_event(&mut self, event: &Event) {
+        if let EventType::MidiEvent(ref e) = event.event {
+            self.feed(e);
+        }
+    }
+
+    pub fn to_events(&self) -> Vec<Event> {
+        let mut events: Vec<Event> = Vec::new();
+        for (channel, state) in self.channels.iter().enumerate() {
+            events.extend(state.to_events(channel as u8));
+        }
+        events
+    }
+}
+
+impl Default for MidiState {
+    fn default() -> MidiState {
+        MidiState::new()
+    }
+}
+
+impl SMF {
+    // State of every channel right before the events on this tick, from all tracks
+    pub fn state_at(&self, tick: u64) -> MidiState {
+        let mut events: Vec<(u64, Event)> = Vec::new();
+        for track in &self.tracks {
+            events.extend(track.absolute_events().into_iter().filter(|&(t, _)| t < tick));
+        }
+        events.sort_by_key(|&(t, _)| t);
+        let mut state: MidiState = MidiState::new();
+        for (_, event) in events {
+            state.feed_event(&event);
+        }
+        state
+    }
+}
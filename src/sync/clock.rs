@@ -0,0 +1,83 @@
+use file::SMF;
+use file::tempo::TempoMap;
+use live::LiveMessage;
+use std::error::Error;
+use std::result::Result;
+use std::fmt;
+
+#[derive(Debug)]
+pub struct SongPositionTooFarError;
+
+impl Error for SongPositionTooFarError {
+    fn description(&self) -> &str {
+        "A Song Position Pointer reaches at most 16383 sixteenth notes after the start of the song"
+    }
+}
+
+impl fmt::Display for SongPositionTooFarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Start is beyond the last Song Position Pointer")
+    }
+}
+
+// MIDI Clock runs at 24 pulses per quarter note, Song Position Pointers count sixteenth notes (6 pulses)
+pub const CLOCKS_PER_QUARTER_NOTE: u64 = 24;
+pub const CLOCKS_PER_MIDI_BEAT: u64 = 6;
+
+// Last MIDI beat a Song Position Pointer can reach
+pub const MAX_SONG_POSITION: u16 = 0x3FFF;
+
+// Song Position Pointer value of the sixteenth note at or before this tick, clamped to MAX_SONG_POSITION
+pub fn song_position(tick: u64, ticks_per_quarter_note: u16) -> u16 {
+    midi_beats(tick, ticks_per_quarter_note).min(u64::from(MAX_SONG_POSITION)) as u16
+}
+
+// Sixteenth notes from the start of the song to this tick, rounded down
+fn midi_beats(tick: u64, ticks_per_quarter_note: u16) -> u64 {
+    tick * 4 / u64::from(ticks_per_quarter_note.max(1))
+}
+
+// Tick of a Song Position Pointer value, rounded down
+pub fn song_position_tick(position: u16, ticks_per_quarter_note: u16) -> u64 {
+    u64::from(position) * u64::from(ticks_per_quarter_note) / 4
+}
+
+// Time of a clock pulse, pulses that fall between two ticks are placed exactly with the tempo of that tick
+fn pulse_micros(map: &TempoMap, pulse: u64, ticks_per_quarter_note: u16) -> f64 {
+    let tick: f64 = (pulse * u64::from(ticks_per_quarter_note)) as f64 / CLOCKS_PER_QUARTER_NOTE as f64;
+    map.fractional_tick_to_micros(tick)
+}
+
+impl SMF {
+    // MIDI Clock for [start, end) with times in microseconds from the start of the file
+    // Playing from the start sends Start, otherwise a Song Position Pointer to the sixteenth note at or before start,
+    // then Continue, both right before the first pulse. Stop is sent at end
+    // Fails when start is after the last sixteenth note a Song Position Pointer can reach
+    pub fn midi_clock(&self, start: u64, end: u64) -> Result<Vec<(f64, LiveMessage)>, Box<Error>> {
+        let ppq: u16 = self.ticks_per_quarter_note()?;
+        let map: TempoMap = self.tempo_map();
+        let mut messages: Vec<(f64, LiveMessage)> = Vec::new();
+        let first_pulse: u64 = if start == 0 {
+            messages.push((0.0, LiveMessage::Start));
+            0
+        } else {
+            if midi_beats(start, ppq) > u64::from(MAX_SONG_POSITION) {
+                return Err(Box::new(SongPositionTooFarError))
+            }
+            let position: u16 = song_position(start, ppq);
+            let pulse: u64 = u64::from(position) * CLOCKS_PER_MIDI_BEAT;
+            let micros: f64 = pulse_micros(&map, pulse, ppq);
+            messages.push((micros, LiveMessage::SongPositionPointer(position)));
+            messages.push((micros, LiveMessage::Continue));
+            pulse
+        };
+        let mut pulse: u64 = first_pulse;
+        // Pulses strictly before end
+        while pulse * u64::from(ppq) < end * CLOCKS_PER_QUARTER_NOTE {
+            messages.push((pulse_micros(&map, pulse, ppq), LiveMessage::TimingClock));
+            pulse += 1;
+        }
+        messages.push((map.tick_to_micros(end), LiveMessage::Stop));
+        Ok(messages)
+    }
+}
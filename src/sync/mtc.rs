@@ -0,0 +1,284 @@
+use file::SMF;
+use file::header::data::MidiDivisionsType;
+use file::tempo::TempoMap;
+use file::track::data::event::EventType;
+use file::track::data::event::MetaEvent;
+use file::track::data::event::MetaEventType;
+use file::track::data::event::meta::SMTPEOffset;
+use live::LiveMessage;
+
+// SMPTE frame rates, in the order of their code in the hour byte of MIDI Time Code and SMTPE Offsets
+#[derive(Clone, Copy, PartialEq)]
+pub enum FrameRate {
+    Fps24,
+    Fps25,
+    Fps2997DropFrame,
+    Fps30
+}
+
+impl FrameRate {
+    pub fn from_code(code: u8) -> FrameRate {
+        match code & 0x03u8 {
+            0 => FrameRate::Fps24,
+            1 => FrameRate::Fps25,
+            2 => FrameRate::Fps2997DropFrame,
+            _ => FrameRate::Fps30
+        }
+    }
+
+    pub fn code(&self) -> u8 {
+        match *self {
+            FrameRate::Fps24 => 0,
+            FrameRate::Fps25 => 1,
+            FrameRate::Fps2997DropFrame => 2,
+            FrameRate::Fps30 => 3
+        }
+    }
+
+    // From the frames per second of an SMTPE division: 24, 25, 29 or 30
+    pub fn from_smtpe_frames_per_second(frames_per_second: u16) -> Option<FrameRate> {
+        match frames_per_second {
+            24 => Some(FrameRate::Fps24),
+            25 => Some(FrameRate::Fps25),
+            29 => Some(FrameRate::Fps2997DropFrame),
+            30 => Some(FrameRate::Fps30),
+            _ => None
+        }
+    }
+
+    // Frames counted per second in timecode labels
+    pub fn nominal_frames_per_second(&self) -> u64 {
+        match *self {
+            FrameRate::Fps24 => 24,
+            FrameRate::Fps25 => 25,
+            FrameRate::Fps2997DropFrame | FrameRate::Fps30 => 30
+        }
+    }
+
+    pub fn seconds_per_frame(&self) -> f64 {
+        match *self {
+            FrameRate::Fps2997DropFrame => 1001.0 / 30_000.0,
+            _ => 1.0 / self.nominal_frames_per_second() as f64
+        }
+    }
+}
+
+// Drop frame timecode skips labels 0 and 1 at the start of every minute, except every tenth minute
+const DROP_FRAMES_PER_10_MINUTES: u64 = 17_982;
+const DROP_FRAMES_PER_MINUTE: u64 = 1_798;
+
+// Timecode of the nth frame since 00:00:00:00, hours wrap after 24
+pub fn frame_to_timecode(frame: u64, rate: FrameRate) -> SMTPEOffset {
+    let mut label: u64 = frame;
+    if rate == FrameRate::Fps2997DropFrame {
+        let tens: u64 = frame / DROP_FRAMES_PER_10_MINUTES;
+        let rest: u64 = frame % DROP_FRAMES_PER_10_MINUTES;
+        label += 18 * tens;
+        if rest >= 2 {
+            label += 2 * ((rest - 2) / DROP_FRAMES_PER_MINUTE);
+        }
+    }
+    let fps: u64 = rate.nominal_frames_per_second();
+    let seconds: u64 = label / fps;
+    SMTPEOffset {
+        hour: (rate.code() << 5) | ((seconds / 3600) % 24) as u8,
+        minute: ((seconds / 60) % 60) as u8,
+        seconds: (seconds % 60) as u8,
+        frames: (label % fps) as u8,
+        hundred_of_frame: 0
+    }
+}
+
+// Frames since 00:00:00:00 counted with the frame rate stored in the timecode
+pub fn timecode_to_frame(timecode: &SMTPEOffset) -> u64 {
+    let rate: FrameRate = timecode.frame_rate();
+    let minutes: u64 = u64::from(timecode.hours()) * 60 + u64::from(timecode.minute);
+    let label: u64 = (minutes * 60 + u64::from(timecode.seconds)) * rate.nominal_frames_per_second() + u64::from(timecode.frames);
+    if rate == FrameRate::Fps2997DropFrame {
+        label - 2 * (minutes - minutes / 10)
+    } else {
+        label
+    }
+}
+
+// Sub frames are hundredths of a frame
+pub fn timecode_to_seconds(timecode: &SMTPEOffset) -> f64 {
+    let frames: f64 = timecode_to_frame(timecode) as f64 + f64::from(timecode.hundred_of_frame) / 100.0;
+    frames * timecode.frame_rate().seconds_per_frame()
+}
+
+pub fn seconds_to_timecode(seconds: f64, rate: FrameRate) -> SMTPEOffset {
+    // Rounding errors must not give the previous frame for a time right on a frame boundary
+    let frames: f64 = (seconds / rate.seconds_per_frame() + 1e-6).max(0.0);
+    let mut timecode: SMTPEOffset = frame_to_timecode(frames.floor() as u64, rate);
+    timecode.hundred_of_frame = ((frames - frames.floor()) * 100.0).floor().min(99.0) as u8;
+    timecode
+}
+
+impl SMTPEOffset {
+    // Bits 5 and 6 of the hour byte
+    pub fn frame_rate(&self) -> FrameRate {
+        FrameRate::from_code(self.hour >> 5)
+    }
+
+    pub fn hours(&self) -> u8 {
+        self.hour & 0x1Fu8
+    }
+}
+
+// Quarter Frame n carries one nibble of the timecode, 8 of them make a full timecode over 2 frames
+pub fn quarter_frame(timecode: &SMTPEOffset, piece: u8) -> LiveMessage {
+    let value: u8 = match piece & 0x07u8 {
+        0 => timecode.frames & 0x0Fu8,
+        1 => (timecode.frames >> 4) & 0x01u8,
+        2 => timecode.seconds & 0x0Fu8,
+        3 => (timecode.seconds >> 4) & 0x03u8,
+        4 => timecode.minute & 0x0Fu8,
+        5 => (timecode.minute >> 4) & 0x03u8,
+        6 => timecode.hours() & 0x0Fu8,
+        _ => ((timecode.hours() >> 4) & 0x01u8) | (timecode.frame_rate().code() << 1)
+    };
+    LiveMessage::MTCQuarterFrame(((piece & 0x07u8) << 4) | value)
+}
+
+// Universal Real Time Sysex F0 7F 7F 01 01 hr mn sc fr F7, sent to all devices
+pub fn full_frame(timecode: &SMTPEOffset) -> LiveMessage {
+    LiveMessage::SysEx(vec![
+        0x7Fu8, 0x7Fu8, 0x01u8, 0x01u8,
+        timecode.hour & 0x7Fu8, timecode.minute & 0x3Fu8, timecode.seconds & 0x3Fu8, timecode.frames & 0x1Fu8
+    ])
+}
+
+// Timecode positions from incoming Quarter Frames and Full Frame messages
+
+#[derive(Clone)]
+pub struct MtcDecoder {
+    pieces: Vec<u8>,
+    next_piece: u8
+}
+
+impl MtcDecoder {
+    pub fn new() -> MtcDecoder {
+        MtcDecoder {
+            pieces: vec![0; 8],
+            next_piece: 0
+        }
+    }
+
+    // A position is given after each full cycle of 8 Quarter Frames sent forward, pieces out of order restart the cycle
+    // The cycle carries the time of its first piece and lasts 2 frames, so 2 frames are added to get the current time
+    pub fn feed(&mut self, message: &LiveMessage) -> Option<SMTPEOffset> {
+        match *message {
+            LiveMessage::MTCQuarterFrame(data) => {
+                let piece: u8 = (data >> 4) & 0x07u8;
+                if piece != self.next_piece {
+                    self.next_piece = 0;
+                    if piece != 0 {
+                        return None
+                    }
+                }
+                self.pieces[piece as usize] = data & 0x0Fu8;
+                self.next_piece = (piece + 1) % 8;
+                if piece != 7 {
+                    return None
+                }
+                let p = &self.pieces;
+                let timecode = SMTPEOffset {
+                    hour: ((p[7] >> 1) << 5) | ((p[7] & 0x01u8) << 4) | p[6],
+                    minute: (p[5] << 4) | p[4],
+                    seconds: (p[3] << 4) | p[2],
+                    frames: (p[1] << 4) | p[0],
+                    hundred_of_frame: 0
+                };
+                let rate: FrameRate = timecode.frame_rate();
+                Some(frame_to_timecode(timecode_to_frame(&timecode) + 2, rate))
+            },
+            LiveMessage::SysEx(ref data) => {
+                if data.len() == 8 && data[0] == 0x7Fu8 && data[2] == 0x01u8 && data[3] == 0x01u8 {
+                    self.next_piece = 0;
+                    Some(SMTPEOffset {
+                        hour: data[4],
+                        minute: data[5],
+                        seconds: data[6],
+                        frames: data[7],
+                        hundred_of_frame: 0
+                    })
+                } else {
+                    None
+                }
+            },
+            _ => None
+        }
+    }
+}
+
+impl Default for MtcDecoder {
+    fn default() -> MtcDecoder {
+        MtcDecoder::new()
+    }
+}
+
+impl SMF {
+    // SMTPE Offset at the start of the first track, the time of tick 0
+    pub fn smtpe_offset(&self) -> Option<SMTPEOffset> {
+        let track = self.tracks.first()?;
+        for (tick, event) in track.absolute_events() {
+            if tick > 0 {
+                break;
+            }
+            if let EventType::MetaEvent(MetaEvent { event: MetaEventType::SMTPEOffset(ref o), .. }) = event.event {
+                return Some(o.clone())
+            }
+        }
+        None
+    }
+
+    // Rate of the SMTPE division, or of the SMTPE Offset for Ticks per Quarter Note files
+    pub fn mtc_frame_rate(&self) -> Option<FrameRate> {
+        match self.header.division_system {
+            MidiDivisionsType::SMTPEFrames(ref d) => FrameRate::from_smtpe_frames_per_second(d.smtpe_frames_per_second),
+            MidiDivisionsType::TicksPerQuarterNote(_) => self.smtpe_offset().map(|o| o.frame_rate())
+        }
+    }
+
+    // Timecode at a tick, counted from the SMTPE Offset if there is one
+    pub fn timecode_at(&self, tick: u64, rate: FrameRate) -> SMTPEOffset {
+        let offset: f64 = self.smtpe_offset().map(|o| timecode_to_seconds(&o)).unwrap_or(0.0);
+        seconds_to_timecode(offset + self.tempo_map().tick_to_seconds(tick), rate)
+    }
+
+    pub fn mtc_full_frame(&self, tick: u64, rate: FrameRate) -> LiveMessage {
+        full_frame(&self.timecode_at(tick, rate))
+    }
+
+    // Quarter Frames for [start, end), times in microseconds from the start of the file
+    // The first cycle starts on the first even frame at or after start
+    pub fn mtc_quarter_frames(&self, start: u64, end: u64, rate: FrameRate) -> Vec<(f64, LiveMessage)> {
+        let map: TempoMap = self.tempo_map();
+        let offset: f64 = self.smtpe_offset().map(|o| timecode_to_seconds(&o)).unwrap_or(0.0);
+        let seconds_per_frame: f64 = rate.seconds_per_frame();
+        let start_frames: f64 = (offset + map.tick_to_seconds(start)) / seconds_per_frame;
+        // Tiny rounding errors must not push a frame boundary to the next frame
+        let mut frame: u64 = (start_frames - 1e-6).ceil().max(0.0) as u64;
+        frame += frame % 2;
+        let end_micros: f64 = map.tick_to_micros(end);
+        let mut messages: Vec<(f64, LiveMessage)> = Vec::new();
+        let mut quarter: u64 = 0;
+        loop {
+            let piece: u64 = quarter % 8;
+            if (piece == 0) & (quarter > 0) {
+                frame += 2;
+            }
+            let cycle_timecode: SMTPEOffset = frame_to_timecode(frame, rate);
+            let seconds: f64 = (frame as f64 + piece as f64 / 4.0) * seconds_per_frame - offset;
+            let micros: f64 = seconds * 1_000_000.0;
+            // Clocks count whole microseconds, rounding errors must not add a message at end
+            if micros + 0.5 >= end_micros {
+                break;
+            }
+            messages.push((micros, quarter_frame(&cycle_timecode, piece as u8)));
+            quarter += 1;
+        }
+        messages
+    }
+}
@@ -0,0 +1,313 @@
+pub mod quantize;
+
+use file::SMF;
+use file::track::SMFTrackChunk;
+use file::track::data::event::EventType;
+use file::track::data::event::MetaEventType;
+use file::track::data::event::MidiEventType;
+use std::error::Error;
+use std::result::Result;
+
+// What to do with a note that would end up outside of 0-127
+#[derive(Clone, Copy)]
+pub enum OutOfRange {
+    Clamp,
+    Drop,
+    FoldOctave  // Moved back by octaves until it fits
+}
+
+#[derive(Clone)]
+pub struct TransposeOptions {
+    pub semitones: i8,
+    pub skip_channels: Vec<u8>,
+    pub out_of_range: OutOfRange
+}
+
+impl TransposeOptions {
+    // Skips the General Midi drum channel (10, so 9 here) and folds notes back by octaves by default
+    // Clamping would stack every out of range note on key 0 or 127
+    pub fn new(semitones: i8) -> TransposeOptions {
+        TransposeOptions {
+            semitones,
+            skip_channels: vec![9],
+            out_of_range: OutOfRange::FoldOctave
+        }
+    }
+}
+
+// Shape applied to the velocity before scaling, on a 0.0-1.0 scale
+#[derive(Clone, Copy)]
+pub enum VelocityCurve {
+    Linear,
+    Power(f64),  // Above 1.0 makes soft notes softer, below 1.0 makes them louder
+    Compress { threshold: u8, ratio: f64 }  // Above the threshold, velocities grow ratio times slower
+}
+
+#[derive(Clone)]
+pub struct VelocityOptions {
+    pub curve: VelocityCurve,
+    pub scale: f64,
+    pub offset: i16,
+    pub min: u8,
+    pub max: u8,
+    pub skip_channels: Vec<u8>
+}
+
+impl VelocityOptions {
+    // Does nothing until fields are changed
+    pub fn new() -> VelocityOptions {
+        VelocityOptions {
+            curve: VelocityCurve::Linear,
+            scale: 1.0,
+            offset: 0,
+            min: 1,
+            max: 127,
+            skip_channels: Vec::new()
+        }
+    }
+
+    // Only clamps velocities between min and max
+    pub fn clamp(min: u8, max: u8) -> VelocityOptions {
+        VelocityOptions {
+            min,
+            max,
+            ..VelocityOptions::new()
+        }
+    }
+
+    fn apply(&self, velocity: u8) -> i32 {
+        let normalized: f64 = f64::from(velocity) / 127.0;
+        let curved: f64 = match self.curve {
+            VelocityCurve::Linear => normalized,
+            VelocityCurve::Power(exponent) => normalized.powf(exponent),
+            VelocityCurve::Compress { threshold, ratio } => {
+                let threshold: f64 = f64::from(threshold) / 127.0;
+                if (normalized > threshold) & (ratio > 0.0) {
+                    threshold + (normalized - threshold) / ratio
+                } else {
+                    normalized
+                }
+            }
+        };
+        (curved * 127.0 * self.scale).round() as i32 + i32::from(self.offset)
+    }
+}
+
+impl Default for VelocityOptions {
+    fn default() -> VelocityOptions {
+        VelocityOptions::new()
+    }
+}
+
+// Which value of a note did not fit
+#[derive(Clone, Copy, PartialEq)]
+pub enum OutOfRangeKind {
+    Key,
+    Velocity
+}
+
+// A value that did not fit in 0-127 (or in the velocity limits) after a transform
+#[derive(Clone)]
+pub struct OutOfRangeEvent {
+    pub track: usize,  // Always 0 when transforming a single track
+    pub index: usize,
+    pub channel: u8,
+    pub key: u8,  // Key of the note before the transform
+    pub kind: OutOfRangeKind,
+    pub wanted: i32  // Key or velocity asked for, depending on kind
+}
+
+#[derive(Clone)]
+pub struct TransformReport {
+    pub changed: usize,
+    pub out_of_range: Vec<OutOfRangeEvent>
+}
+
+impl TransformReport {
+    fn new() -> TransformReport {
+        TransformReport {
+            changed: 0,
+            out_of_range: Vec::new()
+        }
+    }
+
+    fn append(&mut self, track: usize, other: TransformReport) {
+        self.changed += other.changed;
+        for mut event in other.out_of_range {
+            event.track = track;
+            self.out_of_range.push(event);
+        }
+    }
+}
+
+fn fit_key(wanted: i32, policy: OutOfRange) -> Option<u8> {
+    if (0..=127).contains(&wanted) {
+        return Some(wanted as u8)
+    }
+    match policy {
+        OutOfRange::Clamp => Some(if wanted < 0 { 0 } else { 127 }),
+        OutOfRange::Drop => None,
+        OutOfRange::FoldOctave => {
+            let mut key: i32 = wanted;
+            while key < 0 {
+                key += 12;
+            }
+            while key > 127 {
+                key -= 12;
+            }
+            Some(key as u8)
+        }
+    }
+}
+
+impl SMFTrackChunk {
+    // Transposes Note On, Note Off and Polyphonic Key Pressure, everything else is left as is
+    pub fn transpose(&mut self, options: &TransposeOptions) -> Result<TransformReport, Box<Error>> {
+        let mut report: TransformReport = TransformReport::new();
+        let mut to_drop: Vec<usize> = Vec::new();
+        for (index, track_event) in self.track_events.iter_mut().enumerate() {
+            if let EventType::MidiEvent(ref mut midi_event) = track_event.event.event {
+                if options.skip_channels.contains(&midi_event.channel) {
+                    continue;
+                }
+                let key: &mut u8 = match midi_event.event {
+                    MidiEventType::NoteOff(ref mut e) => &mut e.key,
+                    MidiEventType::NoteOn(ref mut e) => &mut e.key,
+                    MidiEventType::PolyphonicKeyPressure(ref mut e) => &mut e.key,
+                    _ => continue
+                };
+                let wanted: i32 = i32::from(*key) + i32::from(options.semitones);
+                if !(0..=127).contains(&wanted) {
+                    report.out_of_range.push(OutOfRangeEvent {
+                        track: 0,
+                        index,
+                        channel: midi_event.channel,
+                        key: *key,
+                        kind: OutOfRangeKind::Key,
+                        wanted
+                    });
+                }
+                // Only events that end up different are counted
+                match fit_key(wanted, options.out_of_range) {
+                    Some(new_key) if new_key == *key => continue,
+                    Some(new_key) => *key = new_key,
+                    None => to_drop.push(index)
+                }
+                report.changed += 1;
+            }
+        }
+        self.drop_indexes(to_drop)?;
+        Ok(report)
+    }
+
+    // Changes the velocity of Note Ons, Note Ons with a velocity of 0 are Note Offs and are left alone
+    pub fn scale_velocities(&mut self, options: &VelocityOptions) -> Result<TransformReport, Box<Error>> {
+        let mut report: TransformReport = TransformReport::new();
+        // A Note On can never be turned into a Note Off
+        let min: u8 = if options.min == 0 { 1 } else { options.min };
+        let max: u8 = if options.max > 127 { 127 } else { options.max };
+        for (index, track_event) in self.track_events.iter_mut().enumerate() {
+            if let EventType::MidiEvent(ref mut midi_event) = track_event.event.event {
+                if options.skip_channels.contains(&midi_event.channel) {
+                    continue;
+                }
+                if let MidiEventType::NoteOn(ref mut note) = midi_event.event {
+                    if note.velocity == 0 {
+                        continue;
+                    }
+                    let wanted: i32 = options.apply(note.velocity);
+                    let new_velocity: u8 = if wanted < i32::from(min) {
+                        min
+                    } else if wanted > i32::from(max) {
+                        max
+                    } else {
+                        wanted as u8
+                    };
+                    if i32::from(new_velocity) != wanted {
+                        report.out_of_range.push(OutOfRangeEvent {
+                            track: 0,
+                            index,
+                            channel: midi_event.channel,
+                            key: note.key,
+                            kind: OutOfRangeKind::Velocity,
+                            wanted
+                        });
+                    }
+                    if new_velocity != note.velocity {
+                        note.velocity = new_velocity;
+                        report.changed += 1;
+                    }
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    // Moves every channel event to table[old channel], Midi Channel Prefixes follow too
+    pub fn remap_channels(&mut self, table: &[u8; 16]) -> Result<TransformReport, Box<Error>> {
+        let mut report: TransformReport = TransformReport::new();
+        for track_event in &mut self.track_events {
+            let event = &mut track_event.event;
+            match event.event {
+                EventType::MidiEvent(ref mut midi_event) => {
+                    let new_channel: u8 = table[(midi_event.channel & 0x0Fu8) as usize] & 0x0Fu8;
+                    if new_channel != midi_event.channel {
+                        midi_event.channel = new_channel;
+                        event.code_byte = midi_event.code_byte | new_channel;
+                        report.changed += 1;
+                    }
+                },
+                EventType::MetaEvent(ref mut meta_event) => {
+                    if let MetaEventType::MIDIChannelPrefix(ref mut prefix) = meta_event.event {
+                        let new_channel: u8 = table[(prefix.channel & 0x0Fu8) as usize] & 0x0Fu8;
+                        if new_channel != prefix.channel {
+                            prefix.channel = new_channel;
+                            report.changed += 1;
+                        }
+                    }
+                },
+                EventType::SysExEvent(_) => {}
+            }
+        }
+        Ok(report)
+    }
+
+    fn drop_indexes(&mut self, indexes: Vec<usize>) -> Result<(), Box<Error>> {
+        if indexes.is_empty() {
+            return Ok(())
+        }
+        let events = self.absolute_events().into_iter()
+            .enumerate()
+            .filter(|&(index, _)| !indexes.contains(&index))
+            .map(|(_, event)| event)
+            .collect();
+        *self = SMFTrackChunk::from_absolute_events(events)?;
+        Ok(())
+    }
+}
+
+impl SMF {
+    pub fn transpose(&mut self, options: &TransposeOptions) -> Result<TransformReport, Box<Error>> {
+        let mut report: TransformReport = TransformReport::new();
+        for (track_index, track) in self.tracks.iter_mut().enumerate() {
+            report.append(track_index, track.transpose(options)?);
+        }
+        Ok(report)
+    }
+
+    pub fn scale_velocities(&mut self, options: &VelocityOptions) -> Result<TransformReport, Box<Error>> {
+        let mut report: TransformReport = TransformReport::new();
+        for (track_index, track) in self.tracks.iter_mut().enumerate() {
+            report.append(track_index, track.scale_velocities(options)?);
+        }
+        Ok(report)
+    }
+
+    pub fn remap_channels(&mut self, table: &[u8; 16]) -> Result<TransformReport, Box<Error>> {
+        let mut report: TransformReport = TransformReport::new();
+        for (track_index, track) in self.tracks.iter_mut().enumerate() {
+            report.append(track_index, track.remap_channels(table)?);
+        }
+        Ok(report)
+    }
+}
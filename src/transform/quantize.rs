@@ -0,0 +1,228 @@
+use file::SMF;
+use file::track::SMFTrackChunk;
+use file::track::notes::Note;
+use std::error::Error;
+use std::result::Result;
+
+// A musical duration as a fraction of a whole note, 1/16 is a sixteenth note, 1/12 an eighth note triplet
+#[derive(Clone, Copy)]
+pub struct NoteValue {
+    pub numerator: u32,
+    pub denominator: u32
+}
+
+impl NoteValue {
+    pub fn new(numerator: u32, denominator: u32) -> NoteValue {
+        NoteValue {
+            numerator,
+            denominator
+        }
+    }
+
+    // Length in ticks, rounded to the nearest tick
+    pub fn ticks(&self, ticks_per_quarter_note: u16) -> u64 {
+        if self.denominator == 0 {
+            return 0
+        }
+        let whole: u64 = u64::from(ticks_per_quarter_note) * 4 * u64::from(self.numerator);
+        (whole * 2 + u64::from(self.denominator)) / (u64::from(self.denominator) * 2)
+    }
+}
+
+#[derive(Clone)]
+pub struct QuantizeOptions {
+    pub grid: NoteValue,
+    pub strength: f64,  // 1.0 snaps onto the grid, 0.5 moves halfway
+    pub window: f64,  // Only notes closer than window * half a grid step are moved, 1.0 moves them all
+    pub quantize_ends: bool,
+    pub channels: Option<Vec<u8>>  // None for all channels
+}
+
+impl QuantizeOptions {
+    // Full strength on every note start, ends are kept
+    pub fn new(grid: NoteValue) -> QuantizeOptions {
+        QuantizeOptions {
+            grid,
+            strength: 1.0,
+            window: 1.0,
+            quantize_ends: false,
+            channels: None
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SwingOptions {
+    pub grid: NoteValue,  // Subdivision being swung, 1/8 for eighth note swing
+    pub amount: f64,  // Where the offbeat lands within a pair of subdivisions, 0.5 is straight, 0.66 is a triplet feel
+    pub channels: Option<Vec<u8>>
+}
+
+#[derive(Clone)]
+pub struct HumanizeOptions {
+    pub seed: u64,
+    pub timing: u64,  // Notes move by up to this many ticks either way
+    pub velocity: u8,  // Velocities change by up to this much either way
+    pub channels: Option<Vec<u8>>
+}
+
+// Small xorshift generator so that humanizing is reproducible from a seed without any dependency
+struct Rng {
+    state: u64
+}
+
+impl Rng {
+    // Every seed gives its own starting state, xorshift only needs it not to be 0
+    fn new(seed: u64) -> Rng {
+        let mut state: u64 = (seed ^ 0x9E37_79B9_7F4A_7C15u64).wrapping_mul(0xBF58_476D_1CE4_E5B9u64);
+        state ^= state >> 31;
+        Rng {
+            state: if state == 0 { 0x9E37_79B9_7F4A_7C15u64 } else { state }
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1Du64)
+    }
+
+    // Uniform in -range..=range
+    fn offset(&mut self, range: u64) -> i64 {
+        if range == 0 {
+            return 0
+        }
+        (self.next() % (range * 2 + 1)) as i64 - range as i64
+    }
+}
+
+fn selected(note: &Note, channels: &Option<Vec<u8>>) -> bool {
+    match *channels {
+        Some(ref c) => c.contains(&note.channel),
+        None => true
+    }
+}
+
+fn shift(tick: u64, offset: i64) -> u64 {
+    if offset < 0 {
+        tick.saturating_sub((-offset) as u64)
+    } else {
+        tick + offset as u64
+    }
+}
+
+fn quantize_tick(tick: u64, grid: u64, strength: f64, window: f64) -> u64 {
+    if grid == 0 {
+        return tick
+    }
+    let below: u64 = tick - tick % grid;
+    let nearest: u64 = if (tick - below) * 2 >= grid { below + grid } else { below };
+    let distance: f64 = (nearest as f64) - (tick as f64);
+    if distance.abs() > window * (grid as f64) / 2.0 {
+        return tick
+    }
+    shift(tick, (distance * strength).round() as i64)
+}
+
+fn swing_tick(tick: u64, grid: u64, amount: f64) -> u64 {
+    if grid == 0 {
+        return tick
+    }
+    let pair: u64 = grid * 2;
+    let pair_start: u64 = tick - tick % pair;
+    let position: f64 = (tick % pair) as f64;
+    let offbeat: f64 = (pair as f64) * amount;
+    let grid: f64 = grid as f64;
+    let swung: f64 = if position < grid {
+        position * offbeat / grid
+    } else {
+        offbeat + (position - grid) * ((pair as f64) - offbeat) / grid
+    };
+    pair_start + swung.round() as u64
+}
+
+impl SMFTrackChunk {
+    // Grid given in ticks, see SMF::quantize to use a NoteValue
+    pub fn quantize(&mut self, grid: u64, options: &QuantizeOptions) -> Result<(), Box<Error>> {
+        let mut notes: Vec<Note> = self.notes();
+        for note in &mut notes {
+            if !selected(note, &options.channels) {
+                continue;
+            }
+            let length: u64 = note.end - note.start;
+            let start: u64 = quantize_tick(note.start, grid, options.strength, options.window);
+            let mut end: u64 = if options.quantize_ends {
+                quantize_tick(note.end, grid, options.strength, options.window)
+            } else {
+                start + length
+            };
+            // A note snapped to nothing lasts a whole step
+            if end <= start {
+                end = start + if grid > 0 { grid } else { 1 };
+            }
+            note.start = start;
+            note.end = end;
+        }
+        self.update_notes(&notes)
+    }
+
+    // Offbeat subdivisions are pushed later (or earlier), note lengths are kept
+    pub fn swing(&mut self, grid: u64, options: &SwingOptions) -> Result<(), Box<Error>> {
+        let mut notes: Vec<Note> = self.notes();
+        for note in &mut notes {
+            if !selected(note, &options.channels) {
+                continue;
+            }
+            let length: u64 = note.end - note.start;
+            note.start = swing_tick(note.start, grid, options.amount);
+            note.end = note.start + length;
+        }
+        self.update_notes(&notes)
+    }
+
+    // Same seed, same notes, same result
+    pub fn humanize(&mut self, options: &HumanizeOptions) -> Result<(), Box<Error>> {
+        let mut rng: Rng = Rng::new(options.seed);
+        let mut notes: Vec<Note> = self.notes();
+        for note in &mut notes {
+            if !selected(note, &options.channels) {
+                continue;
+            }
+            let length: u64 = note.end - note.start;
+            note.start = shift(note.start, rng.offset(options.timing));
+            note.end = note.start + length;
+            let velocity: i64 = i64::from(note.velocity) + rng.offset(u64::from(options.velocity));
+            note.velocity = if velocity < 1 { 1 } else if velocity > 127 { 127 } else { velocity as u8 };
+        }
+        self.update_notes(&notes)
+    }
+}
+
+impl SMF {
+    pub fn quantize(&mut self, options: &QuantizeOptions) -> Result<(), Box<Error>> {
+        let grid: u64 = options.grid.ticks(self.ticks_per_quarter_note()?);
+        for track in &mut self.tracks {
+            track.quantize(grid, options)?;
+        }
+        Ok(())
+    }
+
+    pub fn swing(&mut self, options: &SwingOptions) -> Result<(), Box<Error>> {
+        let grid: u64 = options.grid.ticks(self.ticks_per_quarter_note()?);
+        for track in &mut self.tracks {
+            track.swing(grid, options)?;
+        }
+        Ok(())
+    }
+
+    // Every track gets its own sequence derived from the seed
+    pub fn humanize(&mut self, options: &HumanizeOptions) -> Result<(), Box<Error>> {
+        for (index, track) in self.tracks.iter_mut().enumerate() {
+            let mut track_options: HumanizeOptions = options.clone();
+            track_options.seed = options.seed.wrapping_add(index as u64);
+            track.humanize(&track_options)?;
+        }
+        Ok(())
+    }
+}
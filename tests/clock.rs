@@ -0,0 +1,90 @@
+extern crate smf_lib;
+
+mod common;
+
+use smf_lib::file::SMF;
+use smf_lib::file::track::data::event::Event;
+use smf_lib::file::track::data::event::MetaEvent;
+use smf_lib::file::track::data::event::MetaEventType;
+use smf_lib::file::track::data::event::meta::SetTempo;
+use smf_lib::live::LiveMessage;
+use smf_lib::sync::clock::SongPositionTooFarError;
+use smf_lib::sync::clock::song_position;
+use smf_lib::sync::clock::song_position_tick;
+
+// 96 ticks per quarter note, 120 BPM then 240 BPM from tick 96
+fn tempo_change() -> SMF {
+    let tempo = |tempo: u32| Event::from_meta(MetaEvent::from_type(MetaEventType::SetTempo(SetTempo { tempo })).unwrap());
+    common::smf(96, vec![vec![(0, tempo(500_000)), (96, tempo(250_000))]])
+}
+
+fn kind(message: &LiveMessage) -> String {
+    match *message {
+        LiveMessage::TimingClock => "clock".to_string(),
+        LiveMessage::Start => "start".to_string(),
+        LiveMessage::Continue => "continue".to_string(),
+        LiveMessage::Stop => "stop".to_string(),
+        LiveMessage::SongPositionPointer(position) => format!("spp {}", position),
+        _ => panic!("unexpected message")
+    }
+}
+
+fn pulses(messages: &[(f64, LiveMessage)]) -> Vec<f64> {
+    messages.iter().filter(|(_, m)| kind(m) == "clock").map(|&(micros, _)| micros).collect()
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-6, "{} is not {}", actual, expected);
+}
+
+#[test]
+fn song_positions() {
+    assert_eq!(song_position(384, 96), 16);
+    // Rounded down to the sixteenth note
+    assert_eq!(song_position(100, 96), 4);
+    assert_eq!(song_position(1_000_000, 96), 0x3FFF);
+    assert_eq!(song_position_tick(16, 96), 384);
+    assert_eq!(song_position_tick(3, 480), 360);
+}
+
+#[test]
+fn clock_across_a_tempo_change() {
+    let messages: Vec<(f64, LiveMessage)> = tempo_change().midi_clock(0, 192).unwrap();
+    assert_eq!(kind(&messages[0].1), "start");
+    assert_eq!(kind(&messages[messages.len() - 1].1), "stop");
+    assert_close(messages[messages.len() - 1].0, 750_000.0);
+    // 24 pulses per quarter note, a pulse every 500000 / 24 then every 250000 / 24 microseconds
+    let times: Vec<f64> = pulses(&messages);
+    assert_eq!(times.len(), 48);
+    assert_close(times[0], 0.0);
+    assert_close(times[1], 500_000.0 / 24.0);
+    assert_close(times[24], 500_000.0);
+    assert_close(times[47], 500_000.0 + 23.0 * 250_000.0 / 24.0);
+    // Only pulses before end, one every 4 ticks at 96 PPQ
+    let messages: Vec<(f64, LiveMessage)> = tempo_change().midi_clock(0, 98).unwrap();
+    assert_eq!(pulses(&messages).len(), 25);
+}
+
+#[test]
+fn continue_from_a_song_position() {
+    let messages: Vec<(f64, LiveMessage)> = tempo_change().midi_clock(100, 192).unwrap();
+    // The sixteenth note at or before tick 100 is tick 96, the clock starts there
+    assert_eq!(kind(&messages[0].1), "spp 4");
+    assert_eq!(kind(&messages[1].1), "continue");
+    assert_close(messages[0].0, 500_000.0);
+    assert_close(messages[1].0, 500_000.0);
+    let times: Vec<f64> = pulses(&messages);
+    assert_eq!(times.len(), 24);
+    assert_close(times[0], 500_000.0);
+    assert_eq!(kind(&messages[messages.len() - 1].1), "stop");
+}
+
+#[test]
+fn start_beyond_the_last_song_position() {
+    // 0x3FFF sixteenth notes at 96 PPQ, 24 ticks each
+    let last: u64 = 0x3FFF * 24;
+    let messages: Vec<(f64, LiveMessage)> = tempo_change().midi_clock(last + 23, last + 24).unwrap();
+    assert_eq!(kind(&messages[0].1), "spp 16383");
+    let error = tempo_change().midi_clock(last + 24, last + 48).err().unwrap();
+    assert!(error.downcast_ref::<SongPositionTooFarError>().is_some());
+}
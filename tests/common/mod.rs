@@ -0,0 +1,20 @@
+use smf_lib::file::SMF;
+use smf_lib::file::header::SMFHeaderChunk;
+use smf_lib::file::header::data::MidiDivisionsType;
+use smf_lib::file::header::data::MidiFormat;
+use smf_lib::file::header::data::MidiTPQNDivisions;
+use smf_lib::file::track::SMFTrackChunk;
+use smf_lib::file::track::data::event::Event;
+
+// File with one track per list of (absolute tick, event), Format 0 for a single track and Format 1 otherwise
+pub fn smf(ticks_per_quarter_note: u16, tracks: Vec<Vec<(u64, Event)>>) -> SMF {
+    SMF {
+        header: SMFHeaderChunk {
+            length: 6,
+            format: if tracks.len() == 1 { MidiFormat::SingleTrack } else { MidiFormat::SimultaneousTracks },
+            nb_tracks: tracks.len() as u16,
+            division_system: MidiDivisionsType::TicksPerQuarterNote(MidiTPQNDivisions { ticks_per_quarter_note })
+        },
+        tracks: tracks.into_iter().map(|events| SMFTrackChunk::from_absolute_events(events).unwrap()).collect()
+    }
+}
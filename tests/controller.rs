@@ -0,0 +1,145 @@
+extern crate smf_lib;
+
+use smf_lib::controller::ControllerDecoder;
+use smf_lib::controller::DataChange;
+use smf_lib::controller::ParameterEvent;
+use smf_lib::controller::ParameterSelection;
+use smf_lib::controller::RPN_NULL;
+use smf_lib::controller::RPN_PITCH_BEND_SENSITIVITY;
+use smf_lib::controller::encode_14_bit;
+use smf_lib::controller::encode_parameter;
+use smf_lib::controller::pitch_bend_sensitivity_cents;
+use smf_lib::controller::pitch_bend_sensitivity_value;
+use smf_lib::file::track::SMFTrackChunk;
+use smf_lib::file::track::data::event::Event;
+use smf_lib::file::track::data::event::MidiEvent;
+use smf_lib::file::track::data::event::MidiEventType;
+use smf_lib::file::track::data::event::midi::ControllerChange;
+
+fn cc(controller_number: u8, controller_value: u8) -> ControllerChange {
+    ControllerChange { controller_number, controller_value }
+}
+
+fn change_name(change: DataChange) -> &'static str {
+    match change {
+        DataChange::Coarse => "coarse",
+        DataChange::Fine => "fine",
+        DataChange::Increment => "increment",
+        DataChange::Decrement => "decrement"
+    }
+}
+
+// Kind, parameter or controller number, value and what changed
+type Described = (&'static str, u16, u16, &'static str);
+
+fn describe(event: &ParameterEvent) -> Described {
+    match *event {
+        ParameterEvent::Registered { parameter, value, change } => ("rpn", parameter, value, change_name(change)),
+        ParameterEvent::NonRegistered { parameter, value, change } => ("nrpn", parameter, value, change_name(change)),
+        ParameterEvent::Controller14 { controller_number, value } => ("cc14", u16::from(controller_number), value, ""),
+        ParameterEvent::Controller { controller_number, value } => ("cc", u16::from(controller_number), u16::from(value), "")
+    }
+}
+
+fn decode(decoder: &mut ControllerDecoder, controllers: &[(u8, u8)]) -> Vec<Described> {
+    controllers.iter().filter_map(|&(number, value)| decoder.feed(&cc(number, value))).map(|e| describe(&e)).collect()
+}
+
+fn pairs(controllers: &[ControllerChange]) -> Vec<(u8, u8)> {
+    controllers.iter().map(|c| (c.controller_number, c.controller_value)).collect()
+}
+
+#[test]
+fn decode_pitch_bend_sensitivity() {
+    let mut decoder: ControllerDecoder = ControllerDecoder::new();
+    let events = decode(&mut decoder, &[(101, 0), (100, 0), (6, 2), (38, 50)]);
+    assert_eq!(events, vec![("rpn", 0, 2 << 7, "coarse"), ("rpn", 0, (2 << 7) | 50, "fine")]);
+    let value: u16 = decoder.rpn(RPN_PITCH_BEND_SENSITIVITY).unwrap();
+    assert!((pitch_bend_sensitivity_cents(value) - 250.0).abs() < 1e-9);
+    assert_eq!(pitch_bend_sensitivity_value(250.0), value);
+    // A new coarse value resets the fine one
+    assert_eq!(decode(&mut decoder, &[(6, 12)]), vec![("rpn", 0, 12 << 7, "coarse")]);
+}
+
+#[test]
+fn interleaved_rpn_and_nrpn() {
+    let mut decoder: ControllerDecoder = ControllerDecoder::new();
+    let events = decode(&mut decoder, &[
+        (101, 0), (100, 0), (6, 2),
+        (99, 1), (98, 8), (6, 64), (96, 0),
+        (101, 0), (100, 0), (97, 0)
+    ]);
+    // Each Data Entry goes to the parameter selected last, values are kept apart
+    assert_eq!(events, vec![
+        ("rpn", 0, 2 << 7, "coarse"),
+        ("nrpn", (1 << 7) | 8, 64 << 7, "coarse"),
+        ("nrpn", (1 << 7) | 8, (64 << 7) + 1, "increment"),
+        ("rpn", 0, (2 << 7) - 1, "decrement")
+    ]);
+    assert_eq!(decoder.rpn(0), Some((2 << 7) - 1));
+    assert_eq!(decoder.nrpn((1 << 7) | 8), Some((64 << 7) + 1));
+    assert_eq!(decoder.rpn((1 << 7) | 8), None);
+}
+
+#[test]
+fn null_rpn_deselects() {
+    let mut decoder: ControllerDecoder = ControllerDecoder::new();
+    // Nothing selected yet
+    assert!(decode(&mut decoder, &[(6, 1), (38, 1), (96, 0)]).is_empty());
+    decode(&mut decoder, &[(101, 0), (100, 1), (6, 64), (101, 127), (100, 127)]);
+    assert!(decoder.selection == ParameterSelection::Registered(RPN_NULL));
+    assert!(decode(&mut decoder, &[(6, 1), (97, 0)]).is_empty());
+    assert_eq!(decoder.rpn(1), Some(64 << 7));
+    // Reset All Controllers deselects too
+    decode(&mut decoder, &[(101, 0), (100, 1)]);
+    decoder.reset();
+    assert!(decoder.selection == ParameterSelection::None);
+    assert!(decode(&mut decoder, &[(6, 1)]).is_empty());
+}
+
+#[test]
+fn decode_14_bit_controllers() {
+    let mut decoder: ControllerDecoder = ControllerDecoder::new();
+    let events = decode(&mut decoder, &[(7, 100), (39, 5), (64, 127), (39, 6)]);
+    assert_eq!(events, vec![
+        ("cc14", 7, 100 << 7, ""), ("cc14", 7, (100 << 7) | 5, ""), ("cc", 64, 127, ""), ("cc14", 7, (100 << 7) | 6, "")
+    ]);
+}
+
+#[test]
+fn encoder_round_trips() {
+    let sent: Vec<ControllerChange> = encode_parameter(true, RPN_PITCH_BEND_SENSITIVITY, (2 << 7) | 50, DataChange::Fine, true);
+    assert_eq!(pairs(&sent), vec![(101, 0), (100, 0), (6, 2), (38, 50), (101, 127), (100, 127)]);
+    let mut decoder: ControllerDecoder = ControllerDecoder::new();
+    let events = decode(&mut decoder, &pairs(&sent));
+    assert_eq!(events.last(), Some(&("rpn", 0, (2 << 7) | 50, "fine")));
+    assert!(decoder.selection == ParameterSelection::Registered(RPN_NULL));
+    // NRPN increment without deselecting
+    let sent: Vec<ControllerChange> = encode_parameter(false, 0x1234, 0, DataChange::Increment, false);
+    assert_eq!(pairs(&sent), vec![(99, 0x24), (98, 0x34), (96, 0)]);
+    assert_eq!(decode(&mut decoder, &pairs(&sent)), vec![("nrpn", 0x1234, 1, "increment")]);
+    // 14-bit controllers and plain controllers through ParameterEvent
+    let sent: Vec<ControllerChange> = encode_14_bit(1, 0x1234);
+    assert_eq!(pairs(&sent), vec![(1, 0x24), (33, 0x34)]);
+    assert_eq!(decode(&mut decoder, &pairs(&sent)).last(), Some(&("cc14", 1, 0x1234, "")));
+    let event: ParameterEvent = ParameterEvent::Controller { controller_number: 64, value: 127 };
+    assert_eq!(pairs(&event.to_controllers()), vec![(64, 127)]);
+    let event: ParameterEvent = ParameterEvent::NonRegistered { parameter: 5, value: 300, change: DataChange::Coarse };
+    assert_eq!(pairs(&event.to_controllers()), vec![(99, 0), (98, 5), (6, 2), (101, 127), (100, 127)]);
+}
+
+#[test]
+fn parameter_events_per_channel() {
+    let event = |channel: u8, number: u8, value: u8| {
+        Event::from_midi(MidiEvent::from_type(channel, MidiEventType::ControllerChange(cc(number, value))))
+    };
+    // The selection on channel 0 does not apply to channel 1, and Reset All Controllers clears it
+    let track: SMFTrackChunk = SMFTrackChunk::from_absolute_events(vec![
+        (0, event(0, 101, 0)), (0, event(0, 100, 0)), (10, event(1, 6, 5)), (20, event(0, 6, 3)),
+        (30, event(0, 121, 0)), (40, event(0, 6, 4))
+    ]).unwrap();
+    let events: Vec<(u64, u8, Described)> = track.parameter_events().iter()
+        .map(|&(tick, channel, ref e)| (tick, channel, describe(e)))
+        .collect();
+    assert_eq!(events, vec![(20, 0, ("rpn", 0, 3 << 7, "coarse")), (30, 0, ("cc", 121, 0, ""))]);
+}
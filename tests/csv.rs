@@ -0,0 +1,61 @@
+extern crate smf_lib;
+
+use smf_lib::convert::ParseError;
+use smf_lib::file::SMF;
+
+const CSV: &str = "0, 0, Header, 1, 2, 96
+1, 0, Start_track
+1, 0, Title_t, \"Tempo \"\"map\"\", with a comma\"
+1, 0, Tempo, 500000
+1, 0, Time_signature, 3, 3, 24, 8
+1, 0, Key_signature, -3, \"minor\"
+1, 384, End_track
+2, 0, Start_track
+2, 0, Program_c, 9, 5
+2, 0, Note_on_c, 0, 60, 100
+2, 0, Control_c, 0, 7, 127
+2, 96, Note_off_c, 0, 60, 64
+2, 96, Pitch_bend_c, 15, 16383
+2, 100, Channel_aftertouch_c, 1, 4
+2, 100, System_exclusive, 5, 126, 127, 9, 1, 247
+2, 200, End_track
+0, 0, End_of_file
+";
+
+fn error_of(csv: &str) -> (usize, String) {
+    let error = SMF::from_csv(csv).err().unwrap();
+    let error: &ParseError = error.downcast_ref::<ParseError>().unwrap();
+    (error.line, error.message.clone())
+}
+
+#[test]
+fn csv_round_trip() {
+    let smf: SMF = SMF::from_csv(CSV).unwrap();
+    assert_eq!(smf.tracks.len(), 2);
+    assert_eq!(smf.to_csv().unwrap(), CSV);
+    // Both give the same file
+    let (mut from_csv, mut original): (Vec<u8>, Vec<u8>) = (Vec::new(), Vec::new());
+    SMF::from_csv(&smf.to_csv().unwrap()).unwrap().write(&mut from_csv).unwrap();
+    smf.write(&mut original).unwrap();
+    assert_eq!(from_csv, original);
+}
+
+#[test]
+fn csv_rejects_out_of_range_values() {
+    let track = |record: &str| format!("0, 0, Header, 0, 1, 96\n1, 0, Start_track\n1, 0, {}\n1, 10, End_track\n0, 0, End_of_file\n", record);
+    for record in &["Note_on_c, 0, 128, 1", "Note_off_c, 0, 60, 128", "Poly_aftertouch_c, 0, 60, 200",
+                    "Control_c, 0, 128, 0", "Control_c, 0, 7, 128", "Program_c, 0, 128", "Channel_aftertouch_c, 0, 255"] {
+        let (line, message) = error_of(&track(record));
+        assert_eq!(line, 3);
+        assert!(message.ends_with("is not between 0 and 127"), "{}", message);
+    }
+    assert_eq!(error_of(&track("Pitch_bend_c, 0, 16384")), (3, String::from("16384 is not between 0 and 16383")));
+    assert_eq!(error_of(&track("Note_on_c, 16, 1, 1")), (3, String::from("Channels go from 0 to 15")));
+}
+
+#[test]
+fn csv_rejects_tracks_beyond_the_header() {
+    let csv: &str = "0, 0, Header, 1, 2, 96\n1, 0, Start_track\n1, 0, End_track\n65535, 0, Start_track\n0, 0, End_of_file\n";
+    assert_eq!(error_of(csv), (4, String::from("Track number above the number of tracks of the Header")));
+    assert_eq!(error_of("1, 0, Start_track\n0, 0, Header, 0, 1, 96\n"), (1, String::from("Start_track before the Header")));
+}
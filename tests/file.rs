@@ -0,0 +1,142 @@
+extern crate smf_lib;
+
+use smf_lib::file::SMF;
+use smf_lib::file::header::data::MidiDivisionsType;
+use smf_lib::file::track::data::event::EventType;
+use smf_lib::file::track::data::event::MetaEventType;
+use smf_lib::file::track::data::event::MidiEventType;
+use std::io::Cursor;
+
+// A format 0 file at 480 ticks per quarter note holding this track data
+fn single_track(mut track: Vec<u8>) -> Vec<u8> {
+    let mut file: Vec<u8> = b"MThd\x00\x00\x00\x06\x00\x00\x00\x01\x01\xE0MTrk".to_vec();
+    let length: u32 = track.len() as u32;
+    file.extend_from_slice(&[(length >> 24) as u8, (length >> 16) as u8, (length >> 8) as u8, length as u8]);
+    file.append(&mut track);
+    file
+}
+
+// One track using Running Status, a Pitch Bend, a 24-bit tempo and multi-byte delta times
+fn sample() -> Vec<u8> {
+    single_track(vec![
+        0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20,  // Set Tempo 500 000
+        0x00, 0x91, 60, 100,                      // Note On
+        0x81, 0x70, 61, 90,                       // Running Status after 240 ticks
+        0x83, 0x60, 0x81, 60, 0,                  // Note Off after 480 ticks
+        0x00, 0xE1, 0x00, 0x40,                   // Pitch Bend center
+        0x01, 0xE1, 0x7F, 0x7F,                   // Pitch Bend max
+        0x00, 0xFF, 0x2F, 0x00
+    ])
+}
+
+#[test]
+fn read_known_values() {
+    let smf: SMF = SMF::read(&mut Cursor::new(sample())).unwrap();
+    let track = &smf.tracks[0];
+    assert_eq!(track.absolute_ticks(), vec![0, 0, 240, 720, 720, 721, 721]);
+    match track.track_events[0].event.event {
+        EventType::MetaEvent(ref m) => match m.event {
+            MetaEventType::SetTempo(ref t) => assert_eq!(t.tempo, 500_000),
+            _ => panic!("expected Set Tempo")
+        },
+        _ => panic!("expected a Meta Event")
+    }
+    match track.track_events[2].event.event {
+        EventType::MidiEvent(ref m) => match m.event {
+            MidiEventType::NoteOn(ref n) => assert_eq!((n.key, n.velocity), (61, 90)),
+            _ => panic!("expected a Note On")
+        },
+        _ => panic!("expected a Midi Event")
+    }
+    let bends: Vec<u16> = track.track_events.iter().filter_map(|e| match e.event.event {
+        EventType::MidiEvent(ref m) => match m.event {
+            MidiEventType::PitchBend(ref p) => Some(p.value),
+            _ => None
+        },
+        _ => None
+    }).collect();
+    assert_eq!(bends, vec![0x2000, 0x3FFF]);
+}
+
+#[test]
+fn read_channel_of_midi_events() {
+    let smf: SMF = SMF::read(&mut Cursor::new(sample())).unwrap();
+    // Every Midi Event of the sample is on channel 1, including the one using Running Status
+    let channels: Vec<u8> = smf.tracks[0].track_events.iter().filter_map(|e| match e.event.event {
+        EventType::MidiEvent(ref m) => Some(m.channel),
+        _ => None
+    }).collect();
+    assert_eq!(channels, vec![1, 1, 1, 1, 1]);
+}
+
+#[test]
+fn read_polyphonic_key_pressure() {
+    let bytes: Vec<u8> = single_track(vec![
+        0x00, 0xA2, 60, 33,   // Polyphonic Key Pressure
+        0x00, 61, 34,         // Running Status
+        0x00, 0xFF, 0x2F, 0x00
+    ]);
+    let smf: SMF = SMF::read(&mut Cursor::new(bytes.clone())).unwrap();
+    let pressures: Vec<(u8, u8, u8)> = smf.tracks[0].track_events.iter().filter_map(|e| match e.event.event {
+        EventType::MidiEvent(ref m) => match m.event {
+            MidiEventType::PolyphonicKeyPressure(ref p) => Some((m.channel, p.key, p.pressure)),
+            _ => panic!("expected a Polyphonic Key Pressure")
+        },
+        _ => None
+    }).collect();
+    assert_eq!(pressures, vec![(2, 60, 33), (2, 61, 34)]);
+    // Written back without Running Status
+    let mut written: Vec<u8> = Vec::new();
+    smf.write(&mut written).unwrap();
+    assert_eq!(&written[22..30], &[0x00, 0xA2, 60, 33, 0x00, 0xA2, 61, 34]);
+}
+
+#[test]
+fn smpte_division_round_trip() {
+    // 25 frames per second (-25 in the upper byte) and 40 ticks per frame
+    let mut bytes: Vec<u8> = single_track(vec![0x00, 0xFF, 0x2F, 0x00]);
+    bytes[12] = 0xE7;
+    bytes[13] = 0x28;
+    let smf: SMF = SMF::read(&mut Cursor::new(bytes.clone())).unwrap();
+    match smf.header.division_system {
+        MidiDivisionsType::SMTPEFrames(ref d) => assert_eq!((d.smtpe_frames_per_second, d.ticks_per_smtpe_frame), (25, 40)),
+        _ => panic!("expected SMTPE divisions")
+    }
+    let mut written: Vec<u8> = Vec::new();
+    smf.write(&mut written).unwrap();
+    assert_eq!(written, bytes);
+}
+
+#[test]
+fn write_read_round_trip() {
+    let smf: SMF = SMF::read(&mut Cursor::new(sample())).unwrap();
+    let mut written: Vec<u8> = Vec::new();
+    smf.write(&mut written).unwrap();
+    // Running Status is not written back, the events are the same
+    assert!(written.len() > sample().len());
+    let reread: SMF = SMF::read(&mut Cursor::new(written.clone())).unwrap();
+    assert_eq!(reread.header.nb_tracks, 1);
+    assert_eq!(reread.tracks[0].absolute_ticks(), smf.tracks[0].absolute_ticks());
+    let mut rewritten: Vec<u8> = Vec::new();
+    reread.write(&mut rewritten).unwrap();
+    assert_eq!(rewritten, written);
+}
+
+#[test]
+fn convert_resolution_report() {
+    let mut smf: SMF = SMF::read(&mut Cursor::new(sample())).unwrap();
+    let report = smf.convert_resolution(96).unwrap();
+    assert_eq!((report.old_ticks_per_quarter_note, report.new_ticks_per_quarter_note), (480, 96));
+    match smf.header.division_system {
+        MidiDivisionsType::TicksPerQuarterNote(ref d) => assert_eq!(d.ticks_per_quarter_note, 96),
+        _ => panic!("expected Ticks per Quarter Note divisions")
+    }
+    assert_eq!(smf.tracks[0].absolute_ticks(), vec![0, 0, 48, 144, 144, 144, 144]);
+    // Only the events at 721 ticks do not fall on a multiple of 5
+    let moved: Vec<(usize, u64, u64)> = report.moved.iter().map(|m| (m.index, m.old_tick, m.new_tick)).collect();
+    assert_eq!(moved, vec![(5, 721, 144), (6, 721, 144)]);
+    assert!(report.moved.iter().all(|m| (m.error + 0.2).abs() < 1e-9));
+    // The second bend had its own tick and now shares the one of the Note Off
+    assert_eq!(report.collapsed.len(), 1);
+    assert_eq!(report.collapsed[0].index, 5);
+}
@@ -0,0 +1,78 @@
+extern crate smf_lib;
+
+use smf_lib::file::track::data::event::midi::ControllerChange;
+use smf_lib::file::track::data::event::midi::NoteChange;
+use smf_lib::file::track::data::event::midi::ProgramChange;
+use smf_lib::gm;
+use smf_lib::gm::XGBankType;
+
+#[test]
+fn gm_programs_and_families() {
+    assert_eq!(gm::program_name(0), "Acoustic Grand Piano");
+    assert_eq!(gm::program_name(127), "Gunshot");
+    // Only the 7 low bits make the program number
+    assert_eq!(gm::program_name(128 + 40), "Violin");
+    assert_eq!(gm::program_family(7), "Piano");
+    assert_eq!(gm::program_family(56), "Brass");
+    assert_eq!(gm::program_family(127), "Sound Effects");
+    assert_eq!(ProgramChange { new_program_number: 73 }.gm_name(), "Flute");
+    assert_eq!(ProgramChange { new_program_number: 73 }.gm_family(), "Pipe");
+    assert_eq!(gm::program_by_name("  violin "), Some(40));
+    assert_eq!(gm::program_by_name("Kazoo"), None);
+    assert_eq!(gm::family_by_name("synth pad"), Some(88));
+}
+
+#[test]
+fn gm_percussion_and_controllers() {
+    assert_eq!(gm::percussion_name(26), None);
+    assert_eq!(gm::percussion_name(27), Some("High Q"));
+    assert_eq!(gm::percussion_name(35), Some("Acoustic Bass Drum"));
+    assert_eq!(gm::percussion_name(87), Some("Open Surdo"));
+    assert_eq!(gm::percussion_name(88), None);
+    assert_eq!(NoteChange { key: 42, velocity: 100 }.percussion_name(), Some("Closed Hi Hat"));
+    assert_eq!(gm::percussion_key_by_name("acoustic snare"), Some(38));
+    assert_eq!(gm::controller_name(7), Some("Channel Volume"));
+    assert_eq!(gm::controller_name(3), None);
+    assert_eq!(ControllerChange { controller_number: 64, controller_value: 127 }.name(), Some("Sustain Pedal"));
+    assert_eq!(gm::controller_by_name("Reset All Controllers"), Some(121));
+    assert_eq!(gm::controller_by_name("Undefined"), None);
+}
+
+#[test]
+fn gm2_variations() {
+    assert_eq!(gm::gm2_program_name(0, 2), "Acoustic Grand Piano (dark)");
+    assert_eq!(gm::gm2_program_name(127, 3), "Explosion");
+    // LSB 0 and unknown variations give the GM1 sound
+    assert_eq!(gm::gm2_program_name(0, 0), "Acoustic Grand Piano");
+    assert_eq!(gm::gm2_program_name(0, 9), "Acoustic Grand Piano");
+    assert_eq!(gm::gm2_program_by_name("Jump Brass"), Some((62, 3)));
+    assert_eq!(gm::gm2_program_by_name("Trumpet"), Some((56, 0)));
+    assert_eq!(gm::gm2_program_by_name("Kazoo"), None);
+}
+
+#[test]
+fn gs_variations_and_drum_kits() {
+    assert_eq!(gm::gs_program_name(25, 16), "Mandolin");
+    assert_eq!(gm::gs_program_name(122, 3), "Wind");
+    // Unknown variations fall back to the capital tone
+    assert_eq!(gm::gs_program_name(25, 24), "Acoustic Guitar (steel)");
+    assert_eq!(gm::gs_program_by_name("12-str.Gt"), Some((25, 8)));
+    assert_eq!(gm::gs_program_by_name("Oboe"), Some((68, 0)));
+    assert_eq!(gm::gs_drum_kit_name(25), Some("TR-808"));
+    assert_eq!(gm::gs_drum_kit_name(1), None);
+}
+
+#[test]
+fn xg_variations_and_banks() {
+    assert_eq!(gm::xg_program_name(0, 1), "GrndPnoK");
+    assert_eq!(gm::xg_program_name(16, 40), "16+2\"2/3");
+    assert_eq!(gm::xg_program_name(98, 67), "XmasBell");
+    // Unknown variations fall back to the GM voice
+    assert_eq!(gm::xg_program_name(0, 2), "Acoustic Grand Piano");
+    assert_eq!(gm::xg_program_by_name("mandolin"), Some((25, 96)));
+    assert_eq!(gm::xg_program_by_name("Tuba"), Some((58, 0)));
+    assert!(gm::xg_bank_type(0) == XGBankType::Normal);
+    assert!(gm::xg_bank_type(64) == XGBankType::SFXVoice);
+    assert!(gm::xg_bank_type(127) == XGBankType::DrumKit);
+    assert!(gm::xg_bank_type(8) == XGBankType::Unknown);
+}
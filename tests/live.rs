@@ -0,0 +1,230 @@
+extern crate smf_lib;
+
+mod common;
+
+use smf_lib::file::SMF;
+use smf_lib::file::track::data::event::Event;
+use smf_lib::file::track::data::event::EventType;
+use smf_lib::file::track::data::event::MidiEvent;
+use smf_lib::file::track::data::event::MidiEventType;
+use smf_lib::file::track::data::event::SysexEvent;
+use smf_lib::file::track::data::event::SysexEventType;
+use smf_lib::file::track::data::event::midi::NoteChange;
+use smf_lib::file::track::data::event::midi::ProgramChange;
+use smf_lib::file::track::data::event::sysex::Sysex;
+use smf_lib::live::LiveMessage;
+use smf_lib::live::MetaEventNotLiveError;
+use smf_lib::live::NotASingleMessageError;
+use smf_lib::live::encoder::LiveEncoder;
+use smf_lib::live::parser::LiveParser;
+use smf_lib::live::player::ManualClock;
+use smf_lib::live::player::Player;
+
+fn note_on(channel: u8, key: u8, velocity: u8) -> LiveMessage {
+    LiveMessage::Channel(MidiEvent::from_type(channel, MidiEventType::NoteOn(NoteChange { key, velocity })))
+}
+
+fn to_bytes(messages: &[LiveMessage]) -> Vec<u8> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for message in messages {
+        bytes.extend(message.to_bytes().unwrap());
+    }
+    bytes
+}
+
+// Short text for a message, channel messages only show their status byte and their first data byte
+fn describe(message: &LiveMessage) -> String {
+    match *message {
+        LiveMessage::Channel(ref e) => {
+            let data: u8 = match e.event {
+                MidiEventType::NoteOn(ref n) => n.key,
+                MidiEventType::ControllerChange(ref c) => c.controller_number,
+                MidiEventType::ProgramChange(ref p) => p.new_program_number,
+                MidiEventType::PitchBend(ref b) => (b.value >> 7) as u8,
+                _ => panic!("unexpected Channel message")
+            };
+            format!("{:02X} {}", e.event.code_byte() | e.channel, data)
+        },
+        LiveMessage::SysEx(ref data) => format!("sysex {:?}", data),
+        LiveMessage::MTCQuarterFrame(value) => format!("mtc {:02X}", value),
+        LiveMessage::SongPositionPointer(position) => format!("spp {}", position),
+        LiveMessage::Start => "start".to_string(),
+        _ => panic!("unexpected message")
+    }
+}
+
+#[test]
+fn parse_running_status_and_real_time() {
+    let mut parser: LiveParser = LiveParser::new();
+    // Note On, a Timing Clock in the middle, then a second Note On using Running Status
+    let messages: Vec<LiveMessage> = parser.feed_bytes(&[0x91, 60, 0xF8, 100, 62, 90]);
+    assert_eq!(messages.len(), 3);
+    match messages[0] {
+        LiveMessage::TimingClock => {},
+        _ => panic!("expected the Timing Clock first")
+    }
+    for (message, key) in messages[1..].iter().zip(&[60u8, 62]) {
+        match *message {
+            LiveMessage::Channel(ref e) => match e.event {
+                MidiEventType::NoteOn(ref n) => assert_eq!((e.channel, n.key), (1, *key)),
+                _ => panic!("expected a Note On")
+            },
+            _ => panic!("expected a Channel message")
+        }
+    }
+    // A data byte without a status is dropped
+    assert!(parser.feed_bytes(&[0xF6, 5]).len() == 1);
+    assert_eq!(parser.dropped_bytes, 1);
+}
+
+#[test]
+fn parse_every_kind_of_message() {
+    let bytes: Vec<u8> = vec![
+        0x90, 60, 100,
+        0xB3, 7, 90,
+        0xC2, 5,
+        0xE0, 0x00, 0x40,
+        0xF0, 0x7E, 0x7F, 0x09, 0x01, 0xF7,
+        0xF1, 0x25,
+        0xF2, 0x10, 0x01,
+        0xFA
+    ];
+    let messages: Vec<String> = LiveParser::new().feed_bytes(&bytes).iter().map(describe).collect();
+    assert_eq!(messages, vec![
+        "90 60", "B3 7", "C2 5", "E0 64", "sysex [126, 127, 9, 1]", "mtc 25", "spp 144", "start"
+    ]);
+}
+
+#[test]
+fn bytes_round_trip() {
+    let bytes: Vec<u8> = vec![
+        0x90, 60, 100, 0xB3, 7, 90, 0xC2, 5, 0xE0, 0x00, 0x40,
+        0xF0, 0x7E, 0x7F, 0x09, 0x01, 0xF7, 0xF1, 0x25, 0xF2, 0x10, 0x01, 0xF3, 4, 0xF6, 0xFA, 0xFD
+    ];
+    let messages: Vec<LiveMessage> = LiveParser::new().feed_bytes(&bytes);
+    assert_eq!(messages.len(), 11);
+    assert_eq!(to_bytes(&messages), bytes);
+}
+
+#[test]
+fn encode_with_running_status() {
+    let mut encoder: LiveEncoder = LiveEncoder::new(true);
+    // Real Time messages keep the running status, another channel needs a new status
+    let bytes: Vec<u8> = encoder.encode(&[
+        note_on(0, 60, 100), note_on(0, 62, 90), LiveMessage::TimingClock, note_on(0, 64, 80), note_on(1, 60, 100)
+    ]).unwrap();
+    assert_eq!(bytes, vec![0x90, 60, 100, 62, 90, 0xF8, 64, 80, 0x91, 60, 100]);
+    assert_eq!(LiveParser::new().feed_bytes(&bytes).len(), 5);
+    // Carried over between calls until reset
+    assert_eq!(encoder.encode(&[note_on(1, 62, 0)]).unwrap(), vec![62, 0]);
+    encoder.reset();
+    assert_eq!(encoder.encode(&[note_on(1, 62, 0)]).unwrap(), vec![0x91, 62, 0]);
+    let mut encoder: LiveEncoder = LiveEncoder::new(false);
+    assert_eq!(encoder.encode(&[note_on(0, 60, 100), note_on(0, 62, 90)]).unwrap(), vec![0x90, 60, 100, 0x90, 62, 90]);
+}
+
+#[test]
+fn system_common_cancels_running_status() {
+    let mut encoder: LiveEncoder = LiveEncoder::new(true);
+    let bytes: Vec<u8> = encoder.encode(&[
+        note_on(0, 60, 100), LiveMessage::MTCQuarterFrame(0x25), note_on(0, 62, 90),
+        LiveMessage::SysEx(vec![0x7D]), note_on(0, 64, 80), LiveMessage::TuneRequest, note_on(0, 65, 70)
+    ]).unwrap();
+    assert_eq!(bytes, vec![0x90, 60, 100, 0xF1, 0x25, 0x90, 62, 90, 0xF0, 0x7D, 0xF7, 0x90, 64, 80, 0xF6, 0x90, 65, 70]);
+}
+
+// Kind of SMF event and its raw data, the data of a Midi Event is written without its status
+fn event_bytes(event: &Event) -> (&'static str, Vec<u8>) {
+    match event.event {
+        EventType::MidiEvent(ref e) => {
+            let mut data: Vec<u8> = Vec::new();
+            e.write(&mut data).unwrap();
+            ("midi", data)
+        },
+        EventType::SysExEvent(SysexEvent { event: SysexEventType::F0SysexEvent(ref s) }) => ("f0", s.data.clone()),
+        EventType::SysExEvent(SysexEvent { event: SysexEventType::F7SysexEvent(ref s) }) => ("f7", s.data.clone()),
+        EventType::MetaEvent(_) => ("meta", Vec::new())
+    }
+}
+
+#[test]
+fn events_round_trip() {
+    let cases: Vec<(LiveMessage, (&str, Vec<u8>))> = vec![
+        (note_on(3, 60, 100), ("midi", vec![60, 100])),
+        (LiveMessage::SysEx(vec![0x7E, 0x7F, 0x09, 0x01]), ("f0", vec![0x7E, 0x7F, 0x09, 0x01, 0xF7])),
+        (LiveMessage::MTCQuarterFrame(0x25), ("f7", vec![0xF1, 0x25])),
+        (LiveMessage::SongPositionPointer(0x90), ("f7", vec![0xF2, 0x10, 0x01])),
+        (LiveMessage::Start, ("f7", vec![0xFA]))
+    ];
+    for (message, expected) in cases {
+        let event: Event = message.to_event().unwrap();
+        assert_eq!(event_bytes(&event), expected);
+        let back: LiveMessage = LiveMessage::from_event(&event).unwrap();
+        assert_eq!(back.to_bytes().unwrap(), message.to_bytes().unwrap());
+    }
+}
+
+#[test]
+fn events_that_are_not_one_message() {
+    let error = LiveMessage::from_event(&Event::end_of_track()).err().unwrap();
+    assert!(error.downcast_ref::<MetaEventNotLiveError>().is_some());
+    let sysex = |event: SysexEventType| Event::from_sysex(SysexEvent { event });
+    let packets: Vec<Event> = vec![
+        // First packet of a split Sysex, without F7
+        sysex(SysexEventType::F0SysexEvent(Sysex { length: 2, data: vec![0x7E, 0x7F] })),
+        // Two messages in one escape
+        sysex(SysexEventType::F7SysexEvent(Sysex { length: 2, data: vec![0xFA, 0xF8] })),
+        // Stray data byte
+        sysex(SysexEventType::F7SysexEvent(Sysex { length: 1, data: vec![0x25] }))
+    ];
+    for event in &packets {
+        let error = LiveMessage::from_event(event).err().unwrap();
+        assert!(error.downcast_ref::<NotASingleMessageError>().is_some());
+    }
+}
+
+// A Program Change then an eighth note every quarter note for 100 quarter notes, at 96 ticks per quarter note
+fn steady_notes() -> SMF {
+    let mut events: Vec<(u64, Event)> = vec![
+        (0, Event::from_midi(MidiEvent::from_type(0, MidiEventType::ProgramChange(ProgramChange { new_program_number: 5 }))))
+    ];
+    for beat in 0..100u64 {
+        events.push((beat * 96, Event::from_midi(MidiEvent::from_type(0, MidiEventType::NoteOn(NoteChange { key: 60, velocity: 100 })))));
+        events.push((beat * 96 + 48, Event::from_midi(MidiEvent::from_type(0, MidiEventType::NoteOff(NoteChange { key: 60, velocity: 0 })))));
+    }
+    common::smf(96, vec![events])
+}
+
+#[test]
+fn loop_set_after_its_end() {
+    let mut player = Player::new(steady_notes(), ManualClock::new(), Vec::new());
+    player.seek(9000).unwrap();
+    player.sink.clear();
+    player.set_loop(Some((0, 96)));
+    player.play();
+    player.clock.advance(1);
+    let sent: usize = player.update().unwrap();
+    // 8904 ticks past the loop end is 92 loops and 72 ticks, the loop is played once from its start up to there
+    assert_eq!(player.position_tick(), 72);
+    assert_eq!(sent, player.sink.len());
+    assert_eq!(sent, 3);
+    match player.sink[0] {
+        LiveMessage::Channel(MidiEvent { event: MidiEventType::ProgramChange(ref p), .. }) => assert_eq!(p.new_program_number, 5),
+        _ => panic!("expected the Program Change")
+    }
+}
+
+#[test]
+fn loop_back_chases_state() {
+    let mut player = Player::new(steady_notes(), ManualClock::new(), Vec::new());
+    player.set_loop(Some((24, 96)));
+    player.play();
+    player.clock.advance(500_000);
+    player.update().unwrap();
+    // The Program Change at tick 0 is before the loop, it is sent again when looping back
+    let programs: usize = player.sink.iter()
+        .filter(|m| matches!(**m, LiveMessage::Channel(MidiEvent { event: MidiEventType::ProgramChange(_), .. })))
+        .count();
+    assert_eq!(programs, 2);
+    assert_eq!(player.position_tick(), 24);
+}
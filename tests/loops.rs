@@ -0,0 +1,60 @@
+extern crate smf_lib;
+
+mod common;
+
+use smf_lib::file::SMF;
+use smf_lib::file::loops::LoopConvention;
+use smf_lib::file::loops::LoopPoints;
+use smf_lib::file::track::data::event::Event;
+use smf_lib::file::track::data::event::EventType;
+use smf_lib::file::track::data::event::MetaEvent;
+use smf_lib::file::track::data::event::MetaEventType;
+use smf_lib::file::track::data::event::MidiEvent;
+use smf_lib::file::track::data::event::MidiEventType;
+use smf_lib::file::track::data::event::meta::Text;
+use smf_lib::file::track::data::event::midi::ControllerChange;
+use smf_lib::file::track::data::event::midi::NoteChange;
+
+fn midi(event: MidiEventType) -> Event {
+    Event::from_midi(MidiEvent::from_type(0, event))
+}
+
+fn marker(text: &str) -> Event {
+    Event::from_meta(MetaEvent::from_type(MetaEventType::Marker(Text { text: String::from(text) })).unwrap())
+}
+
+// A note before the loop and one in it, the loop is marked with both EMIDI controllers and markers
+fn looping_file() -> SMF {
+    let events: Vec<(u64, Event)> = vec![
+        (0, midi(MidiEventType::NoteOn(NoteChange { key: 60, velocity: 100 }))),
+        (48, midi(MidiEventType::NoteOff(NoteChange { key: 60, velocity: 0 }))),
+        (96, midi(MidiEventType::ControllerChange(ControllerChange { controller_number: 116, controller_value: 3 }))),
+        (96, marker("loopStart")),
+        (96, midi(MidiEventType::NoteOn(NoteChange { key: 62, velocity: 100 }))),
+        (144, midi(MidiEventType::NoteOff(NoteChange { key: 62, velocity: 0 }))),
+        (192, midi(MidiEventType::ControllerChange(ControllerChange { controller_number: 117, controller_value: 0 }))),
+        (192, marker("loopEnd"))
+    ];
+    common::smf(96, vec![events])
+}
+
+#[test]
+fn detect_emidi_count() {
+    let points: LoopPoints = looping_file().detect_loop().unwrap();
+    assert!(points.convention == LoopConvention::EMIDI);
+    assert_eq!((points.start, points.end, points.count), (96, 192, Some(3)));
+}
+
+#[test]
+fn render_loop_strips_loop_points() {
+    let smf: SMF = looping_file();
+    let points: LoopPoints = smf.detect_loop().unwrap();
+    let rendered: SMF = smf.render_loop(&points, 2).unwrap();
+    assert!(rendered.detect_loops().is_empty());
+    let notes: Vec<u8> = rendered.tracks[0].track_events.iter().filter_map(|e| match e.event.event {
+        EventType::MidiEvent(MidiEvent { event: MidiEventType::NoteOn(ref n), .. }) => Some(n.key),
+        _ => None
+    }).collect();
+    assert_eq!(notes, vec![60, 62, 62]);
+    assert_eq!(rendered.end_tick(), 288);
+}
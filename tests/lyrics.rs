@@ -0,0 +1,74 @@
+extern crate smf_lib;
+
+mod common;
+
+use smf_lib::file::SMF;
+use smf_lib::file::track::data::event::Event;
+use smf_lib::file::track::data::event::MetaEvent;
+use smf_lib::file::track::data::event::MetaEventType;
+use smf_lib::file::track::data::event::meta::Text;
+
+fn text(text: &str) -> Event {
+    Event::from_meta(MetaEvent::from_type(MetaEventType::TextEvent(Text { text: String::from(text) })).unwrap())
+}
+
+fn lyric(text: &str) -> Event {
+    Event::from_meta(MetaEvent::from_type(MetaEventType::Lyric(Text { text: String::from(text) })).unwrap())
+}
+
+fn track_name(name: &str) -> Event {
+    Event::from_meta(MetaEvent::from_type(MetaEventType::SequenceTrackName(Text { text: String::from(name) })).unwrap())
+}
+
+fn line_texts(smf: &SMF) -> Vec<String> {
+    smf.lyrics().lines.iter().map(|l| l.text()).collect()
+}
+
+#[test]
+fn bare_text_events_are_syllables() {
+    let smf: SMF = common::smf(480, vec![vec![(480, text("\\Hel")), (720, text("lo")), (960, text("/World"))]]);
+    let lines = smf.lyrics().lines;
+    assert_eq!(line_texts(&smf), vec!["Hello", "World"]);
+    assert!(lines[0].new_paragraph);
+    assert!(!lines[1].new_paragraph);
+    // 960 ticks at 480 ticks per quarter note and 120 BPM
+    assert!((lines[1].seconds - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn karaoke_headers_are_not_syllables() {
+    let smf: SMF = common::smf(480, vec![vec![
+        (0, text("@KMIDI KARAOKE FILE")), (0, text("@LENGL")), (0, text("@TSong")), (0, text("@TSinger")),
+        (480, text("Hel")), (720, text("lo"))
+    ]]);
+    let lyrics = smf.lyrics();
+    assert_eq!(lyrics.info.language, Some("ENGL".to_string()));
+    assert_eq!(lyrics.info.titles, vec!["Song", "Singer"]);
+    assert_eq!(line_texts(&smf), vec!["Hello"]);
+    assert_eq!(lyrics.to_lrc(), "[ti:Song]\n[ar:Singer]\n[la:ENGL]\n[00:00.50]Hello\n");
+}
+
+#[test]
+fn lyric_and_text_events_are_merged() {
+    // The same words in both forms are sung once, words only found in one form are kept
+    let smf: SMF = common::smf(480, vec![vec![
+        (480, text("Hel")), (480, lyric("Hel")),
+        (720, text("lo")), (720, lyric("lo")),
+        (960, lyric(" there")),
+        (1200, text("/Bye"))
+    ]]);
+    let lines = smf.lyrics().lines;
+    assert_eq!(line_texts(&smf), vec!["Hello there", "Bye"]);
+    assert_eq!(lines[0].syllables.len(), 3);
+    assert_eq!(smf.lyrics().to_enhanced_lrc(), "[00:00.50]<00:00.50>Hel<00:00.75>lo<00:01.00> there\n[00:01.25]<00:01.25>Bye\n");
+}
+
+#[test]
+fn text_events_of_the_words_track() {
+    // Text in other tracks is left out when there is a Words track
+    let smf: SMF = common::smf(480, vec![
+        vec![(0, text("Sequenced by someone"))],
+        vec![(0, track_name("Words")), (480, text("Hel")), (720, text("lo"))]
+    ]);
+    assert_eq!(line_texts(&smf), vec!["Hello"]);
+}
@@ -0,0 +1,62 @@
+extern crate smf_lib;
+
+use smf_lib::file::track::data::event::meta::SMTPEOffset;
+use smf_lib::live::LiveMessage;
+use smf_lib::sync::mtc::FrameRate;
+use smf_lib::sync::mtc::MtcDecoder;
+use smf_lib::sync::mtc::frame_to_timecode;
+use smf_lib::sync::mtc::full_frame;
+use smf_lib::sync::mtc::quarter_frame;
+use smf_lib::sync::mtc::timecode_to_frame;
+
+// 01:02:03:04 at 25 frames per second, the rate is in bits 5 and 6 of the hour
+fn timecode() -> SMTPEOffset {
+    SMTPEOffset { hour: (1 << 5) | 1, minute: 2, seconds: 3, frames: 4, hundred_of_frame: 0 }
+}
+
+#[test]
+fn drop_frame_known_values() {
+    let rate: FrameRate = FrameRate::Fps2997DropFrame;
+    // Frames 0 and 1 are skipped at the start of every minute but every tenth
+    let timecode: SMTPEOffset = frame_to_timecode(1800, rate);
+    assert_eq!((timecode.hours(), timecode.minute, timecode.seconds, timecode.frames), (0, 1, 0, 2));
+    let timecode: SMTPEOffset = frame_to_timecode(17982, rate);
+    assert_eq!((timecode.minute, timecode.seconds, timecode.frames), (10, 0, 0));
+    for frame in &[0u64, 1799, 1800, 17981, 17982, 2_589_407] {
+        assert_eq!(timecode_to_frame(&frame_to_timecode(*frame, rate)), *frame);
+    }
+}
+
+#[test]
+fn quarter_frames_known_values() {
+    let pieces: Vec<u8> = (0..8).map(|piece| match quarter_frame(&timecode(), piece) {
+        LiveMessage::MTCQuarterFrame(data) => data,
+        _ => panic!("expected a Quarter Frame")
+    }).collect();
+    assert_eq!(pieces, vec![0x04, 0x10, 0x23, 0x30, 0x42, 0x50, 0x61, 0x72]);
+}
+
+#[test]
+fn decoder_round_trip() {
+    let mut decoder: MtcDecoder = MtcDecoder::new();
+    let mut decoded: Option<SMTPEOffset> = None;
+    for piece in 0..8 {
+        decoded = decoder.feed(&quarter_frame(&timecode(), piece));
+    }
+    // The cycle took 2 frames to arrive
+    let decoded: SMTPEOffset = decoded.unwrap();
+    assert_eq!((decoded.hours(), decoded.minute, decoded.seconds, decoded.frames), (1, 2, 3, 6));
+    assert!(decoded.frame_rate() == FrameRate::Fps25);
+    let decoded: SMTPEOffset = MtcDecoder::new().feed(&full_frame(&timecode())).unwrap();
+    assert_eq!((decoded.hour, decoded.minute, decoded.seconds, decoded.frames), (timecode().hour, 2, 3, 4));
+}
+
+#[test]
+fn decoder_ignores_other_sysex() {
+    let mut decoder: MtcDecoder = MtcDecoder::new();
+    // Shorter than a Full Frame, or another Universal Real Time message
+    for data in &[vec![], vec![0x7F], vec![0x7F, 0x7F, 0x01], vec![0x7F, 0x7F, 0x06, 0x01, 0, 0, 0, 0]] {
+        assert!(decoder.feed(&LiveMessage::SysEx(data.clone())).is_none());
+    }
+    assert!(decoder.feed(&full_frame(&timecode())).is_some());
+}
@@ -0,0 +1,75 @@
+extern crate smf_lib;
+
+mod common;
+
+use smf_lib::file::SMF;
+use smf_lib::file::track::data::event::Event;
+use smf_lib::file::track::data::event::EventType;
+use smf_lib::file::track::data::event::MidiEvent;
+use smf_lib::file::track::data::event::MidiEventType;
+use smf_lib::file::track::data::event::midi::NoteChange;
+use std::io::Cursor;
+
+fn midi_events(smf: &SMF) -> Vec<(u64, u8, String)> {
+    smf.tracks[0].absolute_events().into_iter().filter_map(|(tick, e)| match e.event {
+        EventType::MidiEvent(ref m) => Some((tick, m.channel, match m.event {
+            MidiEventType::NoteOn(ref n) => format!("on {} {}", n.key, n.velocity),
+            MidiEventType::NoteOff(ref n) => format!("off {}", n.key),
+            MidiEventType::ProgramChange(ref p) => format!("program {}", p.new_program_number),
+            MidiEventType::ControllerChange(ref c) => format!("cc {} {}", c.controller_number, c.controller_value),
+            MidiEventType::PitchBend(ref b) => format!("bend {}", b.value),
+            _ => String::from("other")
+        })),
+        _ => None
+    }).collect()
+}
+
+fn mus(score: &[u8], instruments: &[u16]) -> Vec<u8> {
+    let mut bytes: Vec<u8> = b"MUS\x1A".to_vec();
+    let header: [u16; 6] = [score.len() as u16, 16 + 2 * instruments.len() as u16, 1, 0, instruments.len() as u16, 0];
+    for value in header.iter().chain(instruments.iter()) {
+        bytes.extend_from_slice(&[*value as u8, (*value >> 8) as u8]);
+    }
+    bytes.extend_from_slice(score);
+    bytes
+}
+
+#[test]
+fn read_and_write_mus() {
+    let score: Vec<u8> = vec![
+        0x40, 0, 30,                 // Channel 0 program 30
+        0x9F, 0x80 | 36, 90, 70,     // Percussion plays 36 at volume 90, then 70 ticks
+        0x10, 60,                    // Channel 0 plays 60 at the last volume, 127
+        0x20, 128,                   // Bend center
+        0x80, 60, 0x81, 0x0C,        // Release 60, then 140 ticks
+        0x60
+    ];
+    let smf: SMF = SMF::read_mus(&mut Cursor::new(mus(&score, &[30, 136]))).unwrap();
+    // 140 Hz at 70 ticks per quarter note and the default tempo, percussion moves from 15 to 9
+    let expected: Vec<(u64, u8, String)> = vec![
+        (0, 0, String::from("program 30")), (0, 9, String::from("on 36 90")),
+        (70, 0, String::from("on 60 127")), (70, 0, String::from("bend 8192")), (70, 0, String::from("off 60"))
+    ];
+    assert_eq!(midi_events(&smf), expected);
+    assert_eq!(smf.end_tick(), 210);
+    let mut written: Vec<u8> = Vec::new();
+    smf.write_mus(&mut written).unwrap();
+    assert_eq!(&written[..4], b"MUS\x1A");
+    // Two instruments, program 30 and percussion key 36
+    assert_eq!(&written[12..14], &[2, 0]);
+    assert_eq!(&written[16..20], &[30, 0, 136, 0]);
+    let read: SMF = SMF::read_mus(&mut Cursor::new(written)).unwrap();
+    assert_eq!(midi_events(&read), expected);
+}
+
+#[test]
+fn write_mus_too_long() {
+    let mut events: Vec<(u64, Event)> = Vec::new();
+    for index in 0..20_000u64 {
+        let change: NoteChange = NoteChange { key: 60, velocity: 100 };
+        events.push((index * 2, Event::from_midi(MidiEvent::from_type(0, MidiEventType::NoteOn(change.clone())))));
+        events.push((index * 2 + 1, Event::from_midi(MidiEvent::from_type(0, MidiEventType::NoteOff(change)))));
+    }
+    let smf: SMF = common::smf(2, vec![events]);
+    assert_eq!(smf.write_mus(&mut Vec::new()).unwrap_err().to_string(), "Too long for MUS");
+}
@@ -0,0 +1,94 @@
+extern crate smf_lib;
+
+use smf_lib::file::track::data::event::meta::KeySignature;
+use smf_lib::pitch::Accidental;
+use smf_lib::pitch::DEFAULT_A4;
+use smf_lib::pitch::Pitch;
+use smf_lib::pitch::PitchNaming;
+
+fn key_of(name: &str, middle_c_octave: i8) -> Option<u8> {
+    Pitch::parse(name, middle_c_octave).ok().map(|p| p.key)
+}
+
+// Sharps are positive, flats negative
+fn key_signature(sharps_flats: i8, major_key: bool) -> KeySignature {
+    KeySignature { number_of_sharp_flats: sharps_flats as u8, major_key }
+}
+
+#[test]
+fn parse_names() {
+    assert_eq!(key_of("C4", 4), Some(60));
+    assert_eq!(key_of(" c#4 ", 4), Some(61));
+    assert_eq!(key_of("Db4", 4), Some(61));
+    assert_eq!(key_of("Bb-1", 4), Some(10));
+    assert_eq!(key_of("Fx4", 4), Some(67));
+    assert_eq!(key_of("E♭4", 4), Some(63));
+    // Accidentals may cross the octave
+    assert_eq!(key_of("B#3", 4), Some(60));
+    assert_eq!(key_of("Cb4", 4), Some(59));
+    // Yamaha octave numbers
+    assert_eq!(key_of("C3", 3), Some(60));
+    assert_eq!(key_of("G9", 4), Some(127));
+    assert_eq!("A4".parse::<Pitch>().ok().map(|p| p.key), Some(69));
+    for name in &["", "H4", "C", "C#", "Cy4", "G#9", "Cb-1"] {
+        assert!(Pitch::parse(name, 4).is_err(), "{} should not parse", name);
+    }
+}
+
+#[test]
+fn names() {
+    let flats: PitchNaming = PitchNaming { accidental: Accidental::Flat, ..PitchNaming::new() };
+    assert_eq!(Pitch::new(61).name(&PitchNaming::new()), "C#4");
+    assert_eq!(Pitch::new(61).name(&flats), "Db4");
+    assert_eq!(Pitch::new(0).to_string(), "C-1");
+    assert_eq!(Pitch::new(127).to_string(), "G9");
+    assert_eq!(Pitch::new(60).name(&PitchNaming { middle_c_octave: 3, ..flats }), "C3");
+    for key in 0..128u8 {
+        assert_eq!(key_of(&Pitch::new(key).name(&flats), 4), Some(key));
+    }
+}
+
+#[test]
+fn names_in_sharp_keys() {
+    // C# major spells F as E# and C as B#, with the octave of the letter
+    let c_sharp_major: KeySignature = key_signature(7, true);
+    assert_eq!(Pitch::new(65).name_in_key(&c_sharp_major, 4), "E#4");
+    assert_eq!(Pitch::new(60).name_in_key(&c_sharp_major, 4), "B#3");
+    let d_major: KeySignature = key_signature(2, true);
+    assert_eq!(Pitch::new(66).name_in_key(&d_major, 4), "F#4");
+    assert_eq!(Pitch::new(62).name_in_key(&d_major, 4), "D4");
+    // Outside of the scale, sharps
+    assert_eq!(Pitch::new(63).name_in_key(&d_major, 4), "D#4");
+}
+
+#[test]
+fn names_in_flat_keys() {
+    let g_flat_major: KeySignature = key_signature(-6, true);
+    assert_eq!(Pitch::new(59).name_in_key(&g_flat_major, 4), "Cb4");
+    assert_eq!(Pitch::new(66).name_in_key(&g_flat_major, 4), "Gb4");
+    let d_minor: KeySignature = key_signature(-1, false);
+    assert_eq!(Pitch::new(70).name_in_key(&d_minor, 4), "Bb4");
+    // Outside of the scale, flats
+    assert_eq!(Pitch::new(61).name_in_key(&d_minor, 4), "Db4");
+    assert_eq!(d_minor.tonic(), 2);
+    assert_eq!(key_signature(-3, false).tonic(), 0);
+    assert_eq!(key_signature(2, true).tonic(), 2);
+}
+
+#[test]
+fn frequencies() {
+    assert!((Pitch::new(69).frequency(DEFAULT_A4) - 440.0).abs() < 1e-9);
+    assert!((Pitch::new(60).frequency(DEFAULT_A4) - 261.625_565).abs() < 1e-6);
+    assert!((Pitch::new(81).frequency(DEFAULT_A4) - 880.0).abs() < 1e-9);
+    assert!((Pitch::new(69).frequency(442.0) - 442.0).abs() < 1e-9);
+    // A bend of 100 cents reaches the next key
+    assert!((Pitch::new(69).frequency_with_cents(DEFAULT_A4, 100.0) - Pitch::new(70).frequency(DEFAULT_A4)).abs() < 1e-9);
+    let (pitch, cents) = Pitch::from_frequency(261.63, DEFAULT_A4).unwrap();
+    assert_eq!(pitch.key, 60);
+    assert!(cents.abs() < 0.1);
+    let (pitch, cents) = Pitch::from_frequency(450.0, DEFAULT_A4).unwrap();
+    assert_eq!(pitch.key, 69);
+    assert!((cents - 38.9).abs() < 0.1);
+    assert!(Pitch::from_frequency(0.0, DEFAULT_A4).is_none());
+    assert!(Pitch::from_frequency(20_000.0, DEFAULT_A4).is_none());
+}
@@ -0,0 +1,52 @@
+extern crate smf_lib;
+
+use smf_lib::file::track::data::event::MidiEventType;
+use smf_lib::file::track::data::event::midi::ControllerChange;
+use smf_lib::file::track::data::event::midi::DEFAULT_PITCH_BEND_RANGE;
+use smf_lib::file::track::data::event::midi::PitchBend;
+use smf_lib::state::ChannelState;
+
+fn value(bend: PitchBend) -> u16 {
+    bend.value
+}
+
+#[test]
+fn offsets_and_normalized_boundaries() {
+    assert_eq!((PitchBend { value: 0 }.offset(), PitchBend { value: 0 }.normalized()), (-8192, -1.0));
+    assert_eq!((PitchBend::center().offset(), PitchBend::center().normalized()), (0, 0.0));
+    assert_eq!(PitchBend { value: 0x3FFF }.offset(), 8191);
+    assert_eq!(PitchBend { value: 0x3FFF }.normalized(), 8191.0 / 8192.0);
+    // Out of range offsets are clamped
+    assert_eq!(value(PitchBend::from_offset(-10_000)), 0);
+    assert_eq!(value(PitchBend::from_offset(10_000)), 0x3FFF);
+    assert_eq!(value(PitchBend::from_normalized(-1.0)), 0);
+    assert_eq!(value(PitchBend::from_normalized(1.0)), 0x3FFF);
+    assert_eq!(value(PitchBend::from_normalized(0.5)), 0x3000);
+}
+
+#[test]
+fn cents() {
+    assert_eq!(value(PitchBend::from_cents(100.0, DEFAULT_PITCH_BEND_RANGE)), 0x3000);
+    assert_eq!(value(PitchBend::from_cents(-200.0, DEFAULT_PITCH_BEND_RANGE)), 0);
+    assert_eq!(value(PitchBend::from_cents(-600.0, 1200.0)), 0x1000);
+    // Beyond the range, or without a range
+    assert_eq!(value(PitchBend::from_cents(300.0, DEFAULT_PITCH_BEND_RANGE)), 0x3FFF);
+    assert_eq!(value(PitchBend::from_cents(50.0, 0.0)), 0x2000);
+    assert_eq!(PitchBend { value: 0x3000 }.cents(DEFAULT_PITCH_BEND_RANGE), 100.0);
+    assert_eq!(PitchBend { value: 0 }.cents(1200.0), -1200.0);
+}
+
+#[test]
+fn channel_bend_in_cents() {
+    let mut channel: ChannelState = ChannelState::new();
+    assert_eq!(channel.pitch_bend_range_cents(), DEFAULT_PITCH_BEND_RANGE);
+    assert_eq!(channel.pitch_bend_cents(), 0.0);
+    channel.feed(&MidiEventType::PitchBend(PitchBend { value: 0x3000 }));
+    assert_eq!(channel.pitch_bend_cents(), 100.0);
+    // RPN 0 set to 12 semitones and 50 cents
+    for &(controller_number, controller_value) in &[(101, 0), (100, 0), (6, 12), (38, 50)] {
+        channel.feed(&MidiEventType::ControllerChange(ControllerChange { controller_number, controller_value }));
+    }
+    assert_eq!(channel.pitch_bend_range_cents(), 1250.0);
+    assert_eq!(channel.pitch_bend_cents(), 625.0);
+}
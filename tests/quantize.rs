@@ -0,0 +1,137 @@
+extern crate smf_lib;
+
+use smf_lib::file::track::SMFTrackChunk;
+use smf_lib::file::track::data::event::Event;
+use smf_lib::file::track::data::event::MidiEvent;
+use smf_lib::file::track::data::event::MidiEventType;
+use smf_lib::file::track::data::event::midi::NoteChange;
+use smf_lib::file::track::notes::Note;
+use smf_lib::transform::quantize::HumanizeOptions;
+use smf_lib::transform::quantize::NoteValue;
+use smf_lib::transform::quantize::QuantizeOptions;
+use smf_lib::transform::quantize::SwingOptions;
+
+fn note_on(key: u8, velocity: u8) -> Event {
+    Event::from_midi(MidiEvent::from_type(0, MidiEventType::NoteOn(NoteChange { key, velocity })))
+}
+
+fn note_off(key: u8) -> Event {
+    Event::from_midi(MidiEvent::from_type(0, MidiEventType::NoteOff(NoteChange { key, velocity: 64 })))
+}
+
+// One note per (start, length), on keys 60, 61, 62...
+fn notes_at(notes: &[(u64, u64)]) -> SMFTrackChunk {
+    let mut events: Vec<(u64, Event)> = Vec::new();
+    for (index, &(start, length)) in notes.iter().enumerate() {
+        events.push((start, note_on(60 + index as u8, 100)));
+        events.push((start + length, note_off(60 + index as u8)));
+    }
+    SMFTrackChunk::from_absolute_events(events).unwrap()
+}
+
+// Start and end of every note, by key
+fn spans(track: &SMFTrackChunk) -> Vec<(u64, u64)> {
+    let mut notes: Vec<Note> = track.notes();
+    notes.sort_by_key(|n| n.key);
+    notes.iter().map(|n| (n.start, n.end)).collect()
+}
+
+#[test]
+fn note_values_in_ticks() {
+    assert_eq!(NoteValue::new(1, 16).ticks(96), 24);
+    assert_eq!(NoteValue::new(1, 12).ticks(96), 32);
+    assert_eq!(NoteValue::new(3, 8).ticks(96), 144);
+    // Rounded to the nearest tick
+    assert_eq!(NoteValue::new(1, 7).ticks(96), 55);
+}
+
+#[test]
+fn notes_pair_first_in_first_out() {
+    // Two overlapping notes on the same key, then a note never released
+    let track: SMFTrackChunk = SMFTrackChunk::from_absolute_events(vec![
+        (0, note_on(60, 100)), (10, note_on(60, 90)), (20, note_on(60, 0)), (30, note_off(60)),
+        (40, note_on(64, 80)), (100, Event::end_of_track())
+    ]).unwrap();
+    let notes: Vec<(u8, u8, u64, u64, Option<usize>)> = track.notes().iter()
+        .map(|n| (n.key, n.velocity, n.start, n.end, n.off_index))
+        .collect();
+    assert_eq!(notes, vec![(60, 100, 0, 20, Some(2)), (60, 90, 10, 30, Some(3)), (64, 80, 40, 100, None)]);
+}
+
+#[test]
+fn quantize_full_strength() {
+    let mut track: SMFTrackChunk = notes_at(&[(0, 20), (13, 27), (50, 10), (100, 5)]);
+    track.quantize(24, &QuantizeOptions::new(NoteValue::new(1, 16))).unwrap();
+    // Starts snap to the nearest sixteenth, lengths are kept
+    assert_eq!(spans(&track), vec![(0, 20), (24, 51), (48, 58), (96, 101)]);
+}
+
+#[test]
+fn quantize_strength_and_window() {
+    let mut options: QuantizeOptions = QuantizeOptions::new(NoteValue::new(1, 16));
+    options.strength = 0.5;
+    let mut track: SMFTrackChunk = notes_at(&[(13, 10), (50, 10)]);
+    track.quantize(24, &options).unwrap();
+    // Halfway: 11 ticks away moves by 6, 2 ticks away moves by 1
+    assert_eq!(spans(&track), vec![(19, 29), (49, 59)]);
+    let mut options: QuantizeOptions = QuantizeOptions::new(NoteValue::new(1, 16));
+    options.window = 0.5;
+    let mut track: SMFTrackChunk = notes_at(&[(13, 10), (50, 10)]);
+    track.quantize(24, &options).unwrap();
+    // Only notes within 6 ticks of the grid move
+    assert_eq!(spans(&track), vec![(13, 23), (48, 58)]);
+}
+
+#[test]
+fn quantize_ends() {
+    let mut options: QuantizeOptions = QuantizeOptions::new(NoteValue::new(1, 16));
+    options.quantize_ends = true;
+    let mut track: SMFTrackChunk = notes_at(&[(2, 45), (30, 3)]);
+    track.quantize(24, &options).unwrap();
+    // The second note snaps to nothing and lasts a whole step
+    assert_eq!(spans(&track), vec![(0, 48), (24, 48)]);
+}
+
+#[test]
+fn swing_offsets() {
+    let options: SwingOptions = SwingOptions {
+        grid: NoteValue::new(1, 8),
+        amount: 2.0 / 3.0,
+        channels: None
+    };
+    let mut track: SMFTrackChunk = notes_at(&[(0, 10), (24, 10), (48, 10), (72, 10), (96, 10)]);
+    track.swing(48, &options).unwrap();
+    // The offbeat eighth moves from 48 to 64 within each pair, ticks in between are stretched
+    assert_eq!(spans(&track), vec![(0, 10), (32, 42), (64, 74), (80, 90), (96, 106)]);
+    let straight: SwingOptions = SwingOptions { amount: 0.5, ..options };
+    let mut track: SMFTrackChunk = notes_at(&[(0, 10), (24, 10), (48, 10)]);
+    track.swing(48, &straight).unwrap();
+    assert_eq!(spans(&track), vec![(0, 10), (24, 34), (48, 58)]);
+}
+
+#[test]
+fn humanize_is_reproducible() {
+    let options: HumanizeOptions = HumanizeOptions {
+        seed: 42,
+        timing: 5,
+        velocity: 10,
+        channels: None
+    };
+    let original: SMFTrackChunk = notes_at(&[(100, 20), (200, 20), (300, 20), (400, 20)]);
+    let humanized = |seed: u64| {
+        let mut track: SMFTrackChunk = original.clone();
+        track.humanize(&HumanizeOptions { seed, ..options.clone() }).unwrap();
+        let mut notes: Vec<Note> = track.notes();
+        notes.sort_by_key(|n| n.key);
+        notes.iter().map(|n| (n.start, n.end, n.velocity)).collect::<Vec<(u64, u64, u8)>>()
+    };
+    let first: Vec<(u64, u64, u8)> = humanized(42);
+    assert_eq!(humanized(42), first);
+    assert!(humanized(43) != first);
+    for (index, &(start, end, velocity)) in first.iter().enumerate() {
+        let original_start: u64 = (index as u64 + 1) * 100;
+        assert!((original_start - 5..=original_start + 5).contains(&start));
+        assert_eq!(end - start, 20);
+        assert!((90..=110).contains(&velocity));
+    }
+}
@@ -0,0 +1,100 @@
+extern crate smf_lib;
+
+use smf_lib::file::SMF;
+use smf_lib::file::header::data::MidiDivisionsType;
+use smf_lib::file::header::data::MidiFormat;
+use smf_lib::file::track::SMFTrackChunk;
+use smf_lib::file::track::data::event::EventType;
+use smf_lib::file::track::data::event::MetaEventType;
+use smf_lib::file::track::data::event::MidiEvent;
+use smf_lib::file::track::data::event::MidiEventType;
+use smf_lib::file::track::data::event::midi::NoteChange;
+use smf_lib::live::LiveMessage;
+use smf_lib::live::recorder::Recorder;
+use smf_lib::live::recorder::RecorderOptions;
+
+fn note_on(channel: u8, key: u8) -> LiveMessage {
+    LiveMessage::Channel(MidiEvent::from_type(channel, MidiEventType::NoteOn(NoteChange { key, velocity: 100 })))
+}
+
+// Tick and a short description of every event, keys for notes
+fn layout(track: &SMFTrackChunk) -> Vec<(u64, String)> {
+    track.absolute_events().iter().map(|(tick, event)| {
+        let text: String = match event.event {
+            EventType::MidiEvent(MidiEvent { channel, event: MidiEventType::NoteOn(ref n), .. }) => format!("{}:{}", channel, n.key),
+            EventType::MetaEvent(ref m) => match m.event {
+                MetaEventType::SetTempo(ref t) => format!("tempo {}", t.tempo),
+                MetaEventType::EndOfTrack(_) => "end".to_string(),
+                _ => "meta".to_string()
+            },
+            EventType::SysExEvent(_) => "sysex".to_string(),
+            _ => "midi".to_string()
+        };
+        (*tick, text)
+    }).collect()
+}
+
+fn ticks_per_quarter_note(smf: &SMF) -> u16 {
+    match smf.header.division_system {
+        MidiDivisionsType::TicksPerQuarterNote(ref d) => d.ticks_per_quarter_note,
+        _ => panic!("expected ticks per quarter note")
+    }
+}
+
+#[test]
+fn tempo_free_recording() {
+    let mut recorder: Recorder = Recorder::new(RecorderOptions::tempo_free());
+    recorder.start(1_000_000);
+    // Before the start, on tick 0
+    recorder.record(900_000, note_on(0, 59));
+    recorder.record(1_000_000, note_on(0, 60));
+    // Rounded to the nearest millisecond
+    recorder.record(1_250_400, note_on(0, 62));
+    recorder.record(1_250_600, note_on(0, 64));
+    // Real Time messages are not kept by default
+    recorder.record(1_300_000, LiveMessage::TimingClock);
+    recorder.stop(3_000_000);
+    let smf: SMF = recorder.to_smf().unwrap();
+    assert_eq!(ticks_per_quarter_note(&smf), 1000);
+    assert!(matches!(smf.header.format, MidiFormat::SingleTrack));
+    let expected: Vec<(u64, String)> = vec![
+        (0, "tempo 1000000".to_string()), (0, "0:59".to_string()), (0, "0:60".to_string()),
+        (250, "0:62".to_string()), (251, "0:64".to_string()), (2000, "end".to_string())
+    ];
+    assert_eq!(layout(&smf.tracks[0]), expected);
+}
+
+#[test]
+fn timestamps_to_ticks_at_a_tempo() {
+    let mut options: RecorderOptions = RecorderOptions::new(96, 500_000);
+    options.keep_real_time = true;
+    let mut recorder: Recorder = Recorder::new(options);
+    // The first message is tick 0 without a call to start, the End of Track follows the last message without stop
+    recorder.record(10_000_000, note_on(1, 60));
+    recorder.record(10_250_000, LiveMessage::TimingClock);
+    recorder.record(11_000_000, note_on(1, 62));
+    let smf: SMF = recorder.to_smf().unwrap();
+    let expected: Vec<(u64, String)> = vec![
+        (0, "tempo 500000".to_string()), (0, "1:60".to_string()), (48, "sysex".to_string()),
+        (192, "1:62".to_string()), (192, "end".to_string())
+    ];
+    assert_eq!(layout(&smf.tracks[0]), expected);
+    recorder.clear();
+    assert!(recorder.is_empty());
+    assert!(Recorder::new(RecorderOptions::new(0, 500_000)).to_smf().is_err());
+}
+
+#[test]
+fn split_channels() {
+    let mut options: RecorderOptions = RecorderOptions::new(96, 500_000);
+    options.split_channels = true;
+    let mut recorder: Recorder = Recorder::new(options);
+    recorder.record(0, note_on(0, 60));
+    recorder.record(500_000, note_on(3, 62));
+    let smf: SMF = recorder.to_smf().unwrap();
+    assert!(matches!(smf.header.format, MidiFormat::SimultaneousTracks));
+    assert_eq!(smf.tracks.len(), 3);
+    assert_eq!(layout(&smf.tracks[0])[0], (0, "tempo 500000".to_string()));
+    assert!(layout(&smf.tracks[1]).contains(&(0, "0:60".to_string())));
+    assert!(layout(&smf.tracks[2]).contains(&(96, "3:62".to_string())));
+}
@@ -0,0 +1,66 @@
+extern crate smf_lib;
+
+mod common;
+
+use smf_lib::convert::rmid::ChunkTooLongError;
+use smf_lib::convert::rmid::Rmid;
+use smf_lib::file::SMF;
+use smf_lib::file::track::data::event::Event;
+use smf_lib::file::track::data::event::MidiEvent;
+use smf_lib::file::track::data::event::MidiEventType;
+use smf_lib::file::track::data::event::midi::NoteChange;
+use std::io::Cursor;
+
+fn one_note() -> SMF {
+    let events: Vec<(u64, Event)> = vec![
+        (0, Event::from_midi(MidiEvent::from_type(0, MidiEventType::NoteOn(NoteChange { key: 60, velocity: 100 })))),
+        (480, Event::from_midi(MidiEvent::from_type(0, MidiEventType::NoteOff(NoteChange { key: 60, velocity: 64 }))))
+    ];
+    common::smf(480, vec![events])
+}
+
+fn smf_bytes(smf: &SMF) -> Vec<u8> {
+    let mut bytes: Vec<u8> = Vec::new();
+    smf.write(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn rmid_round_trip() {
+    let mut rmid: Rmid = Rmid::new(one_note());
+    rmid.set_tag("INAM", "Song");
+    rmid.set_tag("ICMT", "Odd");
+    rmid.dls = Some(b"RIFF\x05\x00\x00\x00DLS x".to_vec());
+    let mut bytes: Vec<u8> = Vec::new();
+    rmid.write(&mut bytes).unwrap();
+    assert_eq!(&bytes[..4], b"RIFF");
+    assert_eq!(&bytes[8..12], b"RMID");
+    assert_eq!(bytes.len() % 2, 0);
+    let read: Rmid = Rmid::read(&mut Cursor::new(bytes.clone())).unwrap();
+    assert_eq!(smf_bytes(&read.smf), smf_bytes(&one_note()));
+    assert_eq!(read.tag("INAM"), Some("Song"));
+    assert_eq!(read.tag("ICMT"), Some("Odd"));
+    assert_eq!(read.dls, rmid.dls);
+    // SMF::read unwraps RMID files
+    assert_eq!(smf_bytes(&SMF::read(&mut Cursor::new(bytes)).unwrap()), smf_bytes(&one_note()));
+}
+
+#[test]
+fn rmid_chunk_longer_than_riff() {
+    // A data chunk claiming 4 GB in a 12 byte RIFF chunk
+    let bytes: Vec<u8> = b"RIFF\x0C\x00\x00\x00RMIDdata\xFF\xFF\xFF\xFF".to_vec();
+    let error = Rmid::read(&mut Cursor::new(bytes)).err().unwrap();
+    assert!(error.downcast_ref::<ChunkTooLongError>().is_some());
+}
+
+#[test]
+fn rmid_info_tag_longer_than_list() {
+    let mut bytes: Vec<u8> = Vec::new();
+    Rmid::new(one_note()).write(&mut bytes).unwrap();
+    // An INAM tag claiming 4 GB in a 12 byte LIST chunk
+    bytes.extend_from_slice(b"LIST\x0C\x00\x00\x00INFOINAM\xFF\xFF\xFF\xFF");
+    let riff_length: u32 = bytes.len() as u32 - 8;
+    bytes[4..8].copy_from_slice(&[riff_length as u8, (riff_length >> 8) as u8, (riff_length >> 16) as u8, (riff_length >> 24) as u8]);
+    let error = Rmid::read(&mut Cursor::new(bytes)).err().unwrap();
+    assert!(error.downcast_ref::<ChunkTooLongError>().is_some());
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "serde")]
+
+extern crate serde_json;
+extern crate smf_lib;
+
+use smf_lib::file::SMF;
+use std::io::Cursor;
+
+// Every kind of event, read back from the bytes of a file
+fn read_file(text: &str) -> SMF {
+    let mut bytes: Vec<u8> = Vec::new();
+    SMF::from_text(text).unwrap().write(&mut bytes).unwrap();
+    SMF::read(&mut Cursor::new(bytes)).unwrap()
+}
+
+#[test]
+fn json_round_trip() {
+    let smf: SMF = read_file("MFile 1 2 96
+MTrk
+0 Meta TrkName \"Tempo\"
+0 Meta Copyright \"Someone\"
+0 Tempo 500000
+0 SMPTE 96 0 3 0 0
+0 TimeSig 3/8 24 8
+0 KeySig -3 minor
+0 Meta 0x7f 00 00 41
+0 Meta 0x60 01 02
+384 Meta TrkEnd
+TrkEnd
+MTrk
+0 PrCh ch=10 p=5
+0 On ch=1 n=60 v=100
+0 Par ch=1 c=7 v=127
+0 PoPr ch=1 n=60 v=20
+96 Off ch=1 n=60 v=64
+96 Pb ch=16 v=16383
+100 ChPr ch=2 v=4
+100 SysEx 7e 7f 09 01 f7
+200 Meta TrkEnd
+TrkEnd
+");
+    let json: String = serde_json::to_string(&smf).unwrap();
+    let from_json: SMF = serde_json::from_str(&json).unwrap();
+    assert_eq!(from_json, smf);
+    // SMPTE divisions
+    let smf: SMF = read_file("MFile 0 1 -25 40\nMTrk\n0 Meta TrkEnd\nTrkEnd\n");
+    let from_json: SMF = serde_json::from_str(&serde_json::to_string(&smf).unwrap()).unwrap();
+    assert_eq!(from_json, smf);
+}
+
+#[test]
+fn json_of_the_readme() {
+    let smf: SMF = read_file("MFile 0 1 96\nMTrk\n0 Meta TrkEnd\nTrkEnd\n");
+    let expected: serde_json::Value = serde_json::from_str(r#"{
+  "header": {
+    "length": 6,
+    "format": "SingleTrack",
+    "nb_tracks": 1,
+    "division_system": {"type": "TicksPerQuarterNote", "ticks_per_quarter_note": 96}
+  },
+  "tracks": [{
+    "length": 4,
+    "track_events": [{
+      "delta_time": 0,
+      "event": {
+        "code_byte": 255,
+        "event": {"type": "MetaEvent", "sub_code_byte": 47, "length": 0, "event": {"type": "EndOfTrack"}}
+      }
+    }]
+  }]
+}"#).unwrap();
+    assert_eq!(serde_json::to_value(&smf).unwrap(), expected);
+    assert_eq!(serde_json::from_value::<SMF>(expected).unwrap(), smf);
+}
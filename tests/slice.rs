@@ -0,0 +1,53 @@
+extern crate smf_lib;
+
+mod common;
+
+use smf_lib::file::SMF;
+use smf_lib::file::track::data::event::Event;
+use smf_lib::file::track::data::event::EventType;
+use smf_lib::file::track::data::event::MidiEvent;
+use smf_lib::file::track::data::event::MidiEventType;
+use smf_lib::file::track::data::event::midi::NoteChange;
+
+fn note(key: u8, on: bool) -> Event {
+    let change: NoteChange = NoteChange { key, velocity: if on { 100 } else { 0x40 } };
+    Event::from_midi(MidiEvent::from_type(0, if on { MidiEventType::NoteOn(change) } else { MidiEventType::NoteOff(change) }))
+}
+
+// Three quarter notes at 96 ticks per quarter note
+fn three_notes() -> SMF {
+    let mut events: Vec<(u64, Event)> = Vec::new();
+    for (index, key) in [60u8, 62, 64].iter().enumerate() {
+        events.push((index as u64 * 96, note(*key, true)));
+        events.push(((index as u64 + 1) * 96, note(*key, false)));
+    }
+    common::smf(96, vec![events])
+}
+
+fn played_keys(smf: &SMF) -> Vec<u8> {
+    smf.tracks[0].track_events.iter().filter_map(|e| match e.event.event {
+        EventType::MidiEvent(MidiEvent { event: MidiEventType::NoteOn(ref n), .. }) => Some(n.key),
+        _ => None
+    }).collect()
+}
+
+#[test]
+fn repeat_section_times() {
+    let smf: SMF = three_notes();
+    let twice: SMF = smf.repeat_section(96, 192, 2).unwrap();
+    assert_eq!(played_keys(&twice), vec![60, 62, 62, 64]);
+    assert_eq!(twice.end_tick(), 384);
+}
+
+#[test]
+fn repeat_section_zero_times_cuts_it_out() {
+    let smf: SMF = three_notes();
+    let cut: SMF = smf.repeat_section(96, 192, 0).unwrap();
+    assert_eq!(played_keys(&cut), vec![60, 64]);
+    assert_eq!(cut.end_tick(), 192);
+    // Nothing before or after the section
+    let empty: SMF = smf.repeat_section(0, 288, 0).unwrap();
+    assert_eq!(empty.tracks.len(), 1);
+    assert!(played_keys(&empty).is_empty());
+    assert_eq!(empty.end_tick(), 0);
+}
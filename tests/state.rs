@@ -0,0 +1,124 @@
+extern crate smf_lib;
+
+mod common;
+
+use smf_lib::file::SMF;
+use smf_lib::file::track::data::event::Event;
+use smf_lib::file::track::data::event::EventType;
+use smf_lib::file::track::data::event::MidiEvent;
+use smf_lib::file::track::data::event::MidiEventType;
+use smf_lib::file::track::data::event::midi::ControllerChange;
+use smf_lib::file::track::data::event::midi::NoteChange;
+use smf_lib::file::track::data::event::midi::PitchBend;
+use smf_lib::file::track::data::event::midi::ProgramChange;
+use smf_lib::state::ChannelState;
+use smf_lib::state::MidiState;
+
+fn midi(channel: u8, event: MidiEventType) -> Event {
+    Event::from_midi(MidiEvent::from_type(channel, event))
+}
+
+fn cc(channel: u8, controller_number: u8, controller_value: u8) -> Event {
+    midi(channel, MidiEventType::ControllerChange(ControllerChange { controller_number, controller_value }))
+}
+
+fn program(channel: u8, new_program_number: u8) -> Event {
+    midi(channel, MidiEventType::ProgramChange(ProgramChange { new_program_number }))
+}
+
+fn note(channel: u8, key: u8, velocity: u8) -> Event {
+    midi(channel, MidiEventType::NoteOn(NoteChange { key, velocity }))
+}
+
+// Status byte and data of a Midi Event, the data of a Pitch Bend is its 14-bit value
+fn summary(event: &Event) -> (u8, u16, u16) {
+    let midi_event: &MidiEvent = match event.event {
+        EventType::MidiEvent(ref m) => m,
+        _ => panic!("expected a Midi Event")
+    };
+    let status: u8 = midi_event.event.code_byte() | midi_event.channel;
+    match midi_event.event {
+        MidiEventType::ControllerChange(ref c) => (status, u16::from(c.controller_number), u16::from(c.controller_value)),
+        MidiEventType::ProgramChange(ref p) => (status, u16::from(p.new_program_number), 0),
+        MidiEventType::PitchBend(ref b) => (status, b.value, 0),
+        MidiEventType::NoteOn(ref n) => (status, u16::from(n.key), u16::from(n.velocity)),
+        _ => panic!("unexpected Midi Event")
+    }
+}
+
+// Bank 1/2, program 5, volume, a pitch bend range of 12 semitones, a bend, a note and Reset All Controllers on channel 0
+// Program 9 on channel 1 in a second track
+fn song() -> SMF {
+    let first: Vec<(u64, Event)> = vec![
+        (0, cc(0, 0, 1)), (0, cc(0, 32, 2)), (0, program(0, 5)), (0, cc(0, 7, 100)), (0, cc(0, 1, 30)),
+        (0, cc(0, 101, 0)), (0, cc(0, 100, 0)), (0, cc(0, 6, 12)), (0, cc(0, 38, 0)),
+        (10, midi(0, MidiEventType::PitchBend(PitchBend { value: 0x3000 }))),
+        (20, note(0, 60, 100)),
+        (30, cc(0, 121, 0)),
+        (40, note(0, 60, 0))
+    ];
+    let second: Vec<(u64, Event)> = vec![(5, program(1, 9))];
+    common::smf(96, vec![first, second])
+}
+
+#[test]
+fn state_at_ticks() {
+    let smf: SMF = song();
+    // Events on the tick itself are not included
+    let start: MidiState = smf.state_at(0);
+    assert_eq!(start.channels[0].program, None);
+    let state: MidiState = smf.state_at(10);
+    let channel: &ChannelState = &state.channels[0];
+    assert_eq!((channel.program, channel.bank(), channel.controller(7)), (Some(5), Some(0x82), Some(100)));
+    assert_eq!(channel.rpn(0), Some(12 << 7));
+    assert_eq!(channel.pitch_bend, None);
+    assert_eq!(state.channels[1].program, Some(9));
+    let state: MidiState = smf.state_at(21);
+    assert_eq!(state.channels[0].pitch_bend, Some(0x3000));
+    assert_eq!(state.channels[0].sounding_notes, vec![(60, 100)]);
+    // Reset All Controllers keeps bank and volume, the RPN values and the sounding notes
+    let state: MidiState = smf.state_at(31);
+    let channel: &ChannelState = &state.channels[0];
+    assert_eq!((channel.bank(), channel.controller(7), channel.controller(1), channel.pitch_bend), (Some(0x82), Some(100), None, None));
+    assert_eq!(channel.rpn(0), Some(12 << 7));
+    assert_eq!(channel.sounding_notes, vec![(60, 100)]);
+    assert!(smf.state_at(41).channels[0].sounding_notes.is_empty());
+}
+
+#[test]
+fn state_to_events() {
+    let events: Vec<(u8, u16, u16)> = song().state_at(21).channels[0].to_events(0).iter().map(summary).collect();
+    // Bank, program, controllers, the RPN then the null RPN, the bend
+    assert_eq!(events, vec![
+        (0xB0, 0, 1), (0xB0, 32, 2), (0xC0, 5, 0), (0xB0, 1, 30), (0xB0, 7, 100),
+        (0xB0, 101, 0), (0xB0, 100, 0), (0xB0, 6, 12), (0xB0, 38, 0), (0xB0, 101, 127), (0xB0, 100, 127),
+        (0xE0, 0x3000, 0)
+    ]);
+    let events: Vec<(u8, u16, u16)> = song().state_at(21).to_events().iter().map(summary).collect();
+    assert_eq!(events.len(), 13);
+    assert_eq!(events[12], (0xC1, 9, 0));
+}
+
+#[test]
+fn data_entry_needs_a_selected_parameter() {
+    let mut channel: ChannelState = ChannelState::new();
+    let feed = |channel: &mut ChannelState, number: u8, value: u8| {
+        channel.feed(&MidiEventType::ControllerChange(ControllerChange { controller_number: number, controller_value: value }));
+    };
+    // Nothing selected yet
+    feed(&mut channel, 6, 5);
+    assert!(channel.to_events(0).is_empty());
+    // NRPN 1/8, coarse then fine, then an increment
+    feed(&mut channel, 99, 1);
+    feed(&mut channel, 98, 8);
+    feed(&mut channel, 6, 3);
+    feed(&mut channel, 38, 4);
+    feed(&mut channel, 96, 0);
+    assert_eq!(channel.nrpn((1 << 7) | 8), Some((3 << 7) | 5));
+    // The null RPN deselects, following Data Entries are ignored
+    feed(&mut channel, 101, 127);
+    feed(&mut channel, 100, 127);
+    feed(&mut channel, 6, 100);
+    assert_eq!(channel.nrpn((1 << 7) | 8), Some((3 << 7) | 5));
+    assert_eq!(channel.rpn(0x3FFF), None);
+}
@@ -0,0 +1,58 @@
+extern crate smf_lib;
+
+use smf_lib::convert::ParseError;
+use smf_lib::file::SMF;
+
+const TEXT: &str = "MFile 1 2 96
+MTrk
+0 Meta TrkName \"Tempo \\\"map\\\"\"
+0 Tempo 500000
+0 TimeSig 3/8 24 8
+0 KeySig -3 minor
+0 Meta 0x60 01 02
+384 Meta TrkEnd
+TrkEnd
+MTrk
+0 PrCh ch=10 p=5
+0 On ch=1 n=60 v=100
+0 Par ch=1 c=7 v=127
+96 Off ch=1 n=60 v=64
+96 Pb ch=16 v=16383
+100 ChPr ch=2 v=4
+100 SysEx 7e 7f 09 01 f7
+200 Meta TrkEnd
+TrkEnd
+";
+
+fn error_of(text: &str) -> (usize, String) {
+    let error = SMF::from_text(text).err().unwrap();
+    let error: &ParseError = error.downcast_ref::<ParseError>().unwrap();
+    (error.line, error.message.clone())
+}
+
+#[test]
+fn text_round_trip() {
+    let smf: SMF = SMF::from_text(TEXT).unwrap();
+    assert_eq!(smf.to_text(false).unwrap(), TEXT);
+    // Delta times give the same file back
+    let delta: String = smf.to_text(true).unwrap();
+    assert!(delta.contains("\n+96 Off ch=1 n=60 v=64\n+0 Pb ch=16 v=16383\n"));
+    assert_eq!(SMF::from_text(&delta).unwrap().to_text(false).unwrap(), TEXT);
+}
+
+#[test]
+fn text_rejects_out_of_range_values() {
+    let track = |event: &str| format!("MFile 0 1 96\nMTrk\n0 {}\n10 Meta TrkEnd\nTrkEnd\n", event);
+    assert_eq!(error_of(&track("On ch=1 n=128 v=1")), (3, String::from("n= goes from 0 to 127")));
+    assert_eq!(error_of(&track("Off ch=1 n=1 v=200")), (3, String::from("v= goes from 0 to 127")));
+    assert_eq!(error_of(&track("Par ch=1 c=128 v=0")), (3, String::from("c= goes from 0 to 127")));
+    assert_eq!(error_of(&track("PrCh ch=1 p=128")), (3, String::from("p= goes from 0 to 127")));
+    assert_eq!(error_of(&track("Pb ch=1 v=16384")), (3, String::from("v= goes from 0 to 16383")));
+    assert_eq!(error_of(&track("On ch=17 n=1 v=1")), (3, String::from("Channels go from 1 to 16")));
+}
+
+#[test]
+fn text_rejects_events_after_track_end() {
+    let text: &str = "MFile 0 1 96\nMTrk\n0 On ch=1 n=60 v=100\n10 Meta TrkEnd\n20 Off ch=1 n=60 v=0\nTrkEnd\n";
+    assert_eq!(error_of(text), (5, String::from("Event after Meta TrkEnd")));
+}
@@ -0,0 +1,153 @@
+extern crate smf_lib;
+
+use smf_lib::VLVTooBigError;
+use smf_lib::file::track::SMFTrackChunk;
+use smf_lib::file::track::data::TrackEvent;
+use smf_lib::file::track::data::event::Event;
+use smf_lib::file::track::data::event::EventType;
+use smf_lib::file::track::data::event::MidiEvent;
+use smf_lib::file::track::data::event::MidiEventType;
+use smf_lib::file::track::data::event::midi::NoteChange;
+
+fn note_on(key: u8) -> Event {
+    Event::from_midi(MidiEvent::from_type(0, MidiEventType::NoteOn(NoteChange { key, velocity: 100 })))
+}
+
+fn delta_times(track: &SMFTrackChunk) -> Vec<u32> {
+    track.track_events.iter().map(|e| e.delta_time).collect()
+}
+
+#[test]
+fn from_absolute_events_sorts_and_ends_the_track() {
+    let track: SMFTrackChunk = SMFTrackChunk::from_absolute_events(vec![
+        (96, note_on(62)), (0, note_on(60)), (96, Event::end_of_track()), (48, note_on(61)), (200, Event::end_of_track())
+    ]).unwrap();
+    assert_eq!(delta_times(&track), vec![0, 48, 48, 104]);
+    assert_eq!(track.absolute_ticks(), vec![0, 48, 96, 200]);
+    assert!(track.track_events[3].event.is_end_of_track());
+    assert_eq!(track.length, 3 * 4 + 4);
+}
+
+#[test]
+fn from_absolute_events_rejects_deltas_too_big() {
+    // The biggest delta a VLV can hold
+    let track: SMFTrackChunk = SMFTrackChunk::from_absolute_events(vec![(0x0FFF_FFFF, note_on(60))]).unwrap();
+    assert_eq!(delta_times(&track), vec![0x0FFF_FFFF, 0]);
+    for tick in &[0x1000_0000u64, 0x1_0000_0000, 0x1_0000_0001] {
+        let error = SMFTrackChunk::from_absolute_events(vec![(0, note_on(60)), (*tick, note_on(62))]).err().unwrap();
+        assert!(error.downcast_ref::<VLVTooBigError>().is_some());
+    }
+}
+
+fn key(event: &Event) -> Option<u8> {
+    match event.event {
+        EventType::MidiEvent(MidiEvent { event: MidiEventType::NoteOn(ref n), .. }) => Some(n.key),
+        _ => None
+    }
+}
+
+// Keys and absolute ticks of a track, the End of Track shows as None
+fn layout(track: &SMFTrackChunk) -> Vec<(u64, Option<u8>)> {
+    track.absolute_events().iter().map(|(tick, event)| (*tick, key(event))).collect()
+}
+
+// Notes 60 at 0, 62 at 96, 64 at 192, ending at 384
+fn three_notes() -> SMFTrackChunk {
+    SMFTrackChunk::from_absolute_events(vec![
+        (0, note_on(60)), (96, note_on(62)), (192, note_on(64)), (384, Event::end_of_track())
+    ]).unwrap()
+}
+
+fn assert_consistent(track: &SMFTrackChunk) {
+    let ends: Vec<bool> = track.track_events.iter().map(|e| e.event.is_end_of_track()).collect();
+    assert_eq!(ends.iter().filter(|e| **e).count(), 1);
+    assert!(ends[ends.len() - 1]);
+    let mut copy: SMFTrackChunk = track.clone();
+    copy.update_length().unwrap();
+    assert_eq!(copy.length, track.length);
+}
+
+#[test]
+fn insert_at_tick() {
+    let mut track: SMFTrackChunk = three_notes();
+    // After the event already on that tick
+    assert_eq!(track.insert_at_tick(96, note_on(61)).unwrap(), 2);
+    assert_eq!(delta_times(&track), vec![0, 96, 0, 96, 192]);
+    // After the End of Track, which moves later
+    assert_eq!(track.insert_at_tick(500, note_on(70)).unwrap(), 4);
+    assert_eq!(layout(&track), vec![(0, Some(60)), (96, Some(62)), (96, Some(61)), (192, Some(64)), (500, Some(70)), (500, None)]);
+    assert_consistent(&track);
+    // Inserting an End of Track earlier does not shorten the track
+    assert_eq!(track.insert_at_tick(10, Event::end_of_track()).unwrap(), 5);
+    assert_eq!(track.end_tick(), 500);
+    assert_consistent(&track);
+}
+
+#[test]
+fn remove_at() {
+    let mut track: SMFTrackChunk = three_notes();
+    let (tick, event) = track.remove_at(1).unwrap();
+    assert_eq!((tick, key(&event)), (96, Some(62)));
+    // The next note keeps its absolute position
+    assert_eq!(delta_times(&track), vec![0, 192, 192]);
+    assert_consistent(&track);
+    // Removing the End of Track puts a new one right after the last event
+    let (tick, event) = track.remove_at(2).unwrap();
+    assert!(event.is_end_of_track());
+    assert_eq!(tick, 384);
+    assert_eq!(layout(&track), vec![(0, Some(60)), (192, Some(64)), (192, None)]);
+    assert!(track.remove_at(3).is_err());
+}
+
+#[test]
+fn remove_where_and_retain() {
+    let mut track: SMFTrackChunk = three_notes();
+    let removed: Vec<(u64, Event)> = track.remove_where(|tick, _| tick >= 96).unwrap();
+    // The End of Track is never removed and stays where it was
+    assert_eq!(removed.iter().map(|(t, e)| (*t, key(e))).collect::<Vec<_>>(), vec![(96, Some(62)), (192, Some(64))]);
+    assert_eq!(layout(&track), vec![(0, Some(60)), (384, None)]);
+    assert_consistent(&track);
+    let mut track: SMFTrackChunk = three_notes();
+    track.retain(|_, e| key(e) == Some(62)).unwrap();
+    assert_eq!(delta_times(&track), vec![96, 288]);
+    assert_consistent(&track);
+}
+
+#[test]
+fn move_event() {
+    let mut track: SMFTrackChunk = three_notes();
+    // Moved after the note already on tick 192
+    assert_eq!(track.move_event(0, 192).unwrap(), 2);
+    assert_eq!(layout(&track), vec![(96, Some(62)), (192, Some(64)), (192, Some(60)), (384, None)]);
+    assert_eq!(delta_times(&track), vec![96, 96, 0, 192]);
+    // Past the End of Track, which follows it
+    assert_eq!(track.move_event(0, 1000).unwrap(), 2);
+    assert_eq!(layout(&track), vec![(192, Some(64)), (192, Some(60)), (1000, Some(62)), (1000, None)]);
+    assert_consistent(&track);
+    // The End of Track can move earlier, but not before the last event
+    assert_eq!(track.move_event(3, 0).unwrap(), 3);
+    assert_eq!(track.end_tick(), 1000);
+    let mut track: SMFTrackChunk = three_notes();
+    track.move_event(3, 200).unwrap();
+    assert_eq!(track.end_tick(), 200);
+    assert_consistent(&track);
+}
+
+#[test]
+fn ensure_end_of_track() {
+    // Two End of Track events in the middle and none at the end
+    let mut track: SMFTrackChunk = SMFTrackChunk {
+        length: 0,
+        track_events: vec![
+            TrackEvent { delta_time: 0, event: Event::end_of_track() },
+            TrackEvent { delta_time: 0, event: note_on(60) },
+            TrackEvent { delta_time: 10, event: Event::end_of_track() },
+            TrackEvent { delta_time: 86, event: note_on(62) },
+            TrackEvent { delta_time: 96, event: note_on(64) }
+        ]
+    };
+    track.ensure_end_of_track().unwrap();
+    assert_eq!(layout(&track), vec![(0, Some(60)), (96, Some(62)), (192, Some(64)), (192, None)]);
+    assert_eq!(delta_times(&track), vec![0, 96, 96, 0]);
+    assert_consistent(&track);
+}
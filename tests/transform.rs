@@ -0,0 +1,71 @@
+extern crate smf_lib;
+
+use smf_lib::file::track::SMFTrackChunk;
+use smf_lib::file::track::data::event::Event;
+use smf_lib::file::track::data::event::EventType;
+use smf_lib::file::track::data::event::MidiEvent;
+use smf_lib::file::track::data::event::MidiEventType;
+use smf_lib::file::track::data::event::midi::NoteChange;
+use smf_lib::transform::OutOfRange;
+use smf_lib::transform::OutOfRangeKind;
+use smf_lib::transform::TransposeOptions;
+use smf_lib::transform::VelocityOptions;
+
+fn note_on(channel: u8, key: u8) -> Event {
+    Event::from_midi(MidiEvent::from_type(channel, MidiEventType::NoteOn(NoteChange { key, velocity: 100 })))
+}
+
+fn keys(track: &SMFTrackChunk) -> Vec<u8> {
+    track.track_events.iter().filter_map(|e| match e.event.event {
+        EventType::MidiEvent(MidiEvent { event: MidiEventType::NoteOn(ref n), .. }) => Some(n.key),
+        _ => None
+    }).collect()
+}
+
+fn track() -> SMFTrackChunk {
+    SMFTrackChunk::from_absolute_events(vec![(0, note_on(0, 60)), (0, note_on(0, 127)), (10, note_on(9, 36))]).unwrap()
+}
+
+#[test]
+fn transpose_folds_by_default() {
+    let mut track: SMFTrackChunk = track();
+    let report = track.transpose(&TransposeOptions::new(12)).unwrap();
+    // 127 + 12 folds back to 127, the drum channel is skipped
+    assert_eq!(keys(&track), vec![72, 127, 36]);
+    assert_eq!(report.changed, 1);
+    assert_eq!(report.out_of_range.len(), 1);
+    assert!(report.out_of_range[0].kind == OutOfRangeKind::Key);
+    assert_eq!((report.out_of_range[0].key, report.out_of_range[0].wanted), (127, 139));
+}
+
+#[test]
+fn transpose_counts_only_modified_events() {
+    let mut track: SMFTrackChunk = track();
+    let mut options: TransposeOptions = TransposeOptions::new(5);
+    options.out_of_range = OutOfRange::Clamp;
+    // Already at the top, clamping leaves it where it was
+    let report = track.transpose(&options).unwrap();
+    assert_eq!(keys(&track), vec![65, 127, 36]);
+    assert_eq!(report.changed, 1);
+    options.out_of_range = OutOfRange::Drop;
+    let report = track.transpose(&options).unwrap();
+    assert_eq!(keys(&track), vec![70, 36]);
+    assert_eq!(report.changed, 2);
+    let report = track.transpose(&TransposeOptions::new(0)).unwrap();
+    assert_eq!(report.changed, 0);
+}
+
+#[test]
+fn scale_velocities_reports_the_velocity() {
+    let mut track: SMFTrackChunk = track();
+    let mut options: VelocityOptions = VelocityOptions::new();
+    options.offset = 30;
+    let report = track.scale_velocities(&options).unwrap();
+    // Every note wants 130 and is limited to 127, the report keeps the key of the note apart
+    assert_eq!(report.changed, 3);
+    let reported: Vec<(u8, i32)> = report.out_of_range.iter().map(|e| {
+        assert!(e.kind == OutOfRangeKind::Velocity);
+        (e.key, e.wanted)
+    }).collect();
+    assert_eq!(reported, vec![(60, 130), (127, 130), (36, 130)]);
+}
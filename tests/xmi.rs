@@ -0,0 +1,79 @@
+extern crate smf_lib;
+
+use smf_lib::file::SMF;
+use smf_lib::file::loops::LoopConvention;
+use smf_lib::file::track::data::event::EventType;
+use smf_lib::file::track::data::event::MetaEventType;
+use smf_lib::file::track::data::event::MidiEventType;
+use std::io::Cursor;
+
+// IFF chunks have big endian lengths and are padded to an even size
+fn chunk(id: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut bytes: Vec<u8> = id.to_vec();
+    let length: u32 = data.len() as u32;
+    bytes.extend_from_slice(&[(length >> 24) as u8, (length >> 16) as u8, (length >> 8) as u8, length as u8]);
+    bytes.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        bytes.push(0);
+    }
+    bytes
+}
+
+// A loop played twice around a quarter note
+fn xmi() -> Vec<u8> {
+    let events: Vec<u8> = vec![
+        0xB0, 116, 2,      // FOR loop, 2 times
+        0x90, 60, 100, 60, // Note On lasting 60 ticks
+        60,                // Delay
+        0xB0, 117, 0,      // NEXT
+        0xFF, 0x2F, 0x00
+    ];
+    let mut form: Vec<u8> = b"XMID".to_vec();
+    form.extend(chunk(b"EVNT", &events));
+    let mut cat: Vec<u8> = b"XMID".to_vec();
+    cat.extend(chunk(b"FORM", &form));
+    let mut bytes: Vec<u8> = chunk(b"FORM", b"XDIR");
+    bytes.extend(chunk(b"CAT ", &cat));
+    bytes
+}
+
+#[test]
+fn read_xmi_sequence() {
+    let files: Vec<SMF> = SMF::read_xmi(&mut Cursor::new(xmi())).unwrap();
+    assert_eq!(files.len(), 1);
+    let smf: &SMF = &files[0];
+    assert_eq!(smf.ticks_per_quarter_note().unwrap(), 60);
+    let events: Vec<(u64, String)> = smf.tracks[0].absolute_events().into_iter().map(|(tick, e)| (tick, match e.event {
+        EventType::MidiEvent(ref m) => match m.event {
+            MidiEventType::NoteOn(ref n) => format!("on {}", n.key),
+            MidiEventType::NoteOff(ref n) => format!("off {}", n.key),
+            MidiEventType::ControllerChange(ref c) => format!("cc {} {}", c.controller_number, c.controller_value),
+            _ => String::from("midi")
+        },
+        EventType::MetaEvent(ref m) => match m.event {
+            MetaEventType::SetTempo(ref t) => format!("tempo {}", t.tempo),
+            MetaEventType::Marker(ref t) => t.text.clone(),
+            MetaEventType::EndOfTrack(_) => String::from("end"),
+            _ => String::from("meta")
+        },
+        _ => String::from("sysex")
+    })).collect();
+    let expected: Vec<(u64, &str)> = vec![
+        (0, "tempo 500000"), (0, "loopStart"), (0, "cc 116 2"), (0, "on 60"),
+        (60, "off 60"), (60, "loopEnd"), (60, "cc 117 0"), (60, "end")
+    ];
+    assert_eq!(events, expected.into_iter().map(|(t, s)| (t, String::from(s))).collect::<Vec<(u64, String)>>());
+}
+
+#[test]
+fn xmi_loop_keeps_its_count() {
+    let smf: SMF = SMF::read_xmi(&mut Cursor::new(xmi())).unwrap().remove(0);
+    let points = smf.detect_loop().unwrap();
+    assert!(points.convention == LoopConvention::EMIDI);
+    assert_eq!((points.start, points.end, points.count), (0, 60, Some(2)));
+}
+
+#[test]
+fn not_xmi() {
+    assert!(SMF::read_xmi(&mut Cursor::new(chunk(b"FORM", b"AIFF"))).is_err());
+}